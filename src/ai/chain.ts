@@ -0,0 +1,232 @@
+// Provider chain - tries the primary provider, then each fallback in order
+// Stops at the first provider that returns a response
+
+import {
+  AIProvider,
+  AILogger,
+  ChatRequest,
+  ChatResponse,
+  AIError,
+  AIConnectionError,
+  ProviderCapabilities,
+  getCapabilities,
+  isAuthError,
+  isModelError,
+  CancelledError,
+  RequestDumpedError,
+  throwIfCancelled,
+} from './types.js'
+
+/**
+ * A provider in the chain together with the model to request from it
+ */
+export interface ChainEntry {
+  provider: AIProvider
+  model: string
+  /** Models to try on the same provider after a model-specific error */
+  fallbackModels?: string[]
+}
+
+/**
+ * Called before each provider attempt
+ * `attempt` is 1-based; `total` is the number of providers in the chain
+ */
+export type ChainProgressCallback = (
+  provider: string,
+  attempt: number,
+  total: number
+) => void
+
+/**
+ * Resolve after `ms`, or reject with CancelledError (clearing the timer)
+ * as soon as the signal fires
+ */
+function sleep(ms: number, signal?: AbortSignal): Promise<void> {
+  throwIfCancelled(signal)
+  return new Promise((resolve, reject) => {
+    const onAbort = () => {
+      clearTimeout(timer)
+      reject(new CancelledError())
+    }
+    const timer = setTimeout(() => {
+      signal?.removeEventListener('abort', onAbort)
+      resolve()
+    }, ms)
+    signal?.addEventListener('abort', onAbort, { once: true })
+  })
+}
+
+/**
+ * Spaces out requests so they start at least `minIntervalMs` apart
+ * (provider.min-interval-ms); avoids self-inflicted 429s in --batch
+ */
+export class RequestThrottle {
+  private lastStart?: number
+
+  /**
+   * Wait until `minIntervalMs` has passed since the previous request
+   * The start slot is reserved before sleeping, so concurrent callers
+   * (--batch --concurrency) queue up instead of starting together
+   * Rejects with CancelledError if the signal fires while waiting
+   */
+  async wait(minIntervalMs: number, signal?: AbortSignal): Promise<void> {
+    const now = Date.now()
+    const start =
+      minIntervalMs > 0 && this.lastStart !== undefined
+        ? Math.max(now, this.lastStart + minIntervalMs)
+        : now
+    this.lastStart = start
+    if (start > now) {
+      await sleep(start - now, signal)
+    }
+  }
+}
+
+/**
+ * Throttle shared by every chain in the process, so batch lines that each
+ * build their own chain are still spaced out
+ */
+export const sharedRequestThrottle = new RequestThrottle()
+
+/**
+ * Chain behavior options
+ */
+export interface ProviderChainOptions {
+  /** Stop at the first authentication error instead of trying fallbacks */
+  stopOnAuthError?: boolean
+  /** Minimum time between request starts (0 = no throttle) */
+  minIntervalMs?: number
+  /** Throttle to use (defaults to the process-wide one) */
+  throttle?: RequestThrottle
+  logger?: AILogger
+  /** Progress hook (e.g. spinner text) invoked before each attempt */
+  onAttempt?: ChainProgressCallback
+}
+
+/**
+ * Provider that delegates to a list of providers in order
+ * The returned response's `provider` field names the provider that answered
+ */
+export class ProviderChain implements AIProvider {
+  name: string
+  private entries: ChainEntry[]
+  private logger?: AILogger
+  private stopOnAuthError: boolean
+  private onAttempt?: ChainProgressCallback
+  private minIntervalMs: number
+  private throttle: RequestThrottle
+
+  constructor(entries: ChainEntry[], options: ProviderChainOptions = {}) {
+    if (entries.length === 0) {
+      throw new AIError('Provider chain is empty')
+    }
+    this.entries = entries
+    this.logger = options.logger
+    this.stopOnAuthError = options.stopOnAuthError ?? false
+    this.onAttempt = options.onAttempt
+    this.minIntervalMs = options.minIntervalMs ?? 0
+    this.throttle = options.throttle ?? sharedRequestThrottle
+    this.name = entries[0]!.provider.name
+  }
+
+  /**
+   * Available if any provider in the chain is available
+   */
+  isAvailable(): boolean {
+    return this.entries.some((entry) => entry.provider.isAvailable())
+  }
+
+  /**
+   * Only report features every provider in the chain supports,
+   * since any of them may end up serving the request
+   */
+  capabilities(): ProviderCapabilities {
+    const all = this.entries.map((entry) => getCapabilities(entry.provider))
+    return {
+      supportsStreaming: all.every((caps) => caps.supportsStreaming),
+      supportsN: all.every((caps) => caps.supportsN),
+      supportsJsonMode: all.every((caps) => caps.supportsJsonMode),
+      supportsReasoningEffort: all.every(
+        (caps) => caps.supportsReasoningEffort
+      ),
+    }
+  }
+
+  /**
+   * Send the request to each provider in turn until one succeeds
+   * Each provider receives its own model; the last error is rethrown
+   * A model-specific error moves on to the entry's next fallback model
+   * before trying the next provider
+   * With stopOnAuthError, an auth failure is rethrown immediately
+   * Cancellation is never retried: CancelledError is rethrown as-is,
+   * and so is RequestDumpedError (nothing was sent)
+   * Every attempt, fallbacks included, waits for the throttle first
+   * If no provider in a multi-provider chain could be reached, the error
+   * lists each provider with its network error instead
+   * `onAttempt` overrides the callback given at construction
+   */
+  async complete(
+    request: ChatRequest,
+    signal?: AbortSignal,
+    onAttempt: ChainProgressCallback | undefined = this.onAttempt
+  ): Promise<ChatResponse> {
+    let lastError: unknown
+    const failures: { provider: string; error: unknown }[] = []
+
+    for (const [index, entry] of this.entries.entries()) {
+      const { provider, model } = entry
+      if (index > 0) {
+        this.logger?.info(
+          `Falling back to provider '${provider.name}' model '${model}'`
+        )
+      }
+      throwIfCancelled(signal)
+      onAttempt?.(provider.name, index + 1, this.entries.length)
+
+      const models = [model, ...(entry.fallbackModels ?? [])]
+      for (const [modelIndex, current] of models.entries()) {
+        if (modelIndex > 0) {
+          this.logger?.info(`Falling back to model '${current}'`)
+        }
+
+        try {
+          await this.throttle.wait(this.minIntervalMs, signal)
+          const response = await provider.complete(
+            { ...request, model: current },
+            signal
+          )
+          return { ...response, provider: response.provider ?? provider.name }
+        } catch (err) {
+          if (
+            err instanceof CancelledError ||
+            err instanceof RequestDumpedError
+          ) {
+            throw err
+          }
+          if (this.stopOnAuthError && isAuthError(err)) {
+            throw err
+          }
+          lastError = err
+          if (!isModelError(err)) {
+            break
+          }
+        }
+      }
+      failures.push({ provider: provider.name, error: lastError })
+    }
+
+    if (
+      failures.length > 1 &&
+      failures.every(({ error }) => error instanceof AIConnectionError)
+    ) {
+      const lines = failures.map(
+        ({ provider, error }) => `  ${provider}: ${(error as Error).message}`
+      )
+      throw new AIConnectionError(
+        `No AI provider could be reached:\n${lines.join('\n')}`
+      )
+    }
+
+    throw lastError
+  }
+}
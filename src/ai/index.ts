@@ -2,56 +2,349 @@
 // Provides generateCommands() for converting natural language to shell commands
 
 import { ContextData } from '../context/types.js'
-import { getProviderApiKey, getProviderModel } from '../config/index.js'
+import {
+  ConfigError,
+  getProviderApiKey,
+  getProviderModel,
+  resolveProviderName,
+} from '../config/index.js'
 import type { Config } from '../config/types.js'
 import {
   ChatMessage,
   ChatRequest,
   AIError,
   AIProvider,
+  AILogger,
+  getCapabilities,
 } from './types.js'
-import { buildPrompt } from './prompt.js'
-import { parseResponse } from './parser.js'
+import { ProviderChain, ChainEntry, ChainProgressCallback } from './chain.js'
+import {
+  buildPrompt,
+  buildExplainDangerPrompt,
+  buildExplainCommandPrompt,
+  buildJsonRetryMessage,
+  getPromptOptions,
+} from './prompt.js'
+import {
+  parseResponse,
+  extractCommandList,
+  stripReasoningTags,
+} from './parser.js'
 import { OpenRouterProvider } from './providers/index.js'
 import { MockProvider } from './mock.js'
 
 // Re-export types for consumers
 export {
   AIError,
+  AITimeoutError,
+  AIConnectionError,
+  CancelledError,
+  RequestDumpedError,
+  DEFAULT_CAPABILITIES,
+  getCapabilities,
+  isAuthError,
+  isModelError,
+  REASONING_EFFORTS,
 } from './types.js'
 export type {
   ChatMessage,
   ChatRequest,
   ChatResponse,
   AIProvider,
+  AILogger,
+  ProviderCapabilities,
+  ReasoningEffort,
 } from './types.js'
-export { buildPrompt } from './prompt.js'
-export { parseResponse } from './parser.js'
-export { OpenRouterProvider } from './providers/index.js'
+export {
+  ProviderChain,
+  RequestThrottle,
+  sharedRequestThrottle,
+} from './chain.js'
+export type {
+  ChainEntry,
+  ChainProgressCallback,
+  ProviderChainOptions,
+} from './chain.js'
+export {
+  buildPrompt,
+  buildExplainDangerPrompt,
+  buildExplainCommandPrompt,
+  buildJsonRetryMessage,
+  getPromptOptions,
+  applyInstructionAffixes,
+  resolveSectionOrder,
+  PROMPT_SECTIONS,
+} from './prompt.js'
+export type { PromptOptions, PromptSection } from './prompt.js'
+export {
+  parseResponse,
+  extractCommandList,
+  stripReasoningTags,
+  StreamingExtractor,
+} from './parser.js'
+export { OpenRouterProvider, formatRequestDump } from './providers/index.js'
+export type { OpenRouterOptions } from './providers/index.js'
 export { MockProvider } from './mock.js'
 
+/**
+ * Generated commands together with the response they were extracted from
+ */
+export interface GeneratedCommands {
+  commands: string[]
+  /** Model output exactly as received (all choices when using native n) */
+  raw: string
+  /** Provider and model that produced the response */
+  provider: string
+  model: string
+  /** Tokens used across all requests (JSON retries included), if reported */
+  totalTokens?: number
+}
+
 /**
  * Generate shell commands from natural language instruction
  *
  * @param context - Gathered context (system, directory, history, stdin)
  * @param instruction - User's natural language instruction
  * @param config - Runtime configuration
+ * @param logger - Optional logger for provider/model progress (-v)
+ * @param onAttempt - Optional callback invoked before each provider attempt
+ * @param signal - Optional signal to cancel the request
  * @returns Array of command strings (1 for single mode, N for multi mode)
  * @throws AIError on API failure or parse error (exit code 4)
+ * @throws CancelledError if `signal` fires first (exit code 130)
  */
 export async function generateCommands(
   context: ContextData,
   instruction: string,
-  config: Config
+  config: Config,
+  logger?: AILogger,
+  onAttempt?: ChainProgressCallback,
+  signal?: AbortSignal
 ): Promise<string[]> {
-  const providerName = config.providerName || config.provider.default
-  const numOptions = config.ui.numOptions
+  const { commands } = await generateCommandsWithRaw(
+    context,
+    instruction,
+    config,
+    logger,
+    onAttempt,
+    signal
+  )
+  return commands
+}
+
+/**
+ * Like generateCommands, but also returns the raw model output (--show-raw)
+ */
+export async function generateCommandsWithRaw(
+  context: ContextData,
+  instruction: string,
+  config: Config,
+  logger?: AILogger,
+  onAttempt?: ChainProgressCallback,
+  signal?: AbortSignal
+): Promise<GeneratedCommands> {
+  // Primary provider followed by configured fallbacks
+  const provider = getProviderChain(config, logger, onAttempt)
+
+  return generateWithProvider(
+    provider,
+    context,
+    instruction,
+    config,
+    logger,
+    signal
+  )
+}
+
+/** Providers getProvider can construct */
+const SUPPORTED_PROVIDERS = ['openrouter']
+
+/**
+ * Result of the local checks for one configured provider
+ * `problem` is unset when the provider looks usable
+ */
+export interface ProviderStatus {
+  name: string
+  problem?: string
+}
+
+/**
+ * Check the selected provider and each fallback without sending requests
+ * Catches unknown provider names and missing API keys; whether an
+ * endpoint is reachable only shows up when the request is made
+ *
+ * @param config - Runtime configuration
+ * @returns One status per provider, primary first
+ */
+export function checkProviders(config: Config): ProviderStatus[] {
+  const primary = resolveProviderName(config)
+  const names = [...new Set([primary, ...config.provider.fallback])]
+
+  return names.map((name) => {
+    if (process.env.MOCK_AI === '1') {
+      return { name }
+    }
+    if (!SUPPORTED_PROVIDERS.includes(name)) {
+      return {
+        name,
+        problem: `unknown provider (supported: ${SUPPORTED_PROVIDERS.join(', ')})`,
+      }
+    }
+    if (!getProviderApiKey(name, config)) {
+      const envVar =
+        name === primary && config.apiKeyEnv
+          ? config.apiKeyEnv
+          : `${name.toUpperCase()}_API_KEY`
+      return {
+        name,
+        problem: `no API key (set ${envVar} or ${name}.api-key in .clai.toml)`,
+      }
+    }
+    return { name }
+  })
+}
+
+/**
+ * Message for an unusable primary provider, listing every provider's status
+ */
+function formatProviderStatuses(statuses: ProviderStatus[]): string {
+  const lines = statuses.map(
+    ({ name, problem }) => `  ${name}: ${problem ?? 'ok'}`
+  )
+  return (
+    `AI provider '${statuses[0]!.name}' is not usable. ` +
+    `Configured providers:\n${lines.join('\n')}`
+  )
+}
+
+/**
+ * Describe which provider and model a run would use (--model-info)
+ * Applies the same resolution as getProviderChain (-p, model-provider-hints,
+ * per-provider models, defaults) without constructing providers
+ *
+ * @param config - Runtime configuration
+ * @returns One line per chain entry, primary first, newline-terminated
+ */
+export function formatModelInfo(config: Config): string {
+  const primary = resolveProviderName(config)
+  const lines = [
+    `Provider: ${primary}`,
+    `Model: ${getProviderModel(primary, config)}`,
+  ]
+
+  for (const model of config.modelFallback) {
+    lines.push(`Fallback model: ${model}`)
+  }
+  for (const name of new Set(config.provider.fallback)) {
+    if (name !== primary) {
+      lines.push(`Fallback: ${name} ${getProviderModel(name, config)}`)
+    }
+  }
+
+  return lines.join('\n') + '\n'
+}
+
+/**
+ * Build the provider chain: the selected provider, then each fallback
+ * Fallbacks that can't be constructed (unknown, no API key) are skipped;
+ * an unusable primary provider is still an error
+ *
+ * @throws ConfigError listing each provider's status if the primary
+ *   provider is unknown or has no API key (exit code 3)
+ */
+export function getProviderChain(
+  config: Config,
+  logger?: AILogger,
+  onAttempt?: ChainProgressCallback
+): ProviderChain {
+  const primary = resolveProviderName(config)
+  const names = [...new Set([primary, ...config.provider.fallback])]
+
+  const statuses = checkProviders(config)
+  if (statuses[0]?.problem) {
+    throw new ConfigError(formatProviderStatuses(statuses))
+  }
 
-  // Get appropriate provider
-  const provider = getProvider(providerName, config)
+  const entries: ChainEntry[] = [
+    {
+      provider: getProvider(primary, config),
+      model: getProviderModel(primary, config),
+      fallbackModels: config.modelFallback,
+    },
+  ]
 
-  // Build prompt messages
-  const messages = buildPrompt(context, instruction, numOptions)
+  for (const name of names.slice(1)) {
+    try {
+      entries.push({
+        provider: getProvider(name, config),
+        model: getProviderModel(name, config),
+      })
+    } catch {
+      logger?.info(`Skipping unavailable fallback provider '${name}'`)
+    }
+  }
+
+  return new ProviderChain(entries, {
+    logger,
+    stopOnAuthError: config.provider.stopOnAuthError,
+    minIntervalMs: config.provider.minIntervalMs,
+    onAttempt,
+  })
+}
+
+/**
+ * Generate commands using an already-constructed provider
+ * Picks the request strategy from the provider's reported capabilities:
+ * - supportsN: ask for N single-command completions via `n`
+ * - otherwise: ask for a JSON array of N commands in one completion
+ *
+ * @param provider - Provider to send the request to
+ * @param context - Gathered context
+ * @param instruction - User's natural language instruction
+ * @param config - Runtime configuration
+ * @param logger - Optional logger for provider/model progress (-v)
+ * @param signal - Optional signal to cancel the request
+ * @returns Array of command strings
+ */
+export async function generateCommandsWithProvider(
+  provider: AIProvider,
+  context: ContextData,
+  instruction: string,
+  config: Config,
+  logger?: AILogger,
+  signal?: AbortSignal
+): Promise<string[]> {
+  const { commands } = await generateWithProvider(
+    provider,
+    context,
+    instruction,
+    config,
+    logger,
+    signal
+  )
+  return commands
+}
+
+async function generateWithProvider(
+  provider: AIProvider,
+  context: ContextData,
+  instruction: string,
+  config: Config,
+  logger?: AILogger,
+  signal?: AbortSignal
+): Promise<GeneratedCommands> {
+  const providerName = resolveProviderName(config)
+  const numOptions = config.ui.numOptions
+  const capabilities = getCapabilities(provider)
+  const useNativeN = numOptions > 1 && capabilities.supportsN
+
+  // Build prompt messages (single-command prompt when using native n)
+  const messages = buildPrompt(
+    context,
+    instruction,
+    useNativeN ? 1 : numOptions,
+    getPromptOptions(config)
+  )
 
   // Get model (from CLI, config, or default)
   const model = getProviderModel(providerName, config)
@@ -63,10 +356,168 @@ export async function generateCommands(
     temperature: 0.1, // Low temperature for more deterministic commands
   }
 
-  const response = await provider.complete(request)
+  if (useNativeN) {
+    request.n = numOptions
+    // Independent samples need some variety to be useful as alternatives
+    request.temperature = 0.7
+  } else if (numOptions > 1 && capabilities.supportsJsonMode) {
+    request.jsonMode = true
+  }
+
+  const reasoningEffort = config.provider.reasoningEffort
+  if (reasoningEffort && capabilities.supportsReasoningEffort) {
+    request.reasoningEffort = reasoningEffort
+  } else if (reasoningEffort) {
+    logger?.info(
+      `Provider '${provider.name}' does not support reasoning effort; ignoring it`
+    )
+  }
+
+  logger?.info(`Using provider '${provider.name}' model '${model}'`)
+
+  let response = await provider.complete(request, signal)
+  let totalTokens = response.usage?.totalTokens
+
+  const stripReasoning = config.prompt.stripReasoningTags !== false
+
+  // Weaker models sometimes answer the JSON prompt with prose; ask again
+  const expectJson = numOptions > 1 && !useNativeN
+  const maxRetries = config.provider.jsonRetries ?? 0
+  let retries = 0
+  while (
+    expectJson &&
+    retries < maxRetries &&
+    extractCommandList(response.content, config.strictJson, stripReasoning)
+      .length === 0
+  ) {
+    retries++
+    logger?.info('Response was not valid JSON, retrying with a stricter prompt')
+    request.messages = [
+      ...request.messages,
+      { role: 'assistant', content: response.content },
+      buildJsonRetryMessage(numOptions),
+    ]
+    response = await provider.complete(request, signal)
+    if (response.usage) {
+      totalTokens = (totalTokens ?? 0) + response.usage.totalTokens
+    }
+  }
+
+  logger?.info(
+    `Response from provider '${response.provider ?? provider.name}'` +
+      (response.model ? ` model '${response.model}'` : '')
+  )
+
+  const source = {
+    provider: response.provider ?? provider.name,
+    model: response.model ?? model,
+    totalTokens,
+  }
+
+  if (useNativeN && response.choices && response.choices.length > 1) {
+    const commands = response.choices.flatMap((choice) =>
+      parseResponse(choice, false, false, stripReasoning)
+    )
+    return {
+      commands: [...new Set(commands)],
+      raw: response.choices.join('\n\n'),
+      ...source,
+    }
+  }
 
   // Parse response into command(s)
-  return parseResponse(response.content, numOptions > 1)
+  return {
+    commands: parseResponse(
+      response.content,
+      expectJson,
+      config.strictJson,
+      stripReasoning
+    ),
+    raw: response.content,
+    ...source,
+  }
+}
+
+// Explanations only need a sentence
+const EXPLAIN_DANGER_MAX_TOKENS = 80
+
+/**
+ * Ask the AI for a one-sentence explanation of why a command is risky
+ * Goes through the provider chain (fallbacks, model fallback, throttle)
+ * Fails open: any error returns null so the normal confirmation still runs
+ *
+ * @param command - Command flagged as dangerous
+ * @param config - Runtime configuration
+ * @returns Explanation text, or null if unavailable
+ */
+export async function explainDanger(
+  command: string,
+  config: Config
+): Promise<string | null> {
+  try {
+    const providerName = resolveProviderName(config)
+    const provider = getProviderChain(config)
+
+    const request: ChatRequest = {
+      model: getProviderModel(providerName, config),
+      messages: buildExplainDangerPrompt(command),
+      temperature: 0,
+      maxTokens: EXPLAIN_DANGER_MAX_TOKENS,
+    }
+
+    const response = await provider.complete(request)
+    const explanation = response.content.trim()
+    return explanation || null
+  } catch {
+    // Explanation is best-effort; the confirmation prompt still protects the user
+    return null
+  }
+}
+
+// Room for a few lines per flag without running away
+const EXPLAIN_COMMAND_MAX_TOKENS = 600
+
+/**
+ * Ask the AI to explain an existing command (--explain-only)
+ * Unlike explainDanger this is the primary output, so errors propagate
+ *
+ * @param command - Command to explain
+ * @param config - Runtime configuration (provider chain, fallbacks)
+ * @param logger - Optional logger for fallback progress
+ * @param onAttempt - Progress hook invoked before each provider attempt
+ * @param signal - Cancels the request when fired
+ * @returns Explanation text
+ * @throws AIError if every provider fails or the reply is empty
+ */
+export async function explainCommand(
+  command: string,
+  config: Config,
+  logger?: AILogger,
+  onAttempt?: ChainProgressCallback,
+  signal?: AbortSignal
+): Promise<string> {
+  const providerName = resolveProviderName(config)
+  const provider = getProviderChain(config, logger, onAttempt)
+
+  const response = await provider.complete(
+    {
+      model: getProviderModel(providerName, config),
+      messages: buildExplainCommandPrompt(command),
+      temperature: 0,
+      maxTokens: EXPLAIN_COMMAND_MAX_TOKENS,
+    },
+    signal
+  )
+
+  const content =
+    config.prompt.stripReasoningTags !== false
+      ? stripReasoningTags(response.content)
+      : response.content
+  const explanation = content.trim()
+  if (!explanation) {
+    throw new AIError('AI returned an empty explanation')
+  }
+  return explanation
 }
 
 /**
@@ -91,7 +542,14 @@ function getProvider(name: string, config: Config): AIProvider {
       )
     }
 
-    return new OpenRouterProvider(apiKey)
+    const providerConfig = config.providers.openrouter
+    return new OpenRouterProvider(apiKey, {
+      referer: providerConfig?.referer,
+      title: providerConfig?.title,
+      extraParams: providerConfig?.extraParams,
+      userAgent: config.provider.userAgent,
+      dumpRequest: config.dumpRequest,
+    })
   }
 
   throw new AIError(
@@ -25,6 +25,15 @@ export class MockProvider implements AIProvider {
   async complete(request: ChatRequest): Promise<ChatResponse> {
     const systemMsg = request.messages[0]?.content || ''
     const isMultiCommand = systemMsg.includes('JSON')
+    const userMsg = request.messages[1]?.content || ''
+
+    // --explain-only
+    if (userMsg.startsWith('Explain this command: ')) {
+      return {
+        content: `Mock explanation of: ${userMsg.slice('Explain this command: '.length)}`,
+        model: 'mock',
+      }
+    }
 
     if (isMultiCommand) {
       // Extract number of commands from system message
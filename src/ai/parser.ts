@@ -6,18 +6,28 @@ import { AIError } from './types.js'
  * Parse AI response content into command(s)
  *
  * Handles:
+ * - Reasoning blocks (<think>...</think>, <reasoning>, <|...|> tokens)
  * - Markdown code fence stripping (```bash, ```sh, ```shell, ```json, ```)
  * - JSON parsing for multi-command mode: {"commands": [...]} or [...]
+ * - Prose around a single command ("Here's the command: ls -la")
  * - Fallback to single trimmed command
  *
+ * With `strict` (--strict-json), a multi-command response must be exactly
+ * a {"commands": [...]} object (code fences allowed): no JSON-in-text
+ * extraction and no single-command fallback
+ *
  * @param content - Raw response content from AI
  * @param expectMultiple - Whether to expect multiple commands (numOptions > 1)
+ * @param strict - Reject multi-command responses that aren't clean JSON
+ * @param stripReasoning - Remove reasoning blocks (prompt.strip-reasoning-tags)
  * @returns Array of command strings
  * @throws AIError if response is empty or cannot be parsed
  */
 export function parseResponse(
   content: string,
-  expectMultiple: boolean
+  expectMultiple: boolean,
+  strict: boolean = false,
+  stripReasoning: boolean = true
 ): string[] {
   // 1. Trim whitespace
   let cleaned = content.trim()
@@ -26,14 +36,25 @@ export function parseResponse(
     throw new AIError('AI returned empty response')
   }
 
-  // 2. Strip markdown code fences
-  cleaned = stripCodeFences(cleaned)
+  // 2. Strip reasoning blocks and markdown code fences
+  cleaned = stripResponse(cleaned, stripReasoning)
 
   // Check if empty after stripping fences
   if (!cleaned) {
     throw new AIError('AI returned empty response after parsing')
   }
 
+  // 3. Strict multi-command mode: clean JSON or an error
+  if (expectMultiple && strict) {
+    const commands = parseStrictCommandList(cleaned)
+    if (commands.length === 0) {
+      throw new AIError(
+        'Model did not return a JSON object with a "commands" array (--strict-json)'
+      )
+    }
+    return commands
+  }
+
   // 3. Try JSON parse for multi-command mode
   if (expectMultiple) {
     const commands = tryParseMultipleCommands(cleaned)
@@ -42,8 +63,8 @@ export function parseResponse(
     }
   }
 
-  // 4. Fallback: treat as single command
-  cleaned = cleaned.trim()
+  // 4. Fallback: treat as single command, minus any explanation around it
+  cleaned = extractFromProse(cleaned.trim())
   if (!cleaned) {
     throw new AIError('AI returned empty response after parsing')
   }
@@ -51,6 +72,78 @@ export function parseResponse(
   return [cleaned]
 }
 
+/**
+ * Extract a JSON command list from a response, without any fallback
+ * Used to detect a malformed multi-command response
+ *
+ * @param content - Raw response content from AI
+ * @returns Parsed commands, or an empty array if no JSON list was found
+ */
+export function extractCommandList(
+  content: string,
+  strict: boolean = false,
+  stripReasoning: boolean = true
+): string[] {
+  const cleaned = stripResponse(content.trim(), stripReasoning)
+  return strict
+    ? parseStrictCommandList(cleaned)
+    : tryParseMultipleCommands(cleaned)
+}
+
+/**
+ * Parse exactly {"commands": [...]} with non-empty string entries
+ * Returns empty array for anything else
+ */
+function parseStrictCommandList(content: string): string[] {
+  let parsed: unknown
+  try {
+    parsed = JSON.parse(content)
+  } catch {
+    return []
+  }
+
+  const commands = (parsed as { commands?: unknown } | null)?.commands
+  if (
+    !Array.isArray(commands) ||
+    commands.length === 0 ||
+    !commands.every((c) => typeof c === 'string' && c.trim())
+  ) {
+    return []
+  }
+  return commands.map((c: string) => c.trim())
+}
+
+// Reasoning models wrap their thinking in these before the answer
+const REASONING_BLOCK = /<(think|thinking|reasoning)>[\s\S]*?<\/\1>/gi
+
+// Some models omit the opening tag: everything up to a stray closing tag
+const REASONING_PREFIX = /^[\s\S]*?<\/(?:think|thinking|reasoning)>/i
+
+// Chat-template special tokens, e.g. <|im_end|> or <|end|>
+const SPECIAL_TOKEN = /<\|[^|<>]*\|>/g
+
+/**
+ * Remove reasoning blocks and special tokens from a response
+ *
+ * @param content - Response content
+ * @returns Content with reasoning removed, trimmed
+ */
+export function stripReasoningTags(content: string): string {
+  return content
+    .replace(REASONING_BLOCK, '')
+    .replace(REASONING_PREFIX, '')
+    .replace(SPECIAL_TOKEN, '')
+    .trim()
+}
+
+// Reasoning can sit outside or inside a code fence, so strip both sides
+function stripResponse(content: string, stripReasoning: boolean): string {
+  if (!stripReasoning) {
+    return stripCodeFences(content)
+  }
+  return stripReasoningTags(stripCodeFences(stripReasoningTags(content)))
+}
+
 /**
  * Strip markdown code fences from content
  * Handles multiple fence formats
@@ -80,6 +173,46 @@ function stripCodeFences(content: string): string {
   return content
 }
 
+// Lead-ins models put before the command despite being told not to,
+// e.g. "Here's the command:" or "Sure! Run this:"; the colon is required
+const PROSE_LEAD_IN =
+  /^(?:(?:sure|ok(?:ay)?|certainly)[,!.]?\s+)?(?:here(?:'s| is)(?: the| a| your)?(?: shell)?(?: command| one-liner)?|the command(?: you need)? is|(?:you can )?(?:run|use|try)(?: this| the following)?(?: command)?|command)\s*:\s*/i
+
+// A fenced block anywhere in the response, on its own lines
+const EMBEDDED_FENCE = /(?:^|\n)```[\w-]*[ \t]*\n([\s\S]*?)\n```[ \t]*(?=\n|$)/g
+
+/**
+ * Pull a single command out of explanation the model wrapped around it
+ * Deliberately conservative, anything else is returned unchanged:
+ * - exactly one fenced block with prose before it: the block's content
+ * - a known lead-in ending in a colon, then one line: that line, without
+ *   surrounding backticks
+ */
+function extractFromProse(content: string): string {
+  const fences = [...content.matchAll(EMBEDDED_FENCE)]
+  const fence = fences.length === 1 ? fences[0] : undefined
+  if (fence && content.slice(0, fence.index).trim()) {
+    const body = fence[1]?.trim()
+    if (body) {
+      return body
+    }
+  }
+
+  const leadIn = content.match(PROSE_LEAD_IN)
+  if (leadIn) {
+    const rest = content.slice(leadIn[0].length).trim()
+    const quoted = rest.match(/^`([^`\n]+)`\.?$/)
+    if (quoted?.[1]) {
+      return quoted[1].trim()
+    }
+    if (rest && !rest.includes('\n')) {
+      return rest
+    }
+  }
+
+  return content
+}
+
 /**
  * Try to parse multiple commands from JSON
  * Returns empty array if parsing fails
@@ -133,3 +266,101 @@ function tryParseMultipleCommands(content: string): string[] {
 
   return []
 }
+
+type StreamState = 'start' | 'opening' | 'body' | 'plain'
+
+// Trailing text that may still be the closing fence, or whitespace the
+// one-shot parse trims; held back until later chunks settle it
+const FENCED_TAIL = /\s*(?:(?:^|\n)`{1,3}\s*)?$/
+const PLAIN_TAIL = /\s*$/
+
+/**
+ * Incremental counterpart of parseResponse for live display of a
+ * streamed single-command response
+ *
+ * Drops the opening ```lang line (even when it spans chunks) and holds
+ * back anything that may turn out to be the closing fence, so the
+ * concatenated output of push() and finish() equals parseResponse's
+ * result for plain and well-formed fenced responses
+ *
+ * Reasoning tags are not stripped, and text already emitted can't be
+ * taken back if a fence never closes; parse the buffered response with
+ * parseResponse for the final command
+ */
+export class StreamingExtractor {
+  private state: StreamState = 'start'
+  private pending = ''
+  private started = false
+
+  /**
+   * Consume the next chunk
+   *
+   * @param chunk - Text received from the stream
+   * @returns Cleaned text that is safe to display now (may be empty)
+   */
+  push(chunk: string): string {
+    this.pending += chunk
+
+    if (this.state === 'start') {
+      const text = this.pending.trimStart()
+      // Not enough yet to tell a fence from a plain response
+      if (text === '' || (text.length < 3 && '```'.startsWith(text))) {
+        return ''
+      }
+      this.pending = text
+      this.state = text.startsWith('```') ? 'opening' : 'plain'
+    }
+
+    if (this.state === 'opening') {
+      const newline = this.pending.indexOf('\n')
+      if (newline === -1) {
+        return ''
+      }
+      this.pending = this.pending.slice(newline + 1)
+      this.state = 'body'
+    }
+
+    return this.release(this.state === 'body' ? FENCED_TAIL : PLAIN_TAIL)
+  }
+
+  /**
+   * Flush whatever was held back once the stream has ended
+   *
+   * @returns Remaining cleaned text (may be empty)
+   */
+  finish(): string {
+    const rest = this.pending
+    this.pending = ''
+
+    switch (this.state) {
+      case 'start':
+        return rest.trim()
+      case 'opening':
+        // Single line: ```ls -la```
+        return stripCodeFences(rest.trim())
+      case 'body':
+        if (
+          /^\s*\n```\s*$/.test(rest) ||
+          (!this.started && /^```\s*$/.test(rest))
+        ) {
+          return ''
+        }
+        return rest.trimEnd()
+      case 'plain':
+        return rest.trimEnd()
+    }
+  }
+
+  private release(tail: RegExp): string {
+    if (!this.started) {
+      this.pending = this.pending.trimStart()
+    }
+    const end = this.pending.search(tail)
+    const ready = this.pending.slice(0, end)
+    this.pending = this.pending.slice(end)
+    if (ready) {
+      this.started = true
+    }
+    return ready
+  }
+}
@@ -1,8 +1,79 @@
 // Prompt builder for AI requests
 
-import { ContextData } from '../context/types.js'
+import { ContextData, ALL_CONTEXT_SECTIONS } from '../context/types.js'
+import { formatSystemInfo } from '../context/system.js'
+import type { Config } from '../config/types.js'
 import { ChatMessage } from './types.js'
 
+/** Sections of the user prompt, in the default order */
+export const PROMPT_SECTIONS = [
+  'system',
+  'directory',
+  'history',
+  'stdin',
+  'instruction',
+] as const
+export type PromptSection = (typeof PROMPT_SECTIONS)[number]
+
+/**
+ * Optional prompt customizations (from the [prompt] config section)
+ */
+export interface PromptOptions {
+  /** Text prepended to every instruction */
+  instructionPrefix?: string
+  /** Text appended to every instruction */
+  instructionSuffix?: string
+  /** Section names in the order to assemble them (prompt.section-order) */
+  sectionOrder?: readonly string[]
+}
+
+/**
+ * Turn prompt.section-order into the sections to assemble, in order
+ * Unknown names and repeats are skipped, as are sections left out of the
+ * list; the instruction is always included (last, if not listed)
+ *
+ * @param order - Configured order, or undefined for the default
+ */
+export function resolveSectionOrder(
+  order?: readonly string[]
+): PromptSection[] {
+  if (!order) {
+    return [...PROMPT_SECTIONS]
+  }
+  const sections = [
+    ...new Set(
+      order.filter((name): name is PromptSection =>
+        (PROMPT_SECTIONS as readonly string[]).includes(name)
+      )
+    ),
+  ]
+  if (!sections.includes('instruction')) {
+    sections.push('instruction')
+  }
+  return sections
+}
+
+/**
+ * Derive prompt options from runtime config
+ */
+export function getPromptOptions(config: Config): PromptOptions {
+  return {
+    instructionPrefix: config.prompt.instructionPrefix,
+    instructionSuffix: config.prompt.instructionSuffix,
+    sectionOrder: config.prompt.sectionOrder,
+  }
+}
+
+/**
+ * Wrap the instruction with the configured prefix/suffix
+ */
+export function applyInstructionAffixes(
+  instruction: string,
+  options: PromptOptions = {}
+): string {
+  return `${options.instructionPrefix ?? ''}${instruction}${options.instructionSuffix ?? ''}`
+}
+
 /**
  * Build chat messages for AI request
  * Constructs system and user messages based on context and instruction
@@ -10,12 +81,14 @@ import { ChatMessage } from './types.js'
  * @param context - Gathered system/directory/history/stdin context
  * @param instruction - User's natural language instruction
  * @param numOptions - Number of command options to generate (1 for single, >1 for multi)
+ * @param options - Optional prompt customizations
  * @returns Array of chat messages for the AI
  */
 export function buildPrompt(
   context: ContextData,
   instruction: string,
-  numOptions: number
+  numOptions: number,
+  options: PromptOptions = {}
 ): ChatMessage[] {
   const isMultiCommand = numOptions > 1
 
@@ -25,7 +98,12 @@ export function buildPrompt(
     : buildSingleCommandSystemPrompt()
 
   // User message includes context and instruction
-  const userMessage = buildUserPrompt(context, instruction, numOptions)
+  const userMessage = buildUserPrompt(
+    context,
+    applyInstructionAffixes(instruction, options),
+    numOptions,
+    resolveSectionOrder(options.sectionOrder)
+  )
 
   return [
     { role: 'system', content: systemMessage },
@@ -49,57 +127,153 @@ function buildMultiCommandSystemPrompt(numOptions: number): string {
 
 /**
  * Build user prompt with context data
+ * Sections are assembled in `order`; the response format note stays last
  */
 function buildUserPrompt(
   context: ContextData,
   instruction: string,
-  numOptions: number
+  numOptions: number,
+  order: PromptSection[]
 ): string {
-  const parts: string[] = []
+  const blocks: Record<PromptSection, string[]> = {
+    system: [],
+    directory: [],
+    history: [],
+    stdin: [],
+    instruction: [],
+  }
+  const sections = context.sections ?? ALL_CONTEXT_SECTIONS
 
   // System context
-  parts.push(`System Context:
-OS: ${context.system.osName} ${context.system.osVersion}
-Architecture: ${context.system.architecture}
-Shell: ${context.system.shell}
-User: ${context.system.user}
-Memory: ${context.system.totalMemoryMb} MB`)
+  if (sections.includes('system')) {
+    const systemLines = Object.entries(
+      formatSystemInfo(context.system, context.systemFields)
+    ).map(([label, value]) => `${label}: ${value}`)
+    if (context.time) {
+      systemLines.push(
+        `Current Time: ${context.time.local} (${context.time.timezone})`
+      )
+    }
+    if (context.environments && context.environments.length > 0) {
+      systemLines.push(`Environments: ${context.environments.join(', ')}`)
+    }
+    if (context.packageManagers && context.packageManagers.length > 0) {
+      systemLines.push(
+        `Package Managers: ${context.packageManagers.join(', ')}`
+      )
+    }
+    if (systemLines.length > 0) {
+      blocks.system.push(`System Context:\n${systemLines.join('\n')}`)
+    }
+  }
 
   // Directory context
-  const filesList =
-    context.files.length > 0
-      ? context.files.slice(0, 20).join(', ')
-      : '(empty directory)'
-  parts.push(`\nDirectory Context:
+  if (sections.includes('directory')) {
+    const filesList =
+      context.files.length > 0
+        ? context.files.slice(0, 20).join(', ')
+        : '(empty directory)'
+    blocks.directory.push(`Directory Context:
 Current directory: ${context.cwd}
 Files: ${filesList}`)
+    if (context.projectDescription) {
+      blocks.directory.push(`Project: ${context.projectDescription}`)
+    }
+  }
 
   // History context (if available)
-  if (context.history.length > 0) {
+  if (sections.includes('history') && context.history.length > 0) {
     const historyList = context.history
       .map((h, i) => `${i + 1}. ${h}`)
       .join('\n')
-    parts.push(`\nRecent Shell History:\n${historyList}`)
+    blocks.history.push(`Recent Shell History:\n${historyList}`)
+  }
+
+  // Previous command's exit status (--last-exit); a failure hints "fix that"
+  if (sections.includes('history') && context.lastExit !== undefined) {
+    const outcome = context.lastExit === 0 ? 'succeeded' : 'failed'
+    blocks.history.push(
+      `Last command ${outcome}: exited with code ${context.lastExit}`
+    )
   }
 
   // Stdin context (if available)
-  if (context.stdin) {
-    parts.push(`\nStdin input:\n${context.stdin}`)
+  if (sections.includes('stdin') && context.stdin) {
+    const label = context.stdinName
+      ? `Contents of ${context.stdinName}`
+      : 'Stdin input'
+    blocks.stdin.push(`${label}:\n${context.stdin}`)
+  }
+
+  // Inline context travels with the instruction it qualifies
+  if (context.inlineContext) {
+    blocks.instruction.push(`Additional Context: ${context.inlineContext}`)
   }
 
   // User instruction
-  parts.push(`\nUser Instruction: ${instruction}`)
+  blocks.instruction.push(`User Instruction: ${instruction}`)
+
+  const parts = order.flatMap((section) => blocks[section])
 
   // Response instruction differs for single vs multi
   if (numOptions > 1) {
     parts.push(
-      `\nRespond with exactly ${numOptions} different command options as JSON: {"commands": ["cmd1", "cmd2", ...]}. Order from simplest to most advanced. No markdown or explanations.`
+      `Respond with exactly ${numOptions} different command options as JSON: {"commands": ["cmd1", "cmd2", ...]}. Order from simplest to most advanced. No markdown or explanations.`
     )
   } else {
     parts.push(
-      `\nRespond ONLY with the executable command. Do not include markdown code fences, explanations, or any other text. Just the command itself.`
+      `Respond ONLY with the executable command. Do not include markdown code fences, explanations, or any other text. Just the command itself.`
     )
   }
 
-  return parts.join('')
+  return parts.join('\n')
+}
+
+/**
+ * Build chat messages asking for a short risk explanation of a command
+ * Used by --explain-danger for commands flagged by the safety patterns
+ *
+ * @param command - The flagged shell command
+ * @returns Array of chat messages for the AI
+ */
+export function buildExplainDangerPrompt(command: string): ChatMessage[] {
+  return [
+    {
+      role: 'system',
+      content:
+        'You are a shell safety reviewer. In ONE short sentence, explain what could go wrong if the given command is run. No preamble, no markdown.',
+    },
+    { role: 'user', content: `Command: ${command}` },
+  ]
+}
+
+/**
+ * Build chat messages asking what an existing command does
+ * Used by --explain-only, where the positional is a command, not a task
+ *
+ * @param command - Shell command to explain
+ * @returns Array of chat messages for the AI
+ */
+export function buildExplainCommandPrompt(command: string): ChatMessage[] {
+  return [
+    {
+      role: 'system',
+      content:
+        'You are a shell expert. Explain what the given command does: its overall effect, then each program, flag and argument. Mention side effects (files written or deleted, network access). Be concise. Plain text, no markdown.',
+    },
+    { role: 'user', content: `Explain this command: ${command}` },
+  ]
+}
+
+/**
+ * Build the follow-up message sent when a multi-command response wasn't JSON
+ *
+ * @param numOptions - Number of commands requested
+ * @returns User message asking for JSON only
+ */
+export function buildJsonRetryMessage(numOptions: number): ChatMessage {
+  return {
+    role: 'user',
+    content: `Your previous reply was not valid JSON. Reply ONLY with JSON of the form {"commands": ["cmd1", "cmd2", ...]} containing exactly ${numOptions} commands. No prose, no markdown, no explanations.`,
+  }
 }
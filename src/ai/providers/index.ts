@@ -1,3 +1,4 @@
 // Provider exports
 
-export { OpenRouterProvider } from './openrouter.js'
+export { OpenRouterProvider, formatRequestDump } from './openrouter.js'
+export type { OpenRouterOptions } from './openrouter.js'
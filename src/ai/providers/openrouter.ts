@@ -1,11 +1,91 @@
 // OpenRouter API Provider Implementation
-// Supports retry logic with exponential backoff for 429 rate limit errors
+// Supports retry logic with exponential backoff for 429 rate limit and
+// 5xx server errors
 
-import { AIProvider, ChatRequest, ChatResponse, AIError } from '../types.js'
+import {
+  AIProvider,
+  ChatRequest,
+  ChatResponse,
+  AIError,
+  AITimeoutError,
+  AIConnectionError,
+  RequestDumpedError,
+  ProviderCapabilities,
+  raceCancellation,
+  throwIfCancelled,
+} from '../types.js'
+import { VERSION } from '../../version.js'
 
 const OPENROUTER_URL = 'https://openrouter.ai/api/v1/chat/completions'
 const TIMEOUT_MS = 60_000
-const MAX_RETRIES = 3
+const MAX_RETRIES = 3 // total attempts, shared by 429 and 5xx retries
+const RATE_LIMIT_BASE_DELAY_MS = 1000
+const SERVER_ERROR_BASE_DELAY_MS = 250
+const DEFAULT_REFERER = 'https://github.com/clai'
+const DEFAULT_TITLE = 'clai'
+// Identifies clai to provider gateways unless provider.user-agent is set
+export const DEFAULT_USER_AGENT = `clai/${VERSION}`
+
+/**
+ * Optional OpenRouter settings
+ */
+export interface OpenRouterOptions {
+  /** HTTP-Referer attribution header (default: https://github.com/clai) */
+  referer?: string
+  /** X-Title attribution header (default: clai) */
+  title?: string
+  /** User-Agent header (default: clai/<version>) */
+  userAgent?: string
+  /** Extra fields overlaid on the request body (never model/messages) */
+  extraParams?: Record<string, unknown>
+  /** Throw RequestDumpedError with the request instead of sending it */
+  dumpRequest?: boolean
+}
+
+// Shown in place of the API key in --dump-request output
+const MASKED_AUTHORIZATION = 'Bearer ********'
+
+/**
+ * Render a request for --dump-request: method and URL, headers (with the
+ * Authorization header masked), then the JSON body
+ */
+export function formatRequestDump(
+  url: string,
+  headers: Record<string, string>,
+  body: Record<string, unknown>
+): string {
+  const headerLines = Object.entries(headers).map(([name, value]) =>
+    name.toLowerCase() === 'authorization'
+      ? `${name}: ${MASKED_AUTHORIZATION}`
+      : `${name}: ${value}`
+  )
+  return [
+    `POST ${url}`,
+    ...headerLines,
+    '',
+    JSON.stringify(body, null, 2),
+    '',
+  ].join('\n')
+}
+
+/**
+ * Backoff before retrying a failed status, or null if it isn't retryable
+ * - 429 rate limit: 1s, 2s, ... (the gateway asked us to slow down)
+ * - 5xx server error: 250ms, 500ms, ... (usually a brief upstream hiccup)
+ * Other 4xx responses won't succeed on retry
+ *
+ * @param status - HTTP status of the failed response
+ * @param attempt - 0-based attempt that just failed
+ */
+export function retryDelay(status: number, attempt: number): number | null {
+  if (status === 429) {
+    return RATE_LIMIT_BASE_DELAY_MS * Math.pow(2, attempt)
+  }
+  if (status >= 500 && status <= 599) {
+    return SERVER_ERROR_BASE_DELAY_MS * Math.pow(2, attempt)
+  }
+  return null
+}
 
 /**
  * Sleep helper for retry delays
@@ -21,9 +101,19 @@ function sleep(ms: number): Promise<void> {
 export class OpenRouterProvider implements AIProvider {
   name = 'openrouter'
   private apiKey: string
+  private referer: string
+  private title: string
+  private userAgent: string
+  private extraParams: Record<string, unknown>
+  private dumpRequest: boolean
 
-  constructor(apiKey: string) {
+  constructor(apiKey: string, options: OpenRouterOptions = {}) {
     this.apiKey = apiKey
+    this.referer = options.referer || DEFAULT_REFERER
+    this.title = options.title || DEFAULT_TITLE
+    this.userAgent = options.userAgent || DEFAULT_USER_AGENT
+    this.extraParams = options.extraParams ?? {}
+    this.dumpRequest = options.dumpRequest ?? false
   }
 
   /**
@@ -33,16 +123,50 @@ export class OpenRouterProvider implements AIProvider {
     return !!this.apiKey && this.apiKey.length > 0
   }
 
+  /**
+   * OpenRouter routes to many backends that don't uniformly honor `n` or
+   * `response_format`, so we report a conservative set
+   * `reasoning` is normalized by OpenRouter and ignored by models without it
+   */
+  capabilities(): ProviderCapabilities {
+    return {
+      supportsStreaming: false,
+      supportsN: false,
+      supportsJsonMode: false,
+      supportsReasoningEffort: true,
+    }
+  }
+
   /**
    * Send completion request to OpenRouter
-   * Retries on 429 rate limit with exponential backoff
+   * Retries on 429 rate limit and 5xx server errors with exponential backoff
+   * Races the request (including backoff sleeps) against `signal`, so a
+   * cancellation rejects with CancelledError right away and aborts the fetch
    */
-  async complete(request: ChatRequest): Promise<ChatResponse> {
+  complete(request: ChatRequest, signal?: AbortSignal): Promise<ChatResponse> {
+    return raceCancellation(this.completeWithRetries(request, signal), signal)
+  }
+
+  private async completeWithRetries(
+    request: ChatRequest,
+    signal?: AbortSignal
+  ): Promise<ChatResponse> {
+    if (this.dumpRequest) {
+      throw new RequestDumpedError(
+        formatRequestDump(
+          OPENROUTER_URL,
+          this.buildHeaders(),
+          this.buildBody(request)
+        )
+      )
+    }
+
     let lastError: Error | null = null
 
     for (let attempt = 0; attempt < MAX_RETRIES; attempt++) {
+      throwIfCancelled(signal)
       try {
-        const response = await this.makeRequest(request)
+        const response = await this.makeRequest(request, signal)
 
         if (response.ok) {
           const json = await response.json()
@@ -52,9 +176,9 @@ export class OpenRouterProvider implements AIProvider {
         // Handle specific status codes
         const body = await response.text()
 
-        // 429: Rate limited - retry with backoff
-        if (response.status === 429 && attempt < MAX_RETRIES - 1) {
-          const delay = 1000 * Math.pow(2, attempt) // 1s, 2s, 4s
+        // 429 / 5xx: transient - retry with backoff
+        const delay = retryDelay(response.status, attempt)
+        if (delay !== null && attempt < MAX_RETRIES - 1) {
           await sleep(delay)
           continue
         }
@@ -67,6 +191,9 @@ export class OpenRouterProvider implements AIProvider {
           throw err
         }
 
+        // Aborted by the caller: don't retry
+        throwIfCancelled(signal)
+
         // Network or other errors
         lastError = err as Error
 
@@ -77,8 +204,9 @@ export class OpenRouterProvider implements AIProvider {
 
         // Otherwise, throw on last attempt
         if (attempt === MAX_RETRIES - 1) {
-          throw new AIError(
-            `Network error: ${lastError.message || 'Unknown error'}`
+          throw new AIConnectionError(
+            `Network error: ${lastError.message || 'Unknown error'}`,
+            lastError
           )
         }
       }
@@ -90,33 +218,77 @@ export class OpenRouterProvider implements AIProvider {
   /**
    * Make the actual HTTP request
    */
-  private async makeRequest(request: ChatRequest): Promise<Response> {
+  private async makeRequest(
+    request: ChatRequest,
+    signal?: AbortSignal
+  ): Promise<Response> {
     const controller = new AbortController()
-    const timeoutId = setTimeout(() => controller.abort(), TIMEOUT_MS)
+    let timedOut = false
+    const timeoutId = setTimeout(() => {
+      timedOut = true
+      controller.abort()
+    }, TIMEOUT_MS)
+    const onAbort = () => controller.abort()
+    signal?.addEventListener('abort', onAbort, { once: true })
 
     try {
       return await fetch(OPENROUTER_URL, {
         method: 'POST',
-        headers: {
-          Authorization: `Bearer ${this.apiKey}`,
-          'Content-Type': 'application/json',
-          'HTTP-Referer': 'https://github.com/clai',
-          'X-Title': 'clai',
-        },
-        body: JSON.stringify({
-          model: request.model,
-          messages: request.messages,
-          ...(request.temperature !== undefined && {
-            temperature: request.temperature,
-          }),
-          ...(request.maxTokens !== undefined && {
-            max_tokens: request.maxTokens,
-          }),
-        }),
+        headers: this.buildHeaders(),
+        body: JSON.stringify(this.buildBody(request)),
         signal: controller.signal,
       })
+    } catch (err) {
+      // Our own timeout, not the caller cancelling: don't retry
+      if (timedOut) {
+        throw new AITimeoutError(
+          `Timeout error: no response within ${TIMEOUT_MS / 1000}s`,
+          undefined,
+          err instanceof Error ? err : undefined
+        )
+      }
+      throw err
     } finally {
       clearTimeout(timeoutId)
+      signal?.removeEventListener('abort', onAbort)
+    }
+  }
+
+  /**
+   * Request headers (auth, attribution, User-Agent)
+   */
+  private buildHeaders(): Record<string, string> {
+    return {
+      Authorization: `Bearer ${this.apiKey}`,
+      'Content-Type': 'application/json',
+      'HTTP-Referer': this.referer,
+      'X-Title': this.title,
+      'User-Agent': this.userAgent,
+    }
+  }
+
+  /**
+   * Build the JSON request body
+   * Extra params overlay the typed fields, but model/messages always win
+   */
+  private buildBody(request: ChatRequest): Record<string, unknown> {
+    return {
+      ...(request.temperature !== undefined && {
+        temperature: request.temperature,
+      }),
+      ...(request.maxTokens !== undefined && {
+        max_tokens: request.maxTokens,
+      }),
+      ...(request.n !== undefined && { n: request.n }),
+      ...(request.jsonMode && {
+        response_format: { type: 'json_object' },
+      }),
+      ...(request.reasoningEffort && {
+        reasoning: { effort: request.reasoningEffort },
+      }),
+      ...this.extraParams,
+      model: request.model,
+      messages: request.messages,
     }
   }
 
@@ -133,7 +305,7 @@ export class OpenRouterProvider implements AIProvider {
         )
       case 408:
       case 504:
-        return new AIError(
+        return new AITimeoutError(
           `Timeout error (${status}): ${body || 'Request timed out'}`,
           status
         )
@@ -152,12 +324,15 @@ export class OpenRouterProvider implements AIProvider {
 
   /**
    * Parse successful response JSON
+   * `content` is null when the model answered with tool calls instead of
+   * text; that gets its own error rather than an empty command
    */
   private parseResponse(json: unknown): ChatResponse {
     const response = json as {
       choices?: Array<{
         message?: {
-          content?: string
+          content?: string | null
+          tool_calls?: unknown[]
         }
       }>
       model?: string
@@ -168,14 +343,28 @@ export class OpenRouterProvider implements AIProvider {
       }
     }
 
-    const content = response.choices?.[0]?.message?.content
+    const message = response.choices?.[0]?.message
+    const content = message?.content
 
     if (!content) {
+      if (message?.tool_calls?.length) {
+        throw new AIError(
+          'Model returned no text content; it may have attempted a tool call'
+        )
+      }
+      if (message) {
+        throw new AIError('Model returned no text content')
+      }
       throw new AIError('Invalid response: no content in choices')
     }
 
+    const choices = (response.choices ?? [])
+      .map((choice) => choice.message?.content)
+      .filter((c): c is string => typeof c === 'string' && c.length > 0)
+
     return {
       content,
+      choices: choices.length > 1 ? choices : undefined,
       model: response.model,
       usage: response.usage
         ? {
@@ -1,5 +1,11 @@
 // AI Types and Interfaces for the clai CLI
-import { ClaiError } from '../error/index.js'
+import {
+  ClaiError,
+  ExitCode,
+  InterruptError,
+  exitCodeForCategory,
+  type ErrorCategory,
+} from '../error/index.js'
 
 /**
  * Chat message for OpenAI-compatible API
@@ -9,6 +15,10 @@ export interface ChatMessage {
   content: string
 }
 
+/** Reasoning effort levels for reasoning models (--reasoning) */
+export const REASONING_EFFORTS = ['low', 'medium', 'high'] as const
+export type ReasoningEffort = (typeof REASONING_EFFORTS)[number]
+
 /**
  * Request to send to AI provider
  */
@@ -17,6 +27,12 @@ export interface ChatRequest {
   messages: ChatMessage[]
   temperature?: number
   maxTokens?: number
+  /** Number of completions to generate (only if provider supports it) */
+  n?: number
+  /** Request a JSON object response (only if provider supports it) */
+  jsonMode?: boolean
+  /** How hard a reasoning model should think (only if provider supports it) */
+  reasoningEffort?: ReasoningEffort
 }
 
 /**
@@ -24,7 +40,11 @@ export interface ChatRequest {
  */
 export interface ChatResponse {
   content: string
+  /** All choice contents when multiple completions were requested (n > 1) */
+  choices?: string[]
   model?: string
+  /** Name of the provider that produced this response */
+  provider?: string
   usage?: {
     promptTokens: number
     completionTokens: number
@@ -32,14 +52,54 @@ export interface ChatResponse {
   }
 }
 
+/**
+ * Optional request features a provider can honor
+ */
+export interface ProviderCapabilities {
+  supportsStreaming: boolean
+  supportsN: boolean
+  supportsJsonMode: boolean
+  supportsReasoningEffort: boolean
+}
+
+/**
+ * Conservative capabilities assumed when a provider doesn't report any
+ */
+export const DEFAULT_CAPABILITIES: ProviderCapabilities = {
+  supportsStreaming: false,
+  supportsN: false,
+  supportsJsonMode: false,
+  supportsReasoningEffort: false,
+}
+
 /**
  * Provider interface for future extensibility
  * Allows adding other providers (Anthropic, Ollama, etc.) in the future
+ * `complete` takes an optional AbortSignal; providers should reject with
+ * CancelledError once it fires rather than finishing the request
+ * `isAvailable` is a local check (e.g. an API key is set) and must not
+ * touch the network: it runs on every invocation and is not cached, and
+ * an unreachable endpoint surfaces as AIConnectionError from `complete`
  */
 export interface AIProvider {
   name: string
   isAvailable(): boolean
-  complete(request: ChatRequest): Promise<ChatResponse>
+  complete(request: ChatRequest, signal?: AbortSignal): Promise<ChatResponse>
+  capabilities?(): ProviderCapabilities
+}
+
+/**
+ * Minimal logger interface for progress messages (shown at -v)
+ */
+export interface AILogger {
+  info(msg: string): void
+}
+
+/**
+ * Get a provider's capabilities, falling back to the conservative defaults
+ */
+export function getCapabilities(provider: AIProvider): ProviderCapabilities {
+  return provider.capabilities?.() ?? DEFAULT_CAPABILITIES
 }
 
 /**
@@ -50,9 +110,143 @@ export class AIError extends ClaiError {
   public readonly statusCode?: number
 
   constructor(message: string, statusCode?: number, cause?: Error) {
-    super(message, 4, cause)
+    super(message, exitCodeForCategory('api'), cause)
     this.name = 'AIError'
     this.statusCode = statusCode
     Object.setPrototypeOf(this, AIError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'api'
+  }
+}
+
+/**
+ * Whether an error is an authentication failure (401/403)
+ * Usually a misconfigured API key rather than a transient problem
+ */
+export function isAuthError(err: unknown): boolean {
+  return (
+    err instanceof AIError &&
+    (err.statusCode === 401 || err.statusCode === 403)
+  )
+}
+
+/**
+ * API request that timed out (408/504 from the gateway, or no response
+ * within the client timeout)
+ * Same exit code as other API errors; --json-errors reports reason "timeout"
+ */
+export class AITimeoutError extends AIError {
+  constructor(message: string, statusCode?: number, cause?: Error) {
+    super(message, statusCode, cause)
+    this.name = 'AITimeoutError'
+    Object.setPrototypeOf(this, AITimeoutError.prototype)
+  }
+
+  override get reason(): string {
+    return 'timeout'
+  }
+}
+
+/**
+ * Provider endpoint could not be reached (DNS, refused connection, ...)
+ * Same exit code as other API errors; --json-errors reports reason
+ * "unreachable"
+ */
+export class AIConnectionError extends AIError {
+  constructor(message: string, cause?: Error) {
+    super(message, undefined, cause)
+    this.name = 'AIConnectionError'
+    Object.setPrototypeOf(this, AIConnectionError.prototype)
+  }
+
+  override get reason(): string {
+    return 'unreachable'
+  }
+}
+
+/**
+ * Whether an error is specific to the requested model
+ * (404 unknown model, or a 400 that names the model)
+ * Another model on the same provider may still work
+ */
+export function isModelError(err: unknown): boolean {
+  if (!(err instanceof AIError)) {
+    return false
+  }
+  return (
+    err.statusCode === 404 ||
+    (err.statusCode === 400 && /model/i.test(err.message))
+  )
+}
+
+/**
+ * Error for a request cancelled through its AbortSignal (e.g. Ctrl+C)
+ * Exit code 130 like any other interrupt
+ */
+export class CancelledError extends InterruptError {
+  constructor(message: string = 'Request cancelled', cause?: Error) {
+    super(message, cause)
+    this.name = 'CancelledError'
+    Object.setPrototypeOf(this, CancelledError.prototype)
+  }
+}
+
+/**
+ * Thrown by a provider instead of sending the request (--dump-request)
+ * `dump` is the request as it would have gone over the wire, secrets
+ * masked; main prints it to stdout and exits 0
+ */
+export class RequestDumpedError extends ClaiError {
+  public readonly dump: string
+
+  constructor(dump: string) {
+    super('Request not sent (--dump-request)', ExitCode.Success)
+    this.name = 'RequestDumpedError'
+    this.dump = dump
+    Object.setPrototypeOf(this, RequestDumpedError.prototype)
+  }
+}
+
+/**
+ * Throw CancelledError if the signal has already fired
+ */
+export function throwIfCancelled(signal?: AbortSignal): void {
+  if (signal?.aborted) {
+    throw new CancelledError()
+  }
+}
+
+/**
+ * Settle with `promise`, or reject with CancelledError as soon as the
+ * signal fires, whichever comes first
+ * The underlying work is not stopped; pass the signal down (e.g. to fetch)
+ * for that
+ */
+export function raceCancellation<T>(
+  promise: Promise<T>,
+  signal?: AbortSignal
+): Promise<T> {
+  if (!signal) {
+    return promise
+  }
+  if (signal.aborted) {
+    return Promise.reject(new CancelledError())
+  }
+
+  return new Promise<T>((resolve, reject) => {
+    const onAbort = () => reject(new CancelledError())
+    signal.addEventListener('abort', onAbort, { once: true })
+    promise.then(
+      (value) => {
+        signal.removeEventListener('abort', onAbort)
+        resolve(value)
+      },
+      (err: unknown) => {
+        signal.removeEventListener('abort', onAbort)
+        reject(err)
+      }
+    )
+  })
 }
@@ -0,0 +1,95 @@
+// Batch mode: one instruction per stdin line, one command per stdout line
+
+import type { Config } from '../config/types.js'
+import { assertOnlyIfSafe, SafetyError } from '../safety/index.js'
+import { applyCommandTemplate } from '../output/index.js'
+
+/** Requests in flight at once */
+export const DEFAULT_BATCH_CONCURRENCY = 4
+
+/**
+ * Outcome for a single batch line
+ */
+export type BatchResult =
+  | { instruction: string; command: string }
+  | { instruction: string; error: string; blocked?: boolean }
+
+/**
+ * Split piped input into instructions (one per non-empty line)
+ */
+export function parseBatchInput(input: string): string[] {
+  return input
+    .split(/\r?\n/)
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0)
+}
+
+/**
+ * Vet a generated command before it can reach stdout
+ * With --only-if-safe a dangerous command throws, so its line becomes an
+ * error (marked blocked) instead of a runnable command. A command that
+ * passes is wrapped in postprocess.command-template
+ *
+ * @param command - Generated command
+ * @param config - Application config
+ * @returns The command to print
+ */
+export function finishBatchCommand(command: string, config: Config): string {
+  assertOnlyIfSafe([command], config)
+  return applyCommandTemplate(command, config.postprocess?.commandTemplate)
+}
+
+/**
+ * Run `generate` for each instruction with at most `concurrency` in flight
+ * A failing instruction produces an error result instead of aborting the batch
+ *
+ * @param instructions - Instructions in input order
+ * @param generate - Produces the command for one instruction (given its index)
+ * @param concurrency - Maximum concurrent generations
+ * @returns Results in input order
+ */
+export async function runBatch(
+  instructions: string[],
+  generate: (instruction: string, index: number) => Promise<string>,
+  concurrency: number = DEFAULT_BATCH_CONCURRENCY
+): Promise<BatchResult[]> {
+  const results: BatchResult[] = new Array(instructions.length)
+  let next = 0
+
+  // Each worker takes the next unclaimed index until none are left
+  async function worker(): Promise<void> {
+    while (next < instructions.length) {
+      const index = next++
+      const instruction = instructions[index]!
+      try {
+        results[index] = {
+          instruction,
+          command: await generate(instruction, index),
+        }
+      } catch (err) {
+        results[index] = {
+          instruction,
+          error: err instanceof Error ? err.message : String(err),
+          ...(err instanceof SafetyError && { blocked: true }),
+        }
+      }
+    }
+  }
+
+  const workers = Math.max(1, Math.min(concurrency, instructions.length))
+  await Promise.all(Array.from({ length: workers }, () => worker()))
+
+  return results
+}
+
+/**
+ * Format a result as its stdout line
+ * Failures become a shell comment so the output stays line-aligned with input
+ */
+export function formatBatchLine(result: BatchResult): string {
+  if ('error' in result) {
+    return `# error: ${result.error.replace(/\s*\n\s*/g, ' ')}`
+  }
+  // Keep one line per instruction even if the model returned several
+  return result.command.replace(/\s*\n\s*/g, '; ')
+}
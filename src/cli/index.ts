@@ -1,12 +1,41 @@
 import { Command, InvalidArgumentError } from 'commander'
 import { UsageError } from '../error/index.js'
+import { OUTPUT_FORMATS, type OutputFormat } from '../output/format.js'
+import { LOG_LEVEL_NAMES, type LogLevelName } from '../logging/logger.js'
+import { VERSION } from '../version.js'
+import { INTEGRATION_SHELLS, type IntegrationShell } from '../shell/index.js'
+import { DEFAULT_BATCH_CONCURRENCY } from '../batch/index.js'
+import { REASONING_EFFORTS, type ReasoningEffort } from '../ai/types.js'
 
 export type ColorMode = 'auto' | 'always' | 'never'
 
+/** Actions of the `clai config` subcommand */
+export const CONFIG_ACTIONS = [
+  'get',
+  'set',
+  'list',
+  'validate',
+  'init',
+  'path',
+] as const
+export type ConfigAction = (typeof CONFIG_ACTIONS)[number]
+
+/**
+ * A `clai config <action> [args...]` invocation
+ * `args` holds the key for get and the assignments for set
+ */
+export interface ConfigCommand {
+  action: ConfigAction
+  args: string[]
+}
+
 export interface Cli {
   instruction: string
   model?: string
+  modelFallback: string[] // tried on the same provider after a model error
   provider?: string
+  reasoning?: ReasoningEffort // effort hint for reasoning models
+  apiKeyEnv?: string // env var holding the selected provider's API key
   quiet: boolean
   verbose: number
   noColor: boolean
@@ -14,11 +43,48 @@ export interface Cli {
   interactive: boolean
   force: boolean
   dryRun: boolean
+  previewScript: boolean // write the command to a temp script first
   context?: string
   offline: boolean
   numOptions: number
+  numOptionsExplicit: boolean // -o given (otherwise the mode decides)
   debug: boolean
   debugFile?: string
+  configFile?: string // --config: load only this config file
+  record?: string // append an audit record for this run to this file
+  summarizeContext: boolean // print a context overview to stderr
+  showRaw: boolean // echo the raw model response to stderr
+  dumpRequest: boolean // print the HTTP request instead of sending it
+  refreshContext: boolean // re-collect cached system info
+  noCacheSystemInfo: boolean // collect system info without the cache
+  stats: boolean // print provider/tokens/duration after generating
+  rawFile?: string // write the raw model response to this file
+  explainDanger: boolean
+  explainOnly: boolean // explain the positional command instead of generating
+  strictJson: boolean // no lenient parsing of multi-command responses
+  onlyIfSafe: boolean // fail instead of printing a dangerous command
+  noStdin: boolean
+  noSpinner: boolean
+  noWarnMissingConfig: boolean
+  stdinAsFile?: string // label piped stdin with this filename in the prompt
+  lastExit?: number // exit status of the user's previous command ($?)
+  inlineContext?: string // positionals after the instruction, joined
+  historyOnly: boolean
+  fixPermissions: boolean
+  validateConfig: boolean
+  printConfig: boolean
+  modelInfo: boolean // print the resolved provider/model and exit
+  set: string[] // section.key=value assignments for the user config file
+  configCommand?: ConfigCommand // `clai config ...` instead of an instruction
+  versionJson: boolean // print version info as JSON and exit
+  shellIntegration?: IntegrationShell // print a key-binding snippet and exit
+  testPattern: boolean // treat the positional as a command to check
+  batch: boolean // read instructions from stdin, one per line
+  concurrency: number // --batch requests in flight at once
+  noFallbackOnAuth: boolean
+  format: OutputFormat
+  logLevel?: LogLevelName
+  jsonErrors: boolean
 }
 
 function parseNumOptions(value: string): number {
@@ -30,6 +96,40 @@ function parseNumOptions(value: string): number {
   return Math.max(1, Math.min(10, num))
 }
 
+function parseExitStatus(value: string): number {
+  const status = Number(value)
+  if (!/^\d+$/.test(value.trim()) || status > 255) {
+    throw new InvalidArgumentError('Must be an exit status (0-255)')
+  }
+  return status
+}
+
+function parseConcurrency(value: string): number {
+  const num = Number(value)
+  if (!/^\d+$/.test(value.trim()) || num < 1) {
+    throw new InvalidArgumentError('Must be a positive number')
+  }
+  return num
+}
+
+function parseModelList(value: string): string[] {
+  const models = value
+    .split(',')
+    .map((m) => m.trim())
+    .filter((m) => m.length > 0)
+  if (models.length === 0) {
+    throw new InvalidArgumentError('Must list at least one model')
+  }
+  return models
+}
+
+function collectAssignment(value: string, previous: string[]): string[] {
+  if (!value.includes('=')) {
+    throw new InvalidArgumentError('Must be section.key=value')
+  }
+  return [...previous, value]
+}
+
 function parseColorMode(value: string): ColorMode {
   if (value === 'auto' || value === 'always' || value === 'never') {
     return value
@@ -37,6 +137,42 @@ function parseColorMode(value: string): ColorMode {
   throw new InvalidArgumentError('Must be auto, always, or never')
 }
 
+function parseOutputFormat(value: string): OutputFormat {
+  const format = OUTPUT_FORMATS.find((f) => f === value)
+  if (format) {
+    return format
+  }
+  throw new InvalidArgumentError(`Must be one of: ${OUTPUT_FORMATS.join(', ')}`)
+}
+
+function parseIntegrationShell(value: string): IntegrationShell {
+  const shell = INTEGRATION_SHELLS.find((s) => s === value)
+  if (shell) {
+    return shell
+  }
+  throw new InvalidArgumentError(
+    `Must be one of: ${INTEGRATION_SHELLS.join(', ')}`
+  )
+}
+
+function parseReasoningEffort(value: string): ReasoningEffort {
+  const effort = REASONING_EFFORTS.find((e) => e === value)
+  if (effort) {
+    return effort
+  }
+  throw new InvalidArgumentError(
+    `Must be one of: ${REASONING_EFFORTS.join(', ')}`
+  )
+}
+
+function parseLogLevel(value: string): LogLevelName {
+  const level = LOG_LEVEL_NAMES.find((l) => l === value)
+  if (level) {
+    return level
+  }
+  throw new InvalidArgumentError(`Must be one of: ${LOG_LEVEL_NAMES.join(', ')}`)
+}
+
 export function parseCli(argv: string[] = process.argv): Cli {
   const program = new Command()
 
@@ -45,10 +181,39 @@ export function parseCli(argv: string[] = process.argv): Cli {
     .description(
       'AI-powered CLI that converts natural language into executable shell commands'
     )
-    .version('0.1.0', '-V, --version', 'Output the version number')
+    .version(VERSION, '-V, --version', 'Output the version number')
+    .option(
+      '--version-json',
+      'Output version info as JSON (name, version, node, target) and exit',
+      false
+    )
+    .option(
+      '--shell-integration <shell>',
+      `Print a Ctrl+G key binding for ${INTEGRATION_SHELLS.join('/')} that turns the typed line into a command, and exit`,
+      parseIntegrationShell
+    )
     .argument('[instruction]', 'Natural language instruction')
+    .argument(
+      '[context...]',
+      'Extra context for the instruction, e.g. "we use podman"'
+    )
     .option('-m, --model <model>', 'Override AI model')
+    .option(
+      '--model-fallback <models>',
+      'Comma-separated models to try on the same provider if the model fails',
+      parseModelList,
+      []
+    )
     .option('-p, --provider <provider>', 'Override AI provider')
+    .option(
+      '--reasoning <effort>',
+      'Reasoning effort for reasoning models: low, medium, high',
+      parseReasoningEffort
+    )
+    .option(
+      '--api-key-env <var>',
+      "Read the selected provider's API key from this environment variable"
+    )
     .option('-q, --quiet', 'Minimal output', false)
     .option(
       '-v, --verbose',
@@ -56,6 +221,11 @@ export function parseCli(argv: string[] = process.argv): Cli {
       (_, prev) => prev + 1,
       0
     )
+    .option(
+      '--log-level <level>',
+      'Log level: error, warn, info, debug, trace (overrides -q/-v)',
+      parseLogLevel
+    )
     .option('--no-color', 'Disable color output')
     .option(
       '--color <mode>',
@@ -70,30 +240,225 @@ export function parseCli(argv: string[] = process.argv): Cli {
     )
     .option('-f, --force', 'Skip dangerous command confirmation', false)
     .option('-n, --dry-run', 'Only print command(s), no execute', false)
+    .option(
+      '--preview-script',
+      'Write the command to a temp script to inspect (and run, if confirmed)',
+      false
+    )
     .option('-c, --context <file>', 'Optional context file path')
+    .option(
+      '--config <path>',
+      'Load only this config file instead of the usual locations (or CLAI_CONFIG)'
+    )
+    .option('--no-stdin', 'Ignore piped stdin (do not use it as context)')
+    .option('--no-spinner', 'Do not show the progress spinner')
+    .option(
+      '--no-warn-missing-config',
+      'Do not note (at -v) that no config file was found'
+    )
+    .option(
+      '--stdin-as-file <name>',
+      'Present piped stdin to the model as the contents of <name>'
+    )
+    .option(
+      '--last-exit <status>',
+      "Exit status of your previous command (e.g. $?), so the model knows it failed",
+      parseExitStatus
+    )
+    .option(
+      '--batch',
+      'Read instructions from stdin (one per line) and print one command per line',
+      false
+    )
+    .option(
+      '--concurrency <n>',
+      'Maximum --batch requests in flight at once',
+      parseConcurrency,
+      DEFAULT_BATCH_CONCURRENCY
+    )
+    .option(
+      '--history-only',
+      'Only use recent shell history as context',
+      false
+    )
+    .option(
+      '--no-fallback-on-auth',
+      'Stop on authentication errors instead of trying fallback providers'
+    )
     .option('--offline', 'Offline mode (not implemented)', false)
     .option(
       '-o, --options <count>',
-      'Number of command options (1-10)',
+      'Number of command options (1-10; default: ui.num-options when interactive, else 1)',
       parseNumOptions,
       1
     )
+    .option(
+      '--format <format>',
+      'Output format: plain, json, jsonl, null',
+      parseOutputFormat,
+      'plain'
+    )
     .option('-d, --debug', 'Print prompt/request to stderr', false)
     .option('--debug-file [path]', 'Enable file logging (optional path)')
+    .option(
+      '--record <path>',
+      'Append a JSON audit record of this run (instruction, command, decision)'
+    )
+    .option(
+      '--summarize-context',
+      'Print a short summary of the gathered context to stderr',
+      false
+    )
+    .option(
+      '--stats',
+      'Print provider, model, token usage and duration to stderr (also with -v)',
+      false
+    )
+    .option(
+      '--refresh-context',
+      'Re-collect system info instead of using the cached values',
+      false
+    )
+    .option(
+      '--no-cache-system-info',
+      'Collect system info fresh without reading or filling the cache'
+    )
+    .option(
+      '--show-raw',
+      'Print the raw model response to stderr (stdout still gets only the command)',
+      false
+    )
+    .option(
+      '--dump-request',
+      'Print the HTTP request (headers with the API key masked, JSON body) instead of sending it, and exit',
+      false
+    )
+    .option(
+      '--raw-file <path>',
+      'Write the raw model response to a file instead of stderr'
+    )
+    .option(
+      '--fix-permissions',
+      'Restrict config files to owner read/write (0600) and exit',
+      false
+    )
+    .option(
+      '--validate-config',
+      'Load and validate config files, report problems, and exit',
+      false
+    )
+    .option(
+      '--print-config',
+      'Print the effective merged config (TOML, or JSON with --format json) and exit',
+      false
+    )
+    .option(
+      '--model-info',
+      'Print the provider and model this run would use, then exit',
+      false
+    )
+    .option(
+      '--set <key=value>',
+      'Set a value in the user config file and exit (repeatable), e.g. provider.default=openrouter',
+      collectAssignment,
+      []
+    )
+    .option(
+      '--test-pattern',
+      'Check the given command against dangerous patterns and exit (no AI call)',
+      false
+    )
+    .option('--json-errors', 'Report errors as single-line JSON on stderr', false)
+    .option(
+      '--explain-only',
+      'Explain the given command instead of generating one (explanation on stdout)',
+      false
+    )
+    .option(
+      '--explain-danger',
+      'Ask the AI why a flagged command is risky before confirming',
+      false
+    )
+    .option(
+      '--strict-json',
+      'Fail instead of guessing when a multi-command response is not valid JSON',
+      false
+    )
+    .option(
+      '--only-if-safe',
+      'Exit with code 5 (printing nothing) if the generated command is dangerous',
+      false
+    )
     .configureOutput({
       writeOut: (str) => process.stdout.write(str),
       writeErr: (str) => process.stderr.write(str),
       outputError: (str, write) => write(`Error: ${str}`),
     })
+    // No implicit `help` subcommand: `clai help` stays an instruction
+    .helpCommand(false)
+    // Without a subcommand the positional is an instruction, as before
+    .action(() => {})
+
+  let configCommand: ConfigCommand | undefined
+  const configProgram = program
+    .command('config')
+    .description('Manage config files (get, set, list, validate, init, path)')
+  const record =
+    (action: ConfigAction) =>
+    (...args: unknown[]) => {
+      // Commander passes the declared arguments, then options and the command
+      const values = args.slice(0, -2).flat() as string[]
+      configCommand = { action, args: values }
+    }
+  configProgram
+    .command('get')
+    .description('Print a setting or section of the effective config')
+    .argument('<key>', 'Dotted key, e.g. ui.color or openrouter.model')
+    .action(record('get'))
+  configProgram
+    .command('set')
+    .description('Set values in the user config file')
+    .argument('<key=value...>', 'Assignments, e.g. provider.default=openrouter')
+    .action(record('set'))
+  configProgram
+    .command('list')
+    .description('Print the effective merged config (like --print-config)')
+    .action(record('list'))
+  configProgram
+    .command('validate')
+    .description('Load and validate config files, report problems')
+    .action(record('validate'))
+  configProgram
+    .command('init')
+    .description('Create the user config file with the default settings')
+    .action(record('init'))
+  configProgram
+    .command('path')
+    .description('Print the config file locations, lowest precedence first')
+    .action(record('path'))
 
   program.parse(argv)
 
   const opts = program.opts()
   const args = program.args
 
-  // instruction is required unless help/version was shown
-  const instruction = args[0]
-  if (!instruction) {
+  // instruction is required unless help/version was shown,
+  // a standalone action (--fix-permissions, --validate-config, --print-config,
+  // --model-info, --version-json, --shell-integration, --set, `clai config`) was
+  // requested, or instructions come from stdin (--batch)
+  const instruction = configCommand ? '' : (args[0] ?? '')
+  if (
+    !instruction &&
+    !configCommand &&
+    !opts.versionJson &&
+    !opts.shellIntegration &&
+    opts.set.length === 0 &&
+    !opts.fixPermissions &&
+    !opts.validateConfig &&
+    !opts.printConfig &&
+    !opts.modelInfo &&
+    !opts.batch
+  ) {
     throw new UsageError('missing required argument: instruction')
   }
 
@@ -104,7 +469,10 @@ export function parseCli(argv: string[] = process.argv): Cli {
   return {
     instruction,
     model: opts.model,
+    modelFallback: opts.modelFallback,
     provider: opts.provider,
+    reasoning: opts.reasoning,
+    apiKeyEnv: opts.apiKeyEnv,
     quiet: opts.quiet,
     verbose: opts.verbose,
     noColor,
@@ -112,10 +480,49 @@ export function parseCli(argv: string[] = process.argv): Cli {
     interactive: opts.interactive,
     force: opts.force,
     dryRun: opts.dryRun,
+    previewScript: opts.previewScript,
     context: opts.context,
     offline: opts.offline,
     numOptions: opts.options,
+    numOptionsExplicit: program.getOptionValueSource('options') === 'cli',
     debug: opts.debug,
     debugFile: opts.debugFile,
+    configFile: opts.config,
+    record: opts.record,
+    summarizeContext: opts.summarizeContext,
+    showRaw: opts.showRaw,
+    dumpRequest: opts.dumpRequest,
+    refreshContext: opts.refreshContext,
+    noCacheSystemInfo: opts.cacheSystemInfo === false, // set by --no-cache-system-info
+    stats: opts.stats,
+    rawFile: opts.rawFile,
+    explainDanger: opts.explainDanger,
+    explainOnly: opts.explainOnly,
+    onlyIfSafe: opts.onlyIfSafe,
+    strictJson: opts.strictJson,
+    noStdin: opts.stdin === false, // commander sets this when --no-stdin is used
+    stdinAsFile: opts.stdinAsFile,
+    lastExit: opts.lastExit,
+    inlineContext: configCommand
+      ? undefined
+      : args.slice(1).join(' ') || undefined,
+    noSpinner: opts.spinner === false, // set by --no-spinner
+    noWarnMissingConfig: opts.warnMissingConfig === false,
+    historyOnly: opts.historyOnly,
+    fixPermissions: opts.fixPermissions,
+    validateConfig: opts.validateConfig,
+    printConfig: opts.printConfig,
+    modelInfo: opts.modelInfo,
+    set: opts.set,
+    configCommand,
+    versionJson: opts.versionJson,
+    shellIntegration: opts.shellIntegration,
+    testPattern: opts.testPattern,
+    batch: opts.batch,
+    concurrency: opts.concurrency,
+    noFallbackOnAuth: opts.fallbackOnAuth === false, // set by --no-fallback-on-auth
+    format: opts.format,
+    logLevel: opts.logLevel,
+    jsonErrors: opts.jsonErrors,
   }
 }
@@ -1,18 +1,60 @@
-import { readFileSync, accessSync, constants, statSync } from 'fs'
-import { homedir } from 'os'
-import { resolve, join } from 'path'
+import {
+  readFileSync,
+  writeFileSync,
+  mkdirSync,
+  accessSync,
+  constants,
+  statSync,
+  chmodSync,
+  existsSync,
+} from 'fs'
+import { tmpdir } from 'os'
+import { resolve, join, dirname } from 'path'
 import TOML from '@iarna/toml'
+import { z } from 'zod'
 import {
   FileConfig,
   FileConfigSchema,
   Config,
+  FallbackMergeMode,
 } from './types.js'
 import { Cli } from '../cli/index.js'
-import { ClaiError } from '../error/index.js'
+import {
+  ClaiError,
+  exitCodeForCategory,
+  type ErrorCategory,
+} from '../error/index.js'
+import { ALL_SYSTEM_FIELDS } from '../context/types.js'
+import { getHomeDir } from '../context/home.js'
+import { DEFAULT_USER_AGENT } from '../ai/providers/openrouter.js'
+import { DEFAULT_COMMAND_JOIN } from '../ui/join.js'
+import {
+  DEFAULT_INTERACTIVE_ACTION,
+  resolveConfirmKeys,
+  validateConfirmKeys,
+} from '../ui/keys.js'
+import { validateCommandTemplate } from '../output/template.js'
 
 // Config cache to avoid reloading
 let configCache: FileConfig | null = null
 
+// Warnings from validating the cached config
+let configWarnings: string[] = []
+
+// File given with --config (CLAI_CONFIG is read at use)
+let pinnedConfigFile: string | undefined
+
+// Default number of JSON retries for multi-command generation
+const DEFAULT_JSON_RETRIES = 1
+
+// Options generated in interactive mode when -o isn't given
+const DEFAULT_INTERACTIVE_NUM_OPTIONS = 3
+
+// Bounds for numeric settings (values outside are clamped with a warning)
+const MAX_FILES_RANGE: [number, number] = [1, 100]
+const MAX_HISTORY_RANGE: [number, number] = [0, 50]
+const PROMPT_TIMEOUT_RANGE: [number, number] = [0, 300000]
+
 // Default config values
 const DEFAULT_CONFIG: FileConfig = {
   provider: {
@@ -24,28 +66,75 @@ const DEFAULT_CONFIG: FileConfig = {
     maxHistory: 3,
     redactPaths: false,
     redactUsername: false,
+    redactionPlaceholder: '[REDACTED]',
+    mode: 'full',
+    fileSort: 'name',
+    detectPackageManagers: true,
+    includeTime: true,
+    detectEnvironments: true,
+    includeProjectDescription: false,
+    historyViaShell: false,
+    systemFields: [...ALL_SYSTEM_FIELDS],
   },
   safety: {
     confirmDangerous: true,
     dangerousPatterns: [],
+    confirmPipeToInterpreter: false,
+    maxCommandLength: 4096,
+    maxCommandLines: 100,
   },
   ui: {
     color: 'auto',
     interactive: false,
     promptTimeout: 30000,
   },
+  prompt: {
+    instructionPrefix: '',
+    instructionSuffix: '',
+    stripReasoningTags: true,
+  },
   providers: {},
 }
 
-// Config file paths in order of precedence (lowest to highest)
-function getConfigPaths(): string[] {
+/**
+ * Pin the config to one file (--config), replacing discovery
+ * Clears the cache so the next load reads that file
+ *
+ * @param path - File to load, or undefined to discover as usual
+ */
+export function setConfigFile(path: string | undefined): void {
+  pinnedConfigFile = path
+  clearConfigCache()
+}
+
+/**
+ * The pinned config file: --config, else $CLAI_CONFIG
+ *
+ * @returns Absolute path, or undefined when config files are discovered
+ */
+export function getPinnedConfigFile(): string | undefined {
+  const path = pinnedConfigFile ?? (process.env.CLAI_CONFIG || undefined)
+  return path ? resolve(path) : undefined
+}
+
+/**
+ * Config file locations in order of precedence (lowest to highest)
+ * Listed whether or not the files exist (`clai config path`)
+ * A pinned file (--config / CLAI_CONFIG) is the only location
+ */
+export function getConfigPaths(): string[] {
+  const pinned = getPinnedConfigFile()
+  if (pinned) {
+    return [pinned]
+  }
+
   const paths: string[] = []
 
   // 1. /etc/clai/config.toml (lowest priority)
   paths.push('/etc/clai/config.toml')
 
   // 2. ~/.config/clai/config.toml
-  const home = homedir()
+  const home = getHomeDir()
   if (home) {
     paths.push(join(home, '.config', 'clai', 'config.toml'))
   }
@@ -62,42 +151,129 @@ function getConfigPaths(): string[] {
   return paths
 }
 
-// Check if file has correct permissions (Unix 0600)
-function checkFilePermissions(path: string): void {
+// Get the permission bits of a file if they are not 0600, null otherwise
+function getInsecureMode(path: string): number | null {
   // Only check on Unix-like systems
   if (process.platform === 'win32') {
-    return
+    return null
   }
 
   try {
-    const stats = statSync(path)
-    const mode = stats.mode
-
     // Check if permissions are 0600 (owner read/write only)
-    const expectedMode = 0o600
-    const actualMode = mode & 0o777
+    const actualMode = statSync(path).mode & 0o777
+    return actualMode === 0o600 ? null : actualMode
+  } catch {
+    // If we can't stat the file, that's ok - it might not exist
+    return null
+  }
+}
 
-    if (actualMode !== expectedMode) {
-      throw new ConfigError(
-        `Config file ${path} has insecure permissions ${actualMode.toString(8)}. Must be 0600.`,
-        3
-      )
-    }
+// Inline API keys are the only secrets a config file can hold
+// (api-key-env just names a variable, so it doesn't count); matches
+// apiKey, *ApiKey and snake_case api_key after transformConfig
+function isSecretKey(key: string): boolean {
+  return /api_?key$/i.test(key)
+}
+
+// Whether a table has an inline API key among its own keys
+function hasSecretKey(table: unknown): boolean {
+  return (
+    !!table &&
+    typeof table === 'object' &&
+    Object.entries(table).some(
+      ([key, value]) => isSecretKey(key) && value !== undefined
+    )
+  )
+}
+
+/**
+ * Whether a config holds an inline API key: a top-level `*ApiKey` or any
+ * `providers.*.apiKey`
+ * Decided on the parsed, transformed config, so quoted (`"api-key" = ...`)
+ * and dotted (`providers.x.api-key = ...`) keys count too
+ *
+ * @param config - Config after TOML parsing and transformConfig
+ */
+function containsSecrets(config: unknown): boolean {
+  if (hasSecretKey(config)) {
+    return true
+  }
+  const providers = (config as { providers?: unknown } | null)?.providers
+  return (
+    !!providers &&
+    typeof providers === 'object' &&
+    Object.values(providers).some(hasSecretKey)
+  )
+}
+
+// Whether a config file exists, holds secrets and is not 0600
+function isInsecureConfigFile(path: string): boolean {
+  if (!fileExists(path) || getInsecureMode(path) === null) {
+    return false
+  }
+  try {
+    return containsSecrets(
+      transformConfig(TOML.parse(readFileSync(path, 'utf-8')))
+    )
+  } catch {
+    return false
+  }
+}
+
+// Check if file has correct permissions (Unix 0600)
+// Files without secrets (e.g. a checked-in project .clai.toml) are exempt,
+// since git can't preserve 0600
+function checkFilePermissions(path: string, config: unknown): void {
+  if (!containsSecrets(config)) {
+    return
+  }
+
+  const actualMode = getInsecureMode(path)
+
+  if (actualMode !== null) {
+    throw new ConfigError(
+      `Config file ${path} contains an API key but has insecure permissions ${actualMode.toString(8)}. Must be 0600.\n` +
+        `Fix with: chmod 600 ${path} (or run 'clai --fix-permissions')`
+    )
+  }
+}
+
+/**
+ * List existing config files that hold secrets but are not 0600
+ */
+export function findInsecureConfigFiles(): string[] {
+  return getConfigPaths().filter(isInsecureConfigFile)
+}
+
+/**
+ * Restrict a config file to owner read/write (0600)
+ * No-op on Windows
+ */
+export function fixConfigPermissions(path: string): void {
+  if (process.platform === 'win32') {
+    return
+  }
+
+  try {
+    chmodSync(path, 0o600)
   } catch (err) {
-    if (err instanceof ConfigError) {
-      throw err
-    }
-    // If we can't stat the file, that's ok - it might not exist
+    throw new ConfigError(
+      `Failed to fix permissions on ${path}: ${err instanceof Error ? err.message : String(err)}`
+    )
   }
 }
 
 // Custom error class with exit code
 export class ConfigError extends ClaiError {
-  constructor(message: string, code: number = 3) {
+  constructor(message: string, code: number = exitCodeForCategory('config')) {
     super(message, code)
     this.name = 'ConfigError'
     Object.setPrototypeOf(this, ConfigError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'config'
+  }
 }
 
 // Check if file exists and is readable
@@ -110,46 +286,208 @@ function fileExists(path: string): boolean {
   }
 }
 
-// Load a single config file
-function loadConfigFile(path: string): Partial<FileConfig> {
+// Read the optional top-level `include` key (a path or list of paths)
+function getIncludes(parsed: unknown, path: string): string[] {
+  const include = (parsed as Record<string, unknown>).include
+  if (include === undefined) {
+    return []
+  }
+  if (typeof include === 'string') {
+    return [include]
+  }
+  if (Array.isArray(include) && include.every((p) => typeof p === 'string')) {
+    return include
+  }
+  throw new ConfigError(
+    `Invalid config file ${path}: include must be a path or a list of paths`
+  )
+}
+
+// Edit distance between two short strings (for "did you mean" hints)
+function editDistance(a: string, b: string): number {
+  let previous = Array.from({ length: b.length + 1 }, (_, j) => j)
+  for (let i = 1; i <= a.length; i++) {
+    const current = [i]
+    for (let j = 1; j <= b.length; j++) {
+      current[j] = Math.min(
+        previous[j]! + 1,
+        current[j - 1]! + 1,
+        previous[j - 1]! + (a[i - 1] === b[j - 1] ? 0 : 1)
+      )
+    }
+    previous = current
+  }
+  return previous[b.length]!
+}
+
+// Closest candidate within a typo's distance (1 for short names, up to 2)
+function closestSection(name: string, candidates: string[]): string | null {
+  let best: string | null = null
+  let bestDistance = Infinity
+  for (const candidate of candidates) {
+    const distance = editDistance(name, candidate)
+    const allowed = Math.min(2, Math.max(1, Math.floor(candidate.length / 3)))
+    if (distance <= allowed && distance < bestDistance) {
+      best = candidate
+      bestDistance = distance
+    }
+  }
+  return best
+}
+
+// Top-level keys a config file may contain, besides the schema sections
+const EXTRA_TOP_LEVEL_KEYS = ['include']
+
+/**
+ * Warn about top-level keys the schema doesn't know (they would otherwise
+ * be dropped silently), suggesting the closest known section for typos
+ * such as [contex]
+ *
+ * @param parsed - Parsed TOML, before the kebab-to-camel transform
+ * @param path - File the keys came from
+ * @returns One warning per unknown key
+ */
+export function findUnknownSections(parsed: unknown, path: string): string[] {
+  // Section names are single words, so schema keys match the file's keys
+  const known = [
+    ...Object.keys(FileConfigSchema.shape),
+    ...EXTRA_TOP_LEVEL_KEYS,
+  ]
+  const bracket = (name: string, isTable: boolean) =>
+    isTable ? `[${name}]` : name
+
+  return Object.entries(parsed as Record<string, unknown>)
+    .filter(([key]) => !known.includes(toCamel(key)))
+    .map(([key, value]) => {
+      const isTable = value !== null && typeof value === 'object'
+      const warning =
+        `Unknown ${isTable ? 'section' : 'key'} ${bracket(key, isTable)} ` +
+        `in ${path} is ignored`
+      const suggestion = closestSection(key.toLowerCase(), known)
+      return suggestion
+        ? `${warning}; did you mean ${bracket(suggestion, isTable)}?`
+        : warning
+    })
+}
+
+// Load a single config file, with its includes
+// The raw tables are merged before zod runs, so schema defaults of the
+// including file can't overwrite values set in an included one
+function loadConfigFile(
+  path: string,
+  warnings: string[] = []
+): Partial<FileConfig> {
   if (!fileExists(path)) {
     return {}
   }
 
-  // Check permissions on Unix
-  checkFilePermissions(path)
+  const result = FileConfigSchema.safeParse(
+    readConfigTree(path, [], warnings)
+  )
+  if (!result.success) {
+    throw new ConfigError(
+      `Invalid config file ${path}: ${result.error.message}`
+    )
+  }
+  return result.data
+}
+
+// Merge raw (transformed, not yet validated) config tables; the override
+// wins, except provider.fallback lists, combined per fallback-merge
+function mergeRawConfigs(
+  base: Record<string, unknown>,
+  override: Record<string, unknown>
+): Record<string, unknown> {
+  const merged = deepMerge(base, override)
+
+  // Each file was validated on its own, so a present fallback is a list
+  type RawProvider = { fallback?: string[]; fallbackMerge?: FallbackMergeMode }
+  const baseProvider = base.provider as RawProvider | undefined
+  const overrideProvider = override.provider as RawProvider | undefined
+  if (baseProvider?.fallback && overrideProvider?.fallback) {
+    merged.provider = {
+      ...(merged.provider as RawProvider),
+      fallback: mergeFallback(
+        baseProvider.fallback,
+        overrideProvider.fallback,
+        overrideProvider.fallbackMerge ?? baseProvider.fallbackMerge
+      ),
+    }
+  }
+
+  return merged
+}
+
+// Read a config file and its includes into one raw table
+// `include` paths are resolved relative to the including file and merged
+// underneath it (the including file wins); `chain` tracks the include stack
+// and `warnings` collects non-fatal problems
+function readConfigTree(
+  path: string,
+  chain: string[],
+  warnings: string[]
+): Record<string, unknown> {
+  const absolutePath = resolve(path)
+  if (chain.includes(absolutePath)) {
+    throw new ConfigError(
+      `Config include cycle detected: ${[...chain, absolutePath].join(' -> ')}`
+    )
+  }
 
   try {
     const content = readFileSync(path, 'utf-8')
     const parsed = TOML.parse(content)
 
+    warnings.push(...findUnknownSections(parsed, path))
+
     // Transform kebab-case to camelCase for compatibility
     const transformed = transformConfig(parsed)
 
-    // Validate with Zod
+    // Check permissions on Unix (only matters for files with secrets)
+    checkFilePermissions(path, transformed)
+
+    // Validate with Zod here too, so errors name the file they are in
     const result = FileConfigSchema.safeParse(transformed)
     if (!result.success) {
       throw new ConfigError(
-        `Invalid config file ${path}: ${result.error.message}`,
-        3
+        `Invalid config file ${path}: ${result.error.message}`
       )
     }
 
-    return result.data
+    // Merge included files first so this file takes precedence
+    let included: Record<string, unknown> = {}
+    for (const include of getIncludes(transformed, path)) {
+      const includePath = resolve(dirname(absolutePath), include)
+      if (!fileExists(includePath)) {
+        throw new ConfigError(
+          `Config file ${path} includes missing file ${includePath}`
+        )
+      }
+      included = mergeRawConfigs(
+        included,
+        readConfigTree(includePath, [...chain, absolutePath], warnings)
+      )
+    }
+
+    return mergeRawConfigs(included, transformed as Record<string, unknown>)
   } catch (err) {
     if (err instanceof ConfigError) {
       throw err
     }
     if (err instanceof Error) {
       throw new ConfigError(
-        `Failed to parse config file ${path}: ${err.message}`,
-        3
+        `Failed to parse config file ${path}: ${err.message}`
       )
     }
     throw err
   }
 }
 
+// Convert one kebab-case key to camelCase
+function toCamel(key: string): string {
+  return key.replace(/-([a-z])/g, (_, letter) => letter.toUpperCase())
+}
+
 // Transform config keys from kebab-case to camelCase
 function transformConfig(obj: unknown): unknown {
   if (Array.isArray(obj)) {
@@ -160,10 +498,28 @@ function transformConfig(obj: unknown): unknown {
     const result: Record<string, unknown> = {}
     for (const [key, value] of Object.entries(obj)) {
       // Convert kebab-case to camelCase
-      const camelKey = key.replace(/-([a-z])/g, (_, letter) =>
-        letter.toUpperCase()
+      result[toCamel(key)] = transformConfig(value)
+    }
+    return result
+  }
+
+  return obj
+}
+
+// Transform config keys from camelCase back to kebab-case (inverse of transformConfig)
+function toKebabConfig(obj: unknown): unknown {
+  if (Array.isArray(obj)) {
+    return obj.map(toKebabConfig)
+  }
+
+  if (obj && typeof obj === 'object') {
+    const result: Record<string, unknown> = {}
+    for (const [key, value] of Object.entries(obj)) {
+      const kebabKey = key.replace(
+        /[A-Z]/g,
+        (letter) => `-${letter.toLowerCase()}`
       )
-      result[camelKey] = transformConfig(value)
+      result[kebabKey] = toKebabConfig(value)
     }
     return result
   }
@@ -201,85 +557,149 @@ function deepMerge<T extends Record<string, unknown>>(
   return result
 }
 
-// Load environment variables that override config
-function loadEnvConfig(): Partial<FileConfig> {
-  const envConfig: Partial<FileConfig> = {}
-
-  // Provider settings
-  if (process.env.CLAI_PROVIDER_DEFAULT || process.env.CLAI_PROVIDER_FALLBACK) {
-    envConfig.provider = {
-      default:
-        process.env.CLAI_PROVIDER_DEFAULT ?? DEFAULT_CONFIG.provider!.default,
-      fallback: process.env.CLAI_PROVIDER_FALLBACK
-        ? process.env.CLAI_PROVIDER_FALLBACK.split(',').map((s) => s.trim())
-        : DEFAULT_CONFIG.provider!.fallback,
-    }
+// Combine provider fallback lists from two config files
+// - replace: override list wins if non-empty (default)
+// - append: base list followed by override list
+// - prepend: override list followed by base list
+// Duplicates are dropped, keeping the first occurrence
+export function mergeFallback(
+  base: string[],
+  override: string[],
+  mode: FallbackMergeMode = 'replace'
+): string[] {
+  let merged: string[]
+  if (mode === 'append') {
+    merged = [...base, ...override]
+  } else if (mode === 'prepend') {
+    merged = [...override, ...base]
+  } else {
+    merged = override.length > 0 ? override : base
   }
+  return [...new Set(merged)]
+}
 
-  // Context settings
-  if (
-    process.env.CLAI_CONTEXT_MAX_FILES ||
-    process.env.CLAI_CONTEXT_MAX_HISTORY ||
-    process.env.CLAI_CONTEXT_REDACT_PATHS ||
-    process.env.CLAI_CONTEXT_REDACT_USERNAME
-  ) {
-    envConfig.context = {
-      maxFiles: process.env.CLAI_CONTEXT_MAX_FILES
-        ? parseInt(process.env.CLAI_CONTEXT_MAX_FILES, 10) ||
-          DEFAULT_CONFIG.context!.maxFiles
-        : DEFAULT_CONFIG.context!.maxFiles,
-      maxHistory: process.env.CLAI_CONTEXT_MAX_HISTORY
-        ? parseInt(process.env.CLAI_CONTEXT_MAX_HISTORY, 10) ||
-          DEFAULT_CONFIG.context!.maxHistory
-        : DEFAULT_CONFIG.context!.maxHistory,
-      redactPaths: process.env.CLAI_CONTEXT_REDACT_PATHS
-        ? process.env.CLAI_CONTEXT_REDACT_PATHS === 'true'
-        : DEFAULT_CONFIG.context!.redactPaths,
-      redactUsername: process.env.CLAI_CONTEXT_REDACT_USERNAME
-        ? process.env.CLAI_CONTEXT_REDACT_USERNAME === 'true'
-        : DEFAULT_CONFIG.context!.redactUsername,
+// Merge a higher-priority file config into the accumulated config
+export function mergeFileConfigs(
+  base: FileConfig,
+  override: Partial<FileConfig>
+): FileConfig {
+  const merged = deepMerge(base, override)
+
+  if (override.provider && base.provider) {
+    const mode =
+      override.provider.fallbackMerge ?? base.provider.fallbackMerge ?? 'replace'
+    merged.provider = {
+      ...merged.provider!,
+      fallback: mergeFallback(
+        base.provider.fallback,
+        override.provider.fallback,
+        mode
+      ),
     }
   }
 
-  // Safety settings
-  if (
-    process.env.CLAI_SAFETY_CONFIRM_DANGEROUS ||
-    process.env.CLAI_SAFETY_DANGEROUS_PATTERNS
-  ) {
-    envConfig.safety = {
-      confirmDangerous: process.env.CLAI_SAFETY_CONFIRM_DANGEROUS
-        ? process.env.CLAI_SAFETY_CONFIRM_DANGEROUS !== 'false'
-        : DEFAULT_CONFIG.safety!.confirmDangerous,
-      dangerousPatterns: process.env.CLAI_SAFETY_DANGEROUS_PATTERNS
-        ? process.env.CLAI_SAFETY_DANGEROUS_PATTERNS.split(',').map((s) =>
-            s.trim()
-          )
-        : DEFAULT_CONFIG.safety!.dangerousPatterns,
-    }
+  return merged
+}
+
+/**
+ * Parse a boolean environment variable value
+ * Accepts 1/0, true/false, yes/no, on/off (case-insensitive)
+ *
+ * @returns The parsed value, or undefined if unrecognized
+ */
+export function parseEnvBool(value: string): boolean | undefined {
+  switch (value.trim().toLowerCase()) {
+    case '1':
+    case 'true':
+    case 'yes':
+    case 'on':
+      return true
+    case '0':
+    case 'false':
+    case 'no':
+    case 'off':
+      return false
+    default:
+      return undefined
   }
+}
 
-  // UI settings
-  if (process.env.CLAI_UI_COLOR || process.env.CLAI_UI_PROMPT_TIMEOUT) {
-    const color = process.env.CLAI_UI_COLOR
-    const promptTimeout = process.env.CLAI_UI_PROMPT_TIMEOUT
-      ? parseInt(process.env.CLAI_UI_PROMPT_TIMEOUT, 10)
-      : undefined
+// Read a boolean env var; undefined if unset, or unrecognized (with a
+// warning), so the config file's value is kept
+function envBool(name: string, warnings: string[]): boolean | undefined {
+  const raw = process.env[name]
+  if (!raw) {
+    return undefined
+  }
+  const parsed = parseEnvBool(raw)
+  if (parsed === undefined) {
+    warnings.push(
+      `${name}='${raw}' is not a boolean (expected 1/0, true/false, yes/no, on/off); ignoring it`
+    )
+  }
+  return parsed
+}
+
+// Read an integer env var; undefined if unset or not a number
+function envInt(name: string): number | undefined {
+  const raw = process.env[name]
+  const parsed = raw ? parseInt(raw, 10) : NaN
+  return isNaN(parsed) ? undefined : parsed
+}
 
-    envConfig.ui = {
+// Read a comma-separated list env var; undefined if unset
+function envList(name: string): string[] | undefined {
+  return process.env[name]?.split(',').map((s) => s.trim())
+}
+
+// Env overrides: only the keys that have an env var set, so deepMerge
+// leaves every other file setting in the section alone
+type EnvConfig = {
+  [Section in keyof FileConfig]?: Partial<NonNullable<FileConfig[Section]>>
+}
+
+// Drop unset keys, and the whole section if none is left
+function definedOnly<T extends Record<string, unknown>>(
+  section: T
+): Partial<T> | undefined {
+  const entries = Object.entries(section).filter(([, v]) => v !== undefined)
+  return entries.length > 0
+    ? (Object.fromEntries(entries) as Partial<T>)
+    : undefined
+}
+
+// Load environment variables that override config
+function loadEnvConfig(warnings: string[]): EnvConfig {
+  const color = process.env.CLAI_UI_COLOR
+  const promptTimeout = envInt('CLAI_UI_PROMPT_TIMEOUT')
+
+  return {
+    provider: definedOnly({
+      default: process.env.CLAI_PROVIDER_DEFAULT || undefined,
+      fallback: envList('CLAI_PROVIDER_FALLBACK'),
+    }),
+    context: definedOnly({
+      // 0 is treated as unset
+      maxFiles: envInt('CLAI_CONTEXT_MAX_FILES') || undefined,
+      maxHistory: envInt('CLAI_CONTEXT_MAX_HISTORY') || undefined,
+      redactPaths: envBool('CLAI_CONTEXT_REDACT_PATHS', warnings),
+      redactUsername: envBool('CLAI_CONTEXT_REDACT_USERNAME', warnings),
+    }),
+    safety: definedOnly({
+      confirmDangerous: envBool('CLAI_SAFETY_CONFIRM_DANGEROUS', warnings),
+      dangerousPatterns: envList('CLAI_SAFETY_DANGEROUS_PATTERNS'),
+    }),
+    ui: definedOnly({
       color:
         color === 'auto' || color === 'always' || color === 'never'
           ? color
-          : DEFAULT_CONFIG.ui!.color,
-      interactive: DEFAULT_CONFIG.ui!.interactive,
-      debugLogFile: DEFAULT_CONFIG.ui!.debugLogFile,
+          : undefined,
       promptTimeout:
-        promptTimeout !== undefined && !isNaN(promptTimeout)
-          ? Math.max(0, Math.min(300000, promptTimeout))
-          : DEFAULT_CONFIG.ui!.promptTimeout,
-    }
+        promptTimeout === undefined
+          ? undefined
+          : Math.max(0, Math.min(300000, promptTimeout)),
+    }),
   }
-
-  return envConfig
 }
 
 // Load file config (with caching)
@@ -293,12 +713,17 @@ export function loadFileConfig(): FileConfig {
 
   // Load config files in order (lowest to highest priority)
   const configPaths = getConfigPaths()
+  const pinned = getPinnedConfigFile()
+  if (pinned && !fileExists(pinned)) {
+    throw new ConfigError(`Config file not found: ${pinned}`)
+  }
+  const fileWarnings: string[] = []
 
   for (const path of configPaths) {
     try {
-      const fileConfig = loadConfigFile(path)
+      const fileConfig = loadConfigFile(path, fileWarnings)
       if (Object.keys(fileConfig).length > 0) {
-        config = deepMerge(config, fileConfig)
+        config = mergeFileConfigs(config, fileConfig)
       }
     } catch (err) {
       if (err instanceof ConfigError && err.code === 3) {
@@ -309,8 +734,14 @@ export function loadFileConfig(): FileConfig {
   }
 
   // Apply environment overrides
-  const envConfig = loadEnvConfig()
-  config = deepMerge(config, envConfig)
+  const envWarnings: string[] = []
+  const envConfig = loadEnvConfig(envWarnings)
+  config = deepMerge(config, envConfig as Partial<FileConfig>)
+
+  // Range-check the merged result (env values bypass the schema)
+  const validated = validateFileConfig(config)
+  config = validated.config
+  configWarnings = [...fileWarnings, ...envWarnings, ...validated.warnings]
 
   // Cache the result
   configCache = config
@@ -321,6 +752,423 @@ export function loadFileConfig(): FileConfig {
 // Clear config cache (useful for testing)
 export function clearConfigCache(): void {
   configCache = null
+  configWarnings = []
+}
+
+// Warnings produced while loading the config (empty until loaded)
+export function getConfigWarnings(): string[] {
+  return [...configWarnings]
+}
+
+/** Shown at -v when no config file exists */
+export const MISSING_CONFIG_NOTICE = 'No config file found, using defaults'
+
+/**
+ * Notice for running without any config file, or null
+ * Silenced by --no-warn-missing-config or a truthy
+ * CLAI_NO_WARN_MISSING_CONFIG (for users who run config-less on purpose)
+ *
+ * @param config - Runtime configuration
+ * @param configMissing - Whether none of getConfigPaths() exists
+ * @param env - Environment to read CLAI_NO_WARN_MISSING_CONFIG from
+ */
+export function missingConfigNotice(
+  config: Config,
+  configMissing: boolean = !getConfigPaths().some((path) => existsSync(path)),
+  env: NodeJS.ProcessEnv = process.env
+): string | null {
+  if (!configMissing || config.noWarnMissingConfig) {
+    return null
+  }
+  const envValue = env.CLAI_NO_WARN_MISSING_CONFIG
+  if (envValue && parseEnvBool(envValue) !== false) {
+    return null
+  }
+  return MISSING_CONFIG_NOTICE
+}
+
+// Shown in place of secrets by --print-config
+const REDACTED = '********'
+
+/**
+ * Mask secrets (provider API keys) so the config can be printed or shared
+ */
+export function redactFileConfig(config: FileConfig): FileConfig {
+  const providers = Object.fromEntries(
+    Object.entries(config.providers ?? {}).map(([name, provider]) => [
+      name,
+      provider.apiKey ? { ...provider, apiKey: REDACTED } : provider,
+    ])
+  )
+  return { ...config, providers }
+}
+
+/**
+ * Serialize a config for --print-config
+ * Keys use the kebab-case names from config files and secrets are masked,
+ * so the TOML output can be saved as a .clai.toml
+ *
+ * @param config - Merged file config
+ * @param format - 'toml' (default) or 'json'
+ */
+export function formatFileConfig(
+  config: FileConfig,
+  format: 'toml' | 'json' = 'toml'
+): string {
+  // JSON round-trip drops undefined values, which TOML can't represent
+  const plain = toKebabConfig(
+    JSON.parse(JSON.stringify(redactFileConfig(config)))
+  ) as TOML.JsonMap
+
+  return format === 'json'
+    ? JSON.stringify(plain, null, 2) + '\n'
+    : TOML.stringify(plain)
+}
+
+/**
+ * Path of the per-user config file that --set writes to
+ * ($XDG_CONFIG_HOME/clai/config.toml, else ~/.config/clai/config.toml;
+ * a file pinned with --config / CLAI_CONFIG takes their place)
+ *
+ * @throws ConfigError if neither directory can be determined
+ */
+export function getUserConfigPath(): string {
+  const pinned = getPinnedConfigFile()
+  if (pinned) {
+    return pinned
+  }
+
+  const xdgConfig = process.env.XDG_CONFIG_HOME
+  if (xdgConfig) {
+    return join(xdgConfig, 'clai', 'config.toml')
+  }
+
+  const home = getHomeDir()
+  if (!home) {
+    throw new ConfigError(
+      'Cannot determine the user config directory (set HOME or XDG_CONFIG_HOME)'
+    )
+  }
+  return join(home, '.config', 'clai', 'config.toml')
+}
+
+// Whether a (camelCase) key path names a setting in FileConfigSchema
+function isKnownConfigKey(path: string[]): boolean {
+  let schema: z.ZodType = FileConfigSchema
+  for (const segment of path) {
+    while (schema instanceof z.ZodOptional || schema instanceof z.ZodDefault) {
+      schema = schema.unwrap() as z.ZodType
+    }
+    if (schema instanceof z.ZodObject) {
+      const field = (schema.shape as Record<string, z.ZodType>)[segment]
+      if (!field) {
+        return false
+      }
+      schema = field
+    } else if (schema instanceof z.ZodRecord) {
+      schema = schema.valueType as z.ZodType
+    } else {
+      return false
+    }
+  }
+  return true
+}
+
+/**
+ * Parse a --set assignment: `section.key=value`
+ * `<provider>.key` is shorthand for `providers.<provider>.key`. Values are
+ * read as TOML (true, 3, ["a", "b"], "quoted"), falling back to a plain
+ * string, so `provider.default=openrouter` needs no quotes
+ *
+ * @throws ConfigError on a malformed assignment or unknown key
+ */
+export function parseConfigAssignment(assignment: string): {
+  path: string[]
+  value: unknown
+} {
+  const eq = assignment.indexOf('=')
+  const key = eq === -1 ? '' : assignment.slice(0, eq).trim()
+  if (!key || key.split('.').some((segment) => !segment)) {
+    throw new ConfigError(
+      `Invalid --set '${assignment}': expected section.key=value`
+    )
+  }
+
+  const raw = assignment.slice(eq + 1).trim()
+  let value: unknown
+  try {
+    value = TOML.parse(`value = ${raw}`).value
+  } catch {
+    value = raw
+  }
+
+  const path = parseConfigKey(key)
+  if (path.length < 2) {
+    throw new ConfigError(`Unknown config key: ${key}`)
+  }
+
+  return { path, value }
+}
+
+/**
+ * An assignment as it may be echoed back, with an API key's value masked
+ *
+ * @param assignment - `section.key=value` as given to --set
+ */
+export function maskConfigAssignment(assignment: string): string {
+  const eq = assignment.indexOf('=')
+  const key = assignment.slice(0, eq).trim()
+  const name = key.split('.').pop() ?? ''
+  return eq !== -1 && isSecretKey(toCamel(name))
+    ? `${key}=${REDACTED}`
+    : assignment
+}
+
+/**
+ * Resolve a dotted config key (`ui.color`, `openrouter.model`, `context`)
+ * into its path in the config file, with the same `<provider>.key`
+ * shorthand as --set
+ *
+ * @throws ConfigError if the key names no setting or section
+ */
+export function parseConfigKey(key: string): string[] {
+  let path = key.trim().split('.')
+  if (path.some((segment) => !segment)) {
+    throw new ConfigError(`Unknown config key: ${key}`)
+  }
+  if (!(toCamel(path[0]!) in FileConfigSchema.shape)) {
+    path = ['providers', ...path]
+  }
+  if (!isKnownConfigKey(path.map(toCamel))) {
+    throw new ConfigError(`Unknown config key: ${key}`)
+  }
+  return path
+}
+
+/**
+ * Look up one setting or section of a config (`clai config get`)
+ * API keys are masked as in --print-config
+ *
+ * @param config - Merged file config
+ * @param key - Dotted key, e.g. ui.color or openrouter.model
+ * @returns The value formatted as TOML (a table for sections)
+ * @throws ConfigError if the key is unknown or not set
+ */
+export function getConfigValue(config: FileConfig, key: string): string {
+  const path = parseConfigKey(key)
+  let value: unknown = toKebabConfig(
+    JSON.parse(JSON.stringify(redactFileConfig(config)))
+  )
+  for (const segment of path) {
+    value =
+      value && typeof value === 'object' && !Array.isArray(value)
+        ? (value as Record<string, unknown>)[segment]
+        : undefined
+  }
+
+  if (value === undefined) {
+    throw new ConfigError(`${key} is not set`)
+  }
+  if (value && typeof value === 'object' && !Array.isArray(value)) {
+    return TOML.stringify(value as TOML.JsonMap)
+  }
+  if (typeof value === 'string') {
+    return value + '\n'
+  }
+  return TOML.stringify({ value } as TOML.JsonMap).replace(/^value = /, '')
+}
+
+/**
+ * Create the user config file with the default settings (`clai config init`)
+ * The file is written with 0600 since it may later hold API keys
+ *
+ * @param path - File to create (default: the user config file)
+ * @returns The file that was written
+ * @throws ConfigError if the file already exists or can't be written
+ */
+export function initUserConfig(path: string = getUserConfigPath()): string {
+  if (fileExists(path)) {
+    throw new ConfigError(`Config file ${path} already exists`)
+  }
+
+  try {
+    mkdirSync(dirname(path), { recursive: true })
+    writeFileSync(path, formatFileConfig(DEFAULT_CONFIG), { mode: 0o600 })
+  } catch (err) {
+    throw new ConfigError(
+      `Failed to write config file ${path}: ${err instanceof Error ? err.message : String(err)}`
+    )
+  }
+
+  clearConfigCache()
+  return path
+}
+
+/**
+ * Write --set assignments into a config file, keeping its other settings
+ * A new file is created with 0600; an existing file keeps its permissions.
+ * Comments in an existing file are not preserved.
+ *
+ * @param assignments - `section.key=value` strings
+ * @param path - File to update (default: the user config file)
+ * @returns The file that was written
+ * @throws ConfigError if a key is unknown or the result doesn't validate
+ */
+export function setConfigValues(
+  assignments: string[],
+  path: string = getUserConfigPath()
+): string {
+  const exists = fileExists(path)
+  let config: Record<string, unknown> = {}
+  if (exists) {
+    try {
+      config = TOML.parse(readFileSync(path, 'utf-8'))
+    } catch (err) {
+      throw new ConfigError(
+        `Failed to parse config file ${path}: ${err instanceof Error ? err.message : String(err)}`
+      )
+    }
+  }
+
+  for (const assignment of assignments) {
+    const { path: keyPath, value } = parseConfigAssignment(assignment)
+    let table = config
+    for (const segment of keyPath.slice(0, -1)) {
+      const next = table[segment]
+      if (!next || typeof next !== 'object' || Array.isArray(next)) {
+        table[segment] = {}
+      }
+      table = table[segment] as Record<string, unknown>
+    }
+    table[keyPath[keyPath.length - 1]!] = value
+  }
+
+  const result = FileConfigSchema.safeParse(transformConfig(config))
+  if (!result.success) {
+    throw new ConfigError(
+      `Invalid value for ${assignments.join(', ')}: ${result.error.message}`
+    )
+  }
+
+  try {
+    mkdirSync(dirname(path), { recursive: true })
+    const content = TOML.stringify(config as TOML.JsonMap)
+    if (exists) {
+      writeFileSync(path, content)
+    } else {
+      writeFileSync(path, content, { mode: 0o600 })
+    }
+  } catch (err) {
+    throw new ConfigError(
+      `Failed to write config file ${path}: ${err instanceof Error ? err.message : String(err)}`
+    )
+  }
+
+  // An existing file keeps its mode; once it holds a key it must be 0600
+  if (containsSecrets(transformConfig(config))) {
+    fixConfigPermissions(path)
+  }
+
+  clearConfigCache()
+  return path
+}
+
+// Request fields that extra-params may not override
+const RESERVED_EXTRA_PARAMS = ['model', 'messages']
+
+// Clamp a value into range, recording a warning when it was out of range
+function clampSetting(
+  name: string,
+  value: number,
+  [min, max]: [number, number],
+  warnings: string[]
+): number {
+  const clamped = Math.max(min, Math.min(max, value))
+  if (clamped !== value) {
+    warnings.push(
+      `${name} = ${value} is out of range (${min}-${max}); using ${clamped}`
+    )
+  }
+  return clamped
+}
+
+/**
+ * Validate a merged file config
+ * - Numeric limits are clamped into range and reported as warnings
+ * - An empty default provider is a hard error (nothing to call)
+ * - Conflicting safety.confirm-keys are a hard error
+ *
+ * @throws ConfigError (exit code 3) on unrecoverable values
+ */
+export function validateFileConfig(config: FileConfig): {
+  config: FileConfig
+  warnings: string[]
+} {
+  const warnings: string[] = []
+  const result: FileConfig = { ...config }
+
+  if (result.provider && !result.provider.default.trim()) {
+    throw new ConfigError('provider.default must not be empty')
+  }
+
+  for (const [name, provider] of Object.entries(result.providers ?? {})) {
+    const reserved = Object.keys(provider.extraParams ?? {}).filter((key) =>
+      RESERVED_EXTRA_PARAMS.includes(key)
+    )
+    if (reserved.length > 0) {
+      throw new ConfigError(
+        `providers.${name}.extra-params must not set ${reserved.join(', ')}`
+      )
+    }
+  }
+
+  if (result.context) {
+    result.context = {
+      ...result.context,
+      maxFiles: clampSetting(
+        'context.max-files',
+        result.context.maxFiles,
+        MAX_FILES_RANGE,
+        warnings
+      ),
+      maxHistory: clampSetting(
+        'context.max-history',
+        result.context.maxHistory,
+        MAX_HISTORY_RANGE,
+        warnings
+      ),
+    }
+  }
+
+  if (result.postprocess?.commandTemplate !== undefined) {
+    const problem = validateCommandTemplate(result.postprocess.commandTemplate)
+    if (problem) {
+      throw new ConfigError(`postprocess.command-template: ${problem}`)
+    }
+  }
+
+  if (result.safety?.confirmKeys) {
+    const problem = validateConfirmKeys(
+      resolveConfirmKeys(result.safety.confirmKeys)
+    )
+    if (problem) {
+      throw new ConfigError(`safety.confirm-keys: ${problem}`)
+    }
+  }
+
+  if (result.ui) {
+    result.ui = {
+      ...result.ui,
+      promptTimeout: clampSetting(
+        'ui.prompt-timeout',
+        result.ui.promptTimeout,
+        PROMPT_TIMEOUT_RANGE,
+        warnings
+      ),
+    }
+  }
+
+  return { config: result, warnings }
 }
 
 // Build runtime config from file config + CLI
@@ -347,42 +1195,109 @@ export function buildConfig(fileConfig: FileConfig, cli: Cli): Config {
   if (cli.debugFile !== undefined) {
     // CLI --debug-file (empty string means use default)
     if (cli.debugFile === '') {
-      debugLogFile = join(homedir(), '.cache', 'clai', 'debug.log')
+      // Without a home directory, fall back to the temp dir
+      debugLogFile = join(
+        getHomeDir() ?? tmpdir(),
+        '.cache',
+        'clai',
+        'debug.log'
+      )
     } else {
       debugLogFile = resolve(cli.debugFile)
     }
   } else if (fileConfig.ui?.debugLogFile) {
-    // From file config
-    const home = homedir()
-    debugLogFile = fileConfig.ui.debugLogFile
-      .replace(/^~\//, home + '/')
-      .replace(/^~$/, home)
+    // From file config (~ is left as-is if home can't be determined)
+    const home = getHomeDir()
+    debugLogFile = home
+      ? fileConfig.ui.debugLogFile
+          .replace(/^~\//, home + '/')
+          .replace(/^~$/, home)
+      : fileConfig.ui.debugLogFile
+  }
+
+  // Audit trail: --record wins over audit.log-file
+  let recordFile: string | undefined
+  if (cli.record !== undefined) {
+    recordFile = resolve(cli.record)
+  } else if (fileConfig.audit?.logFile) {
+    const home = getHomeDir()
+    recordFile = home
+      ? fileConfig.audit.logFile
+          .replace(/^~\//, home + '/')
+          .replace(/^~$/, home)
+      : fileConfig.audit.logFile
   }
 
   return {
-    provider: fileConfig.provider ?? DEFAULT_CONFIG.provider!,
-    context: fileConfig.context ?? DEFAULT_CONFIG.context!,
-    safety: fileConfig.safety ?? DEFAULT_CONFIG.safety!,
+    provider: {
+      ...(fileConfig.provider ?? DEFAULT_CONFIG.provider!),
+      stopOnAuthError:
+        cli.noFallbackOnAuth || (fileConfig.provider?.stopOnAuthError ?? false),
+      jsonRetries: fileConfig.provider?.jsonRetries ?? DEFAULT_JSON_RETRIES,
+      userAgent: fileConfig.provider?.userAgent ?? DEFAULT_USER_AGENT,
+      minIntervalMs: fileConfig.provider?.minIntervalMs ?? 0,
+      reasoningEffort: cli.reasoning ?? fileConfig.provider?.reasoningEffort,
+    },
+    context: {
+      ...(fileConfig.context ?? DEFAULT_CONFIG.context!),
+      ...(cli.historyOnly && { mode: 'history' as const }),
+    },
+    safety: {
+      ...(fileConfig.safety ?? DEFAULT_CONFIG.safety!),
+      confirmKeys: resolveConfirmKeys(fileConfig.safety?.confirmKeys),
+    },
     ui: {
       color,
       debugLogFile,
       interactive,
       numOptions,
+      numOptionsExplicit: cli.numOptionsExplicit,
+      interactiveNumOptions:
+        fileConfig.ui?.numOptions ?? DEFAULT_INTERACTIVE_NUM_OPTIONS,
       promptTimeout: fileConfig.ui?.promptTimeout ?? DEFAULT_CONFIG.ui!.promptTimeout!,
+      commandJoin: fileConfig.ui?.commandJoin ?? DEFAULT_COMMAND_JOIN,
+      interactiveDefaultAction:
+        fileConfig.ui?.interactiveDefaultAction ?? DEFAULT_INTERACTIVE_ACTION,
     },
+    prompt: fileConfig.prompt ?? DEFAULT_CONFIG.prompt!,
+    postprocess: fileConfig.postprocess,
     providers: fileConfig.providers ?? DEFAULT_CONFIG.providers!,
 
     // CLI overrides
     model: cli.model,
+    modelFallback: cli.modelFallback,
     providerName: cli.provider,
+    apiKeyEnv: cli.apiKeyEnv,
     quiet: cli.quiet,
     verbose: cli.verbose,
     force: cli.force,
     dryRun: cli.dryRun,
+    previewScript: cli.previewScript,
     contextFile: cli.context,
     offline: cli.offline,
     debug: cli.debug,
     debugFile: cli.debugFile,
+    recordFile,
+    summarizeContext: cli.summarizeContext,
+    showRaw: cli.showRaw || cli.rawFile !== undefined,
+    dumpRequest: cli.dumpRequest,
+    refreshContext: cli.refreshContext,
+    noCacheSystemInfo: cli.noCacheSystemInfo,
+    stats: cli.stats,
+    rawFile: cli.rawFile,
+    explainDanger: cli.explainDanger,
+    onlyIfSafe: cli.onlyIfSafe,
+    strictJson: cli.strictJson,
+    noStdin: cli.noStdin || cli.batch, // in batch mode stdin holds instructions
+    stdinName: cli.stdinAsFile,
+    lastExit: cli.lastExit,
+    inlineContext: cli.inlineContext,
+    batchConcurrency: cli.concurrency,
+    noSpinner: cli.noSpinner,
+    noWarnMissingConfig: cli.noWarnMissingConfig,
+    jsonErrors: cli.jsonErrors,
+    outputFormat: cli.format,
+    logLevel: cli.logLevel,
     instruction: cli.instruction,
   }
 }
@@ -393,11 +1308,29 @@ export function getConfig(cli: Cli): Config {
   return buildConfig(fileConfig, cli)
 }
 
+/**
+ * Resolve how many command options to generate
+ * - An explicit -o always wins
+ * - Interactive: ui.num-options (default 3), since the user picks one
+ * - Non-interactive: 1, since only the first command is used
+ */
+export function resolveNumOptions(config: Config, interactive: boolean): number {
+  if (config.ui.numOptionsExplicit) {
+    return config.ui.numOptions
+  }
+  return interactive ? config.ui.interactiveNumOptions : 1
+}
+
 // Get API key for a provider (with env var resolution)
+// --api-key-env overrides everything for the selected provider
 export function getProviderApiKey(
   providerName: string,
   config: Config
 ): string | undefined {
+  if (config.apiKeyEnv && providerName === resolveProviderName(config)) {
+    return process.env[config.apiKeyEnv]
+  }
+
   const providerConfig = config.providers[providerName]
 
   if (!providerConfig) {
@@ -435,6 +1368,29 @@ export function getProviderApiKey(
   return undefined
 }
 
+/**
+ * Resolve which provider serves the request
+ * Priority: -p/--provider > a provider.model-provider-hints entry whose
+ * prefix matches --model > provider.default
+ */
+export function resolveProviderName(config: Config): string {
+  if (config.providerName) {
+    return config.providerName
+  }
+
+  const model = config.model
+  if (model) {
+    const hint = config.provider.modelProviderHints?.find((h) =>
+      model.startsWith(h.prefix)
+    )
+    if (hint) {
+      return hint.provider
+    }
+  }
+
+  return config.provider.default
+}
+
 // Get model for a provider
 export function getProviderModel(providerName: string, config: Config): string {
   // Priority: CLI --model > provider config > default
@@ -1,4 +1,10 @@
 import { z } from 'zod'
+import type { SystemField, FileSort } from '../context/types.js'
+import type { OutputFormat } from '../output/format.js'
+import type { LogLevelName } from '../logging/logger.js'
+import type { CommandJoin } from '../ui/join.js'
+import type { ConfirmKeys, InteractiveDefaultAction } from '../ui/keys.js'
+import { REASONING_EFFORTS, type ReasoningEffort } from '../ai/types.js'
 
 // Provider configuration
 export const ProviderConfigSchema = z.object({
@@ -6,30 +12,112 @@ export const ProviderConfigSchema = z.object({
   apiKeyEnv: z.string().optional(),
   model: z.string().optional(),
   endpoint: z.string().optional(),
+  // OpenRouter attribution headers (HTTP-Referer / X-Title)
+  referer: z.string().optional(),
+  title: z.string().optional(),
+  // Extra fields merged into the request body (top_p, stop, ...)
+  extraParams: z.record(z.string(), z.unknown()).optional(),
 })
 
 export type ProviderConfig = z.infer<typeof ProviderConfigSchema>
 
+/** Maps model names starting with `prefix` to `provider` */
+export interface ModelProviderHint {
+  prefix: string
+  provider: string
+}
+
+export type FallbackMergeMode = 'replace' | 'append' | 'prepend'
+
 // File config schema (from TOML files) - all nested objects are optional for partial configs
 export const FileConfigSchema = z.object({
   provider: z
     .object({
       default: z.string().default('openrouter'),
       fallback: z.array(z.string()).default([]),
+      // How this file's fallback list combines with lower-priority files
+      fallbackMerge: z.enum(['replace', 'append', 'prepend']).optional(),
+      // Don't try fallbacks when the provider rejects the API key
+      stopOnAuthError: z.boolean().optional(),
+      // Re-ask for JSON this many times when a multi-command reply isn't JSON
+      jsonRetries: z.number().int().min(0).max(5).optional(),
+      // User-Agent for provider requests (default clai/<version>)
+      userAgent: z.string().optional(),
+      // Minimum milliseconds between requests from one clai process
+      minIntervalMs: z.number().int().min(0).optional(),
+      // Effort hint for reasoning models, sent where the provider supports it
+      reasoningEffort: z.enum(REASONING_EFFORTS).optional(),
+      // Route --model to a provider by model-name prefix when -p isn't given
+      modelProviderHints: z
+        .array(z.object({ prefix: z.string().min(1), provider: z.string() }))
+        .optional(),
     })
     .optional(),
   context: z
     .object({
-      maxFiles: z.number().int().min(1).max(100).default(10),
-      maxHistory: z.number().int().min(0).max(50).default(3),
+      // Range-checked (clamped with a warning) by validateFileConfig
+      maxFiles: z.number().int().default(10),
+      maxHistory: z.number().int().default(3),
       redactPaths: z.boolean().default(false),
       redactUsername: z.boolean().default(false),
+      // Text that replaces the home directory / username when redacting
+      redactionPlaceholder: z.string().min(1).default('[REDACTED]'),
+      mode: z.enum(['full', 'history']).default('full'),
+      // Directory listing order before truncating to max-files
+      fileSort: z.enum(['name', 'mtime']).default('name'),
+      // List package managers found on PATH (apt, brew, ...) in the prompt
+      detectPackageManagers: z.boolean().default(true),
+      // Current local date/time and timezone in the prompt
+      includeTime: z.boolean().default(true),
+      // Active venv/conda/nvm and project markers in the prompt
+      detectEnvironments: z.boolean().default(true),
+      // One-line project description (package.json/Cargo.toml/README)
+      includeProjectDescription: z.boolean().default(false),
+      // Ask the shell for history when no history file has any (spawns it)
+      historyViaShell: z.boolean().default(false),
+      // Allowlist of system info fields sent to the AI
+      systemFields: z
+        .array(
+          z.enum([
+            'os-name',
+            'os-version',
+            'architecture',
+            'shell',
+            'user',
+            'total-memory',
+          ])
+        )
+        .default([
+          'os-name',
+          'os-version',
+          'architecture',
+          'shell',
+          'user',
+          'total-memory',
+        ]),
     })
     .optional(),
   safety: z
     .object({
       confirmDangerous: z.boolean().default(true),
       dangerousPatterns: z.array(z.string()).default([]),
+      disablePatterns: z
+        .array(z.union([z.string(), z.number().int().min(0)]))
+        .optional(),
+      extraPatterns: z.array(z.string()).optional(),
+      // Flag `... | sh`, `... | python` etc. regardless of the pattern list
+      confirmPipeToInterpreter: z.boolean().default(false),
+      maxCommandLength: z.number().int().min(0).default(4096),
+      maxCommandLines: z.number().int().min(0).default(100),
+      // Shortcut keys for the confirmation prompt actions
+      confirmKeys: z
+        .object({
+          execute: z.string(),
+          abort: z.string(),
+          regenerate: z.string(),
+        })
+        .partial()
+        .optional(),
     })
     .optional(),
   ui: z
@@ -37,7 +125,35 @@ export const FileConfigSchema = z.object({
       color: z.enum(['auto', 'always', 'never']).default('auto'),
       debugLogFile: z.string().optional(),
       interactive: z.boolean().default(false),
-      promptTimeout: z.number().int().min(0).max(300000).default(30000),
+      promptTimeout: z.number().int().default(30000),
+      // Options to generate when interactive and -o isn't given
+      numOptions: z.number().int().min(1).max(10).optional(),
+      // Separator for running several selected options as one command
+      commandJoin: z.enum(['&&', ';', '|']).optional(),
+      // What Enter does on the interactive prompt
+      interactiveDefaultAction: z.enum(['execute', 'output']).optional(),
+    })
+    .optional(),
+  prompt: z
+    .object({
+      instructionPrefix: z.string().default(''),
+      instructionSuffix: z.string().default(''),
+      // Remove <think>...</think> and similar blocks from responses
+      stripReasoningTags: z.boolean().default(true),
+      // Order of the user prompt sections; unknown names are skipped
+      sectionOrder: z.array(z.string()).optional(),
+    })
+    .optional(),
+  postprocess: z
+    .object({
+      // Wraps the chosen command, e.g. "time {command}"; checked on load
+      commandTemplate: z.string().optional(),
+    })
+    .optional(),
+  audit: z
+    .object({
+      // JSON-lines audit trail, one record per run (like --record)
+      logFile: z.string().optional(),
     })
     .optional(),
   providers: z.record(z.string(), ProviderConfigSchema).default({}),
@@ -51,6 +167,12 @@ export interface Config {
   provider: {
     default: string
     fallback: string[]
+    stopOnAuthError: boolean
+    jsonRetries: number // 0 = no retry
+    userAgent: string
+    minIntervalMs?: number // 0 = no throttle
+    reasoningEffort?: ReasoningEffort // --reasoning / provider.reasoning-effort
+    modelProviderHints?: ModelProviderHint[] // first matching prefix wins
   }
 
   // Context settings
@@ -59,12 +181,27 @@ export interface Config {
     maxHistory: number
     redactPaths: boolean
     redactUsername: boolean
+    redactionPlaceholder?: string // default [REDACTED]; ~ keeps paths runnable
+    mode: 'full' | 'history' // history = only shell history in the prompt
+    fileSort: FileSort
+    detectPackageManagers: boolean
+    includeTime: boolean
+    detectEnvironments: boolean
+    includeProjectDescription?: boolean
+    historyViaShell?: boolean // default false
+    systemFields: SystemField[]
   }
 
   // Safety settings
   safety: {
     confirmDangerous: boolean
     dangerousPatterns: string[]
+    disablePatterns?: (string | number)[] // pattern strings or indices into the base list
+    extraPatterns?: string[] // appended after the base list
+    confirmPipeToInterpreter: boolean // flag pipes into sh/python/node/...
+    maxCommandLength: number // characters, 0 = no limit
+    maxCommandLines: number // lines, 0 = no limit
+    confirmKeys: ConfirmKeys // prompt shortcuts, default e/a/r
   }
 
   // UI settings
@@ -72,8 +209,25 @@ export interface Config {
     color: 'auto' | 'always' | 'never'
     debugLogFile?: string
     interactive: boolean
-    numOptions: number // 1-10, from CLI
+    numOptions: number // 1-10, from CLI (see resolveNumOptions)
+    numOptionsExplicit: boolean // -o was given on the command line
+    interactiveNumOptions: number // 1-10, default when interactive
     promptTimeout: number // milliseconds, 0 = no timeout, default 30000
+    commandJoin: CommandJoin // joins multi-selected options, default &&
+    interactiveDefaultAction: InteractiveDefaultAction // Enter: run or print, default execute
+  }
+
+  // Prompt settings
+  prompt: {
+    instructionPrefix: string
+    instructionSuffix: string
+    stripReasoningTags?: boolean // default true
+    sectionOrder?: string[] // default system, directory, history, stdin, instruction
+  }
+
+  // Post-processing of the chosen command
+  postprocess?: {
+    commandTemplate?: string // must contain {command}; default no change
   }
 
   // Provider-specific configs
@@ -81,14 +235,38 @@ export interface Config {
 
   // CLI overrides
   model?: string
+  modelFallback: string[] // --model-fallback, for the primary provider
   providerName?: string
+  apiKeyEnv?: string // --api-key-env, for the selected provider
   quiet: boolean
   verbose: number
   force: boolean
   dryRun: boolean
+  previewScript?: boolean // --preview-script
   contextFile?: string
   offline: boolean
   debug: boolean
   debugFile?: string
+  recordFile?: string // --record or audit.log-file, resolved path
+  summarizeContext: boolean // --summarize-context
+  showRaw: boolean // --show-raw (implied by --raw-file)
+  dumpRequest?: boolean // --dump-request
+  refreshContext?: boolean // --refresh-context
+  noCacheSystemInfo?: boolean // --no-cache-system-info
+  stats?: boolean // --stats
+  rawFile?: string // --raw-file
+  explainDanger: boolean
+  onlyIfSafe: boolean // --only-if-safe
+  strictJson: boolean // --strict-json
+  noStdin: boolean
+  noSpinner: boolean // --no-spinner (CLAI_NO_SPINNER is checked at use)
+  noWarnMissingConfig?: boolean // --no-warn-missing-config (env checked at use)
+  stdinName?: string // --stdin-as-file label for piped stdin
+  lastExit?: number // --last-exit, status of the user's previous command
+  inlineContext?: string // trailing positionals after the instruction
+  batchConcurrency?: number // --concurrency, --batch requests in flight
+  jsonErrors: boolean
+  outputFormat: OutputFormat
+  logLevel?: LogLevelName // --log-level, overrides quiet/verbose
   instruction: string
 }
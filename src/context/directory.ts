@@ -1,9 +1,10 @@
 // Directory context gathering
 
-import { readdirSync } from 'fs'
-import { basename } from 'path'
-import { ContextError } from './types.js'
-import { redactPath } from './redaction.js'
+import { opendirSync, statSync, type Dir } from 'fs'
+import { basename, join } from 'path'
+import { ContextError, type FileSort } from './types.js'
+import { redactPath, DEFAULT_REDACTION_PLACEHOLDER } from './redaction.js'
+import { selectTopN } from './topn.js'
 
 const PATH_TRUNCATE_LENGTH = 80
 
@@ -36,9 +37,45 @@ function truncatePath(
   return basename(path)
 }
 
+/**
+ * Modification time for sorting; unreadable entries sort last
+ */
+function getMtime(path: string): number {
+  try {
+    return statSync(path).mtimeMs
+  } catch {
+    return 0
+  }
+}
+
+/**
+ * Stream entry names from an open directory without building a full list
+ */
+function* readNames(dir: Dir): Generator<string> {
+  let entry = dir.readSync()
+  while (entry !== null) {
+    yield entry.name
+    entry = dir.readSync()
+  }
+}
+
+/**
+ * Stream entry names paired with their modification times
+ */
+function* readMtimes(
+  dir: Dir,
+  cwd: string
+): Generator<{ name: string; mtime: number }> {
+  for (const name of readNames(dir)) {
+    yield { name, mtime: getMtime(join(cwd, name)) }
+  }
+}
+
 /**
  * Scan current directory and return list of files
- * - Limited to maxFiles (sorted alphabetically)
+ * - Limited to maxFiles (sorted alphabetically, or newest first with 'mtime')
+ * - Only the top maxFiles entries are kept while scanning, so very large
+ *   directories don't need to be loaded in full
  * - Paths truncated to 80 chars (using basename if too long)
  * - Optionally redacted for privacy
  *
@@ -46,19 +83,32 @@ function truncatePath(
  */
 export function scanDirectory(
   maxFiles: number,
-  redactPaths: boolean
+  redactPaths: boolean,
+  sort: FileSort = 'name',
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
 ): string[] {
   const cwd = getCwd()
 
   try {
-    // Read directory entries
-    const entries = readdirSync(cwd, { withFileTypes: true })
+    const dir = opendirSync(cwd)
+    let sorted: string[]
 
-    // Sort by name and take first maxFiles
-    const sorted = entries
-      .map((entry) => entry.name)
-      .sort((a, b) => a.localeCompare(b))
-      .slice(0, maxFiles)
+    try {
+      if (sort === 'mtime') {
+        // Newest first; names break ties
+        sorted = selectTopN(
+          readMtimes(dir, cwd),
+          maxFiles,
+          (a, b) => b.mtime - a.mtime || a.name.localeCompare(b.name)
+        ).map((entry) => entry.name)
+      } else {
+        sorted = selectTopN(readNames(dir), maxFiles, (a, b) =>
+          a.localeCompare(b)
+        )
+      }
+    } finally {
+      dir.closeSync()
+    }
 
     // Apply truncation and redaction
     return sorted.map((fileName) => {
@@ -66,7 +116,7 @@ export function scanDirectory(
       let result = truncatePath(fullPath)
 
       if (redactPaths) {
-        result = redactPath(result)
+        result = redactPath(result, placeholder)
       }
 
       return result
@@ -80,10 +130,13 @@ export function scanDirectory(
 /**
  * Get redacted current working directory
  */
-export function getRedactedCwd(redactPaths: boolean): string {
+export function getRedactedCwd(
+  redactPaths: boolean,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): string {
   const cwd = getCwd()
   if (redactPaths) {
-    return redactPath(cwd)
+    return redactPath(cwd, placeholder)
   }
   return cwd
 }
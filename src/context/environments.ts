@@ -0,0 +1,77 @@
+// Active language environments (venv, conda, nvm), so the model suggests
+// e.g. `pip install` inside the venv rather than `sudo pip`
+
+import { existsSync, statSync } from 'fs'
+import { join } from 'path'
+import { redactPath, DEFAULT_REDACTION_PLACEHOLDER } from './redaction.js'
+
+// Project marker files, in prompt order
+const PROJECT_MARKERS: readonly [file: string, label: string][] = [
+  ['package.json', 'node project (package.json)'],
+  ['pyproject.toml', 'python project (pyproject.toml)'],
+  ['requirements.txt', 'python project (requirements.txt)'],
+]
+
+// Unactivated virtualenv directories worth mentioning
+const VENV_DIRS = ['.venv', 'venv']
+
+function isDirectory(path: string): boolean {
+  try {
+    return statSync(path).isDirectory()
+  } catch {
+    return false
+  }
+}
+
+/**
+ * Detect active language environments and project markers
+ *
+ * Sources:
+ * - VIRTUAL_ENV: activated Python virtualenv
+ * - CONDA_DEFAULT_ENV: active conda environment
+ * - NVM_BIN: Node selected through nvm (version taken from the path)
+ * - .venv/ or venv/ in cwd when no virtualenv is active
+ * - package.json, pyproject.toml, requirements.txt in cwd
+ *
+ * @param env - Environment to inspect (defaults to process.env)
+ * @param cwd - Directory to look for marker files in
+ * @param redactPaths - Redact home directory paths
+ * @param placeholder - Replacement for the home directory
+ * @returns Human-readable descriptions, e.g. "python venv /srv/app/.venv"
+ */
+export function detectEnvironments(
+  env: NodeJS.ProcessEnv = process.env,
+  cwd: string = process.cwd(),
+  redactPaths: boolean = false,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): string[] {
+  const found: string[] = []
+  const showPath = (path: string) =>
+    redactPaths ? redactPath(path, placeholder) : path
+
+  if (env.VIRTUAL_ENV) {
+    found.push(`python venv ${showPath(env.VIRTUAL_ENV)} (active)`)
+  } else {
+    const venv = VENV_DIRS.find((dir) => isDirectory(join(cwd, dir)))
+    if (venv) {
+      found.push(`python venv ${venv}/ (not activated)`)
+    }
+  }
+
+  if (env.CONDA_DEFAULT_ENV) {
+    found.push(`conda env ${env.CONDA_DEFAULT_ENV} (active)`)
+  }
+
+  if (env.NVM_BIN) {
+    const version = env.NVM_BIN.match(/versions\/node\/(v[^/\\]+)/)?.[1]
+    found.push(version ? `nvm node ${version} (active)` : 'nvm node (active)')
+  }
+
+  for (const [file, label] of PROJECT_MARKERS) {
+    if (existsSync(join(cwd, file))) {
+      found.push(label)
+    }
+  }
+
+  return found
+}
@@ -1,17 +1,27 @@
 // Shell history gathering
 
+import { execFile } from 'child_process'
 import { readFileSync } from 'fs'
+import { readFile } from 'fs/promises'
 import { join } from 'path'
-import { homedir } from 'os'
+import { promisify } from 'util'
+import { getHomeDir } from './home.js'
 
-// History file paths by shell
+const execFileAsync = promisify(execFile)
+
+// History file paths by shell, relative to home, in the order tried:
+// the shell's default, then locations used by frameworks (prezto's
+// .zhistory, zsh-newuser-install's .histfile) and XDG-style setups
 const HISTORY_PATHS: Record<string, string[]> = {
-  bash: ['.bash_history'],
-  zsh: ['.zsh_history'],
+  bash: ['.bash_history', '.bash_eternal_history', '.local/state/bash/history'],
+  zsh: ['.zsh_history', '.zhistory', '.histfile', '.local/state/zsh/history'],
   fish: ['.local/share/fish/fish_history'],
   sh: ['.sh_history'],
 }
 
+// How long the context.history-via-shell fallback may take
+const SHELL_HISTORY_TIMEOUT_MS = 2000
+
 /**
  * Detect shell from SHELL environment variable
  * Returns shell name (e.g., 'bash', 'zsh') or null if unknown
@@ -31,41 +41,153 @@ function detectShell(): string | null {
 }
 
 /**
- * Get history file path for a given shell
+ * History files to try for a shell, in order
+ * An exported $HISTFILE comes first, then $ZDOTDIR (zsh) or
+ * $XDG_DATA_HOME (fish), then the locations in HISTORY_PATHS under home
+ *
+ * @param shell - Shell name from detectShell
+ * @param home - Home directory, or null if unknown
+ * @param env - Environment to read HISTFILE/ZDOTDIR/XDG_DATA_HOME from
  */
-function getHistoryPath(shell: string): string | null {
-  const home = homedir()
-  if (!home) return null
-
+export function getHistoryCandidates(
+  shell: string,
+  home: string | null,
+  env: NodeJS.ProcessEnv = process.env
+): string[] {
   const paths = HISTORY_PATHS[shell]
-  if (!paths) return null
+  if (!paths) return []
 
-  return join(home, paths[0]!)
+  const candidates: string[] = []
+  if (env.HISTFILE && shell !== 'fish') {
+    candidates.push(env.HISTFILE)
+  }
+  if (shell === 'zsh' && env.ZDOTDIR) {
+    candidates.push(
+      join(env.ZDOTDIR, '.zsh_history'),
+      join(env.ZDOTDIR, '.zhistory')
+    )
+  }
+  if (shell === 'fish' && env.XDG_DATA_HOME) {
+    candidates.push(join(env.XDG_DATA_HOME, 'fish', 'fish_history'))
+  }
+  if (home) {
+    candidates.push(...paths.map((path) => join(home, path)))
+  }
+
+  return [...new Set(candidates)]
+}
+
+// Printed around the history dump, so whatever the rc files write to
+// stdout (banners, instant-prompt output, motd) isn't taken as history
+const SHELL_HISTORY_MARKER = '__clai_history__'
+
+// Arguments that make a shell print its recent history between markers,
+// one command per line without numbers (fish prints newest first)
+function historyDumpArgs(shell: string, numLines: number): string[] | null {
+  const mark = `echo ${SHELL_HISTORY_MARKER}`
+  switch (shell) {
+    case 'bash':
+    case 'zsh':
+      return ['-ic', `${mark}; fc -ln -${numLines}; ${mark}`]
+    case 'fish':
+      return ['-c', `${mark}; history --max ${numLines}; ${mark}`]
+    default:
+      return null
+  }
+}
+
+/**
+ * Lines between the first pair of history markers in a shell's output
+ * Returns an empty array when the markers are missing (the shell didn't
+ * get to run the dump)
+ */
+export function extractShellHistory(stdout: string): string[] {
+  const lines = stdout.split('\n').map((line) => line.trim())
+  const start = lines.indexOf(SHELL_HISTORY_MARKER)
+  const end = lines.indexOf(SHELL_HISTORY_MARKER, start + 1)
+  if (start === -1 || end === -1) {
+    return []
+  }
+  return lines.slice(start + 1, end)
 }
 
 /**
- * Read last N lines from a file efficiently
- * Uses tail-like approach: seek to end minus ~4KB, read, take last N lines
+ * Ask the shell itself for recent history (context.history-via-shell)
+ * Last resort for history kept somewhere we don't know to look; spawns an
+ * interactive shell, so it is opt-in and time-limited
+ * Returns an empty array on any failure
  */
-function readLastLines(filePath: string, numLines: number): string[] {
+export async function readHistoryViaShell(
+  shell: string,
+  numLines: number
+): Promise<string[]> {
+  const args = historyDumpArgs(shell, numLines)
+  const shellPath = process.env.SHELL
+  if (!args || !shellPath) {
+    return []
+  }
+
+  try {
+    const { stdout } = await execFileAsync(shellPath, args, {
+      timeout: SHELL_HISTORY_TIMEOUT_MS,
+      encoding: 'utf-8',
+    })
+    const lines = lastLines(extractShellHistory(stdout).join('\n'), numLines)
+    return shell === 'fish' ? lines.reverse() : lines
+  } catch {
+    return []
+  }
+}
+
+// Last N non-empty lines of file content (none for N <= 0: slice(-0)
+// would return everything)
+function lastLines(content: string, numLines: number): string[] {
+  if (numLines <= 0) {
+    return []
+  }
+  const lines = content.split('\n')
+
+  // Filter out empty lines and get last N
+  const nonEmptyLines = lines.filter((line) => line.trim().length > 0)
+  return nonEmptyLines.slice(-numLines)
+}
+
+/**
+ * Read the last N non-empty lines of a history file
+ * The whole file is read, so an entry is never cut at the front however
+ * long it is (a seek-from-end window would need to drop a partial line)
+ * Returns an empty array if the file can't be read
+ */
+export function readHistoryTail(filePath: string, numLines: number): string[] {
   try {
     // Read entire file - for most history files this is fine
     // For very large files, we'd use a streaming approach
-    const content = readFileSync(filePath, 'utf-8')
-    const lines = content.split('\n')
-
-    // Filter out empty lines and get last N
-    const nonEmptyLines = lines.filter((line) => line.trim().length > 0)
-    return nonEmptyLines.slice(-numLines)
+    return lastLines(readFileSync(filePath, 'utf-8'), numLines)
   } catch {
     // File doesn't exist or can't be read
     return []
   }
 }
 
+/**
+ * Non-blocking readHistoryTail, so a slow history file (e.g. on NFS)
+ * doesn't stall the event loop while other context is gathered
+ */
+export async function readHistoryTailAsync(
+  filePath: string,
+  numLines: number
+): Promise<string[]> {
+  try {
+    return lastLines(await readFile(filePath, 'utf-8'), numLines)
+  } catch {
+    return []
+  }
+}
+
 /**
  * Get shell history
- * Returns last N commands based on detected shell
+ * Returns last N commands based on detected shell, from the first
+ * candidate file (see getHistoryCandidates) that has any
  *
  * - bash: ~/.bash_history
  * - zsh: ~/.zsh_history
@@ -80,18 +202,41 @@ export function getShellHistory(maxHistory: number): string[] {
     return []
   }
 
-  const historyPath = getHistoryPath(shell)
-  if (!historyPath) {
-    return []
-  }
-
-  const lines = readLastLines(historyPath, maxHistory)
-
   // For fish shell, we read raw lines (matching Rust behavior)
   // Fish uses format: "- cmd: <command>" but we return raw lines
   // This matches the PRD: "The Rust code does NOT parse fish's - cmd: ... format"
+  for (const path of getHistoryCandidates(shell, getHomeDir())) {
+    const lines = readHistoryTail(path, maxHistory)
+    if (lines.length > 0) {
+      return lines
+    }
+  }
+
+  return []
+}
+
+/**
+ * Async getShellHistory, used on the gatherContext path
+ * With `viaShell` (context.history-via-shell), asks the shell when no
+ * history file has any lines
+ */
+export async function getShellHistoryAsync(
+  maxHistory: number,
+  options: { viaShell?: boolean } = {}
+): Promise<string[]> {
+  const shell = detectShell()
+  if (!shell) {
+    return []
+  }
+
+  for (const path of getHistoryCandidates(shell, getHomeDir())) {
+    const lines = await readHistoryTailAsync(path, maxHistory)
+    if (lines.length > 0) {
+      return lines
+    }
+  }
 
-  return lines
+  return options.viaShell ? readHistoryViaShell(shell, maxHistory) : []
 }
 
 /**
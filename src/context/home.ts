@@ -0,0 +1,34 @@
+// Home directory resolution shared by redaction, history and config paths
+
+import { homedir, userInfo } from 'os'
+
+/**
+ * Resolve the current user's home directory
+ * Uses $HOME when set, otherwise the OS account database, so minimal
+ * containers and CI jobs without $HOME still resolve correctly
+ *
+ * @returns Home directory, or null if it can't be determined
+ */
+export function getHomeDir(): string | null {
+  // os.homedir() honors $HOME and falls back to the passwd entry,
+  // but returns '' for an empty $HOME and can throw without either
+  try {
+    const home = homedir()
+    if (home) {
+      return home
+    }
+  } catch {
+    // Fall through to the account database
+  }
+
+  try {
+    const home = userInfo().homedir
+    if (home) {
+      return home
+    }
+  } catch {
+    // No passwd entry for this uid
+  }
+
+  return null
+}
@@ -2,48 +2,139 @@
 // Gathers system info, directory context, shell history, and stdin
 
 import { Config } from '../config/types.js'
-import { ContextData, ContextError } from './types.js'
-import { getSystemInfo } from './system.js'
+import {
+  ContextData,
+  ContextError,
+  ContextMode,
+  ContextSection,
+  ALL_CONTEXT_SECTIONS,
+} from './types.js'
+import {
+  getSystemInfo,
+  refreshSystemInfo,
+  collectSystemInfo,
+} from './system.js'
 import { getCwd, scanDirectory } from './directory.js'
-import { getShellHistory } from './history.js'
+import { detectPackageManagers } from './packages.js'
+import { getCurrentTime } from './time.js'
+import { detectEnvironments } from './environments.js'
+import { readProjectDescription } from './project.js'
+import { getShellHistoryAsync } from './history.js'
 import { readStdin } from './stdin.js'
-import { redactPath } from './redaction.js'
+import { redactPath, DEFAULT_REDACTION_PLACEHOLDER } from './redaction.js'
 
 // Re-export types and functions
-export { ContextError } from './types.js'
-export type { ContextData, SystemInfo } from './types.js'
-export { getSystemInfo, clearSystemCache } from './system.js'
+export {
+  ContextError,
+  ALL_CONTEXT_SECTIONS,
+  ALL_SYSTEM_FIELDS,
+} from './types.js'
+export type {
+  ContextData,
+  SystemInfo,
+  SystemField,
+  ContextSection,
+  ContextMode,
+  FileSort,
+  CurrentTime,
+} from './types.js'
+export {
+  getSystemInfo,
+  clearSystemCache,
+  refreshSystemInfo,
+  collectSystemInfo,
+  formatSystemInfo,
+} from './system.js'
 export { getCwd, scanDirectory, getRedactedCwd } from './directory.js'
-export { getShellHistory, getDetectedShell } from './history.js'
-export { readStdin, hasPipedStdin } from './stdin.js'
-export { redactPath, redactUsername, redactEnvVars } from './redaction.js'
+export {
+  getShellHistory,
+  getShellHistoryAsync,
+  readHistoryTail,
+  readHistoryTailAsync,
+  extractShellHistory,
+  getHistoryCandidates,
+  readHistoryViaShell,
+  getDetectedShell,
+} from './history.js'
+export {
+  readStdin,
+  hasPipedStdin,
+  looksBinary,
+  decodeStdin,
+  BINARY_STDIN_MARKER,
+} from './stdin.js'
+export {
+  redactPath,
+  redactUsername,
+  redactEnvVars,
+  DEFAULT_REDACTION_PLACEHOLDER,
+} from './redaction.js'
+export { getHomeDir } from './home.js'
+export { selectTopN } from './topn.js'
+export {
+  detectPackageManagers,
+  KNOWN_PACKAGE_MANAGERS,
+} from './packages.js'
+export { summarizeContext } from './summary.js'
+export { detectEnvironments } from './environments.js'
+export {
+  readProjectDescription,
+  MAX_PROJECT_DESCRIPTION,
+} from './project.js'
+export {
+  getCurrentTime,
+  detectTimezone,
+  formatLocalTimestamp,
+} from './time.js'
+
+/**
+ * Resolve which context sections a mode includes
+ */
+export function getContextSections(mode: ContextMode): ContextSection[] {
+  if (mode === 'history') {
+    return ['history']
+  }
+  return [...ALL_CONTEXT_SECTIONS]
+}
 
 /**
  * Gather all context information for the AI prompt
  *
  * Components:
- * - System info (cached): OS, shell, user, memory
+ * - System info (cached unless --no-cache-system-info): OS, shell, user, memory
+ * - Current local time and timezone (context.include-time)
+ * - Active venv/conda/nvm environments (context.detect-environments)
+ * - Project description (context.include-project-description)
  * - Current working directory (fatal if fails)
  * - Directory files (sorted, truncated, redacted)
- * - Shell history (last N commands)
- * - Stdin content (only if piped, max 10KB)
+ * - Shell history (last N commands) and the previous exit status (--last-exit)
+ * - Stdin content (only if piped, max 10KB; skipped with --no-stdin)
  *
  * @param config - Runtime configuration with context settings
  * @returns ContextData with all gathered information
  * @throws ContextError if CWD cannot be determined (fatal)
  */
 export async function gatherContext(config: Config): Promise<ContextData> {
-  const { maxFiles, maxHistory, redactPaths, redactUsername } = config.context
+  const { maxFiles, maxHistory, redactPaths, redactUsername, fileSort } =
+    config.context
+  const placeholder =
+    config.context.redactionPlaceholder ?? DEFAULT_REDACTION_PLACEHOLDER
+  const sections = getContextSections(config.context.mode)
 
-  // 1. System info (cached, non-fatal)
-  const system = getSystemInfo(redactUsername)
+  // 1. System info (cached unless --refresh-context or
+  // --no-cache-system-info, non-fatal)
+  const system = config.noCacheSystemInfo
+    ? collectSystemInfo(redactUsername, placeholder)
+    : config.refreshContext
+      ? refreshSystemInfo(redactUsername, placeholder)
+      : getSystemInfo(redactUsername, placeholder)
 
   // 2. CWD (fatal if fails)
   let cwd: string
   try {
     cwd = getCwd()
     if (redactPaths) {
-      cwd = redactPath(cwd)
+      cwd = redactPath(cwd, placeholder)
     }
   } catch (err) {
     if (err instanceof ContextError) {
@@ -55,20 +146,73 @@ export async function gatherContext(config: Config): Promise<ContextData> {
     )
   }
 
+  // 2b. Package managers on PATH (part of the system section)
+  const packageManagers =
+    config.context.detectPackageManagers && sections.includes('system')
+      ? detectPackageManagers()
+      : undefined
+
+  // 2c. Current time (part of the system section)
+  const time =
+    config.context.includeTime && sections.includes('system')
+      ? getCurrentTime()
+      : undefined
+
+  // 2d. Language environments (part of the system section)
+  const environments =
+    config.context.detectEnvironments && sections.includes('system')
+      ? detectEnvironments(
+          process.env,
+          process.cwd(),
+          redactPaths,
+          placeholder
+        )
+      : undefined
+
+  // 2e. Project description (part of the directory section)
+  const projectDescription =
+    config.context.includeProjectDescription && sections.includes('directory')
+      ? readProjectDescription(process.cwd())
+      : undefined
+
+  // Shell history is read in the background while the rest is gathered
+  // max-history = 0 turns it off: no file read, no shell spawned
+  const historyRead =
+    sections.includes('history') && maxHistory > 0
+      ? getShellHistoryAsync(maxHistory, {
+          viaShell: config.context.historyViaShell,
+        })
+      : Promise.resolve([])
+
   // 3. Directory files (non-fatal, empty on error)
-  const files = scanDirectory(maxFiles, redactPaths)
+  const files = sections.includes('directory')
+    ? scanDirectory(maxFiles, redactPaths, fileSort, placeholder)
+    : []
 
-  // 4. Shell history (non-fatal, empty on error)
-  const history = getShellHistory(maxHistory)
+  // 4. Stdin (only if piped, non-fatal)
+  // With --no-stdin we leave it unread so it isn't consumed as context
+  const stdin =
+    config.noStdin || !sections.includes('stdin')
+      ? undefined
+      : await readStdin()
 
-  // 5. Stdin (only if piped, non-fatal)
-  const stdin = await readStdin()
+  // 5. Shell history (non-fatal, empty on error)
+  const history = await historyRead
 
   return {
     system,
     cwd,
     files,
     history,
+    time,
+    environments,
+    packageManagers,
+    projectDescription,
     stdin,
+    stdinName: config.stdinName,
+    lastExit: config.lastExit,
+    inlineContext: config.inlineContext,
+    sections,
+    systemFields: config.context.systemFields,
   }
 }
@@ -0,0 +1,65 @@
+// Package manager detection, so install commands use the right tool
+
+import { accessSync, constants, statSync } from 'fs'
+import { delimiter, join } from 'path'
+
+/** Package managers we look for, in prompt order */
+export const KNOWN_PACKAGE_MANAGERS: readonly string[] = [
+  'apt',
+  'dnf',
+  'yum',
+  'pacman',
+  'zypper',
+  'apk',
+  'emerge',
+  'nix',
+  'brew',
+  'port',
+  'snap',
+  'flatpak',
+  'winget',
+  'choco',
+  'scoop',
+]
+
+// Executable suffixes to try on Windows (PATHEXT is not consulted)
+const WINDOWS_SUFFIXES = ['.exe', '.cmd', '.bat']
+
+/**
+ * Whether `path` is an executable regular file
+ */
+function isExecutable(path: string): boolean {
+  try {
+    if (!statSync(path).isFile()) {
+      return false
+    }
+    accessSync(path, constants.X_OK)
+    return true
+  } catch {
+    return false
+  }
+}
+
+/**
+ * Detect which known package managers are available on PATH
+ *
+ * @param pathEnv - PATH value to search (defaults to process.env.PATH)
+ * @returns Names of detected package managers, in KNOWN_PACKAGE_MANAGERS order
+ */
+export function detectPackageManagers(
+  pathEnv: string | undefined = process.env.PATH
+): string[] {
+  if (!pathEnv) {
+    return []
+  }
+
+  const dirs = pathEnv.split(delimiter).filter((dir) => dir.length > 0)
+  const suffixes =
+    process.platform === 'win32' ? ['', ...WINDOWS_SUFFIXES] : ['']
+
+  return KNOWN_PACKAGE_MANAGERS.filter((name) =>
+    dirs.some((dir) =>
+      suffixes.some((suffix) => isExecutable(join(dir, name + suffix)))
+    )
+  )
+}
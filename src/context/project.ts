@@ -0,0 +1,96 @@
+// Short description of the project in cwd (context.include-project-description)
+// Taken from the package.json/Cargo.toml description or the README's first line
+
+import { existsSync, readFileSync } from 'fs'
+import { join } from 'path'
+import TOML from '@iarna/toml'
+
+// Default cap on the description length (characters)
+export const MAX_PROJECT_DESCRIPTION = 200
+
+const README_FILES = ['README.md', 'README', 'README.rst', 'README.txt']
+
+function readText(path: string): string | null {
+  try {
+    return existsSync(path) ? readFileSync(path, 'utf-8') : null
+  } catch {
+    return null
+  }
+}
+
+function fromPackageJson(cwd: string): string | null {
+  const content = readText(join(cwd, 'package.json'))
+  if (!content) {
+    return null
+  }
+  try {
+    const { description } = JSON.parse(content) as { description?: unknown }
+    return typeof description === 'string' ? description : null
+  } catch {
+    return null
+  }
+}
+
+function fromCargoToml(cwd: string): string | null {
+  const content = readText(join(cwd, 'Cargo.toml'))
+  if (!content) {
+    return null
+  }
+  try {
+    const manifest = TOML.parse(content) as {
+      package?: { description?: unknown }
+    }
+    const description = manifest.package?.description
+    return typeof description === 'string' ? description : null
+  } catch {
+    return null
+  }
+}
+
+function fromReadme(cwd: string): string | null {
+  for (const name of README_FILES) {
+    const content = readText(join(cwd, name))
+    if (!content) {
+      continue
+    }
+    // First non-empty line that isn't markup (badges, HTML, rst underlines)
+    for (const raw of content.split('\n')) {
+      const line = raw
+        .trim()
+        .replace(/^#+\s*/, '')
+        .trim()
+      if (line === '' || /^(!\[|\[!\[|<|[=\-~*^]+$)/.test(line)) {
+        continue
+      }
+      return line
+    }
+  }
+  return null
+}
+
+/**
+ * Find a one-line description of the project in a directory
+ *
+ * Sources, first match wins:
+ * - "description" in package.json
+ * - [package] description in Cargo.toml
+ * - First heading or line of README(.md/.rst/.txt)
+ *
+ * @param cwd - Project directory
+ * @param maxLength - Truncate longer descriptions (with "...")
+ * @returns Description on a single line, or undefined if none found
+ */
+export function readProjectDescription(
+  cwd: string = process.cwd(),
+  maxLength: number = MAX_PROJECT_DESCRIPTION
+): string | undefined {
+  const found = fromPackageJson(cwd) ?? fromCargoToml(cwd) ?? fromReadme(cwd)
+  const description = found?.replace(/\s+/g, ' ').trim()
+  if (!description) {
+    return undefined
+  }
+  if (description.length <= maxLength) {
+    return description
+  }
+  return `${description.slice(0, Math.max(0, maxLength - 3)).trimEnd()}...`
+}
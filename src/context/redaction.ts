@@ -1,15 +1,27 @@
 // Path and username redaction helpers for privacy
 
-import { homedir } from 'os'
+import { getHomeDir } from './home.js'
 
-const REDACTED = '[REDACTED]'
+/** Default replacement for redacted values (context.redaction-placeholder) */
+export const DEFAULT_REDACTION_PLACEHOLDER = '[REDACTED]'
+
+// Home paths only match up to a path boundary, so /home/al doesn't
+// redact the start of /home/alice
+const PATH_END = `(?=[/\\s'"]|$)`
 
 /**
  * Redact sensitive path information from a string
- * Replaces home directory patterns with [REDACTED]
+ * Replaces home directory patterns with the placeholder
+ * A placeholder of `~` keeps paths runnable: /home/me/src -> ~/src
+ *
+ * @param path - Path (or text containing paths) to redact
+ * @param placeholder - Replacement for the home directory
  */
-export function redactPath(path: string): string {
-  const home = homedir()
+export function redactPath(
+  path: string,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): string {
+  const home = getHomeDir()
   if (!home) return path
 
   // Normalize path separators for Windows
@@ -21,26 +33,27 @@ export function redactPath(path: string): string {
   const username = homeParts[homeParts.length - 1] || ''
 
   let redacted = normalizedPath
+  // Function replacer so `$` in the placeholder is taken literally
+  const replacement = () => placeholder
 
   // Replace full home directory path
   if (normalizedHome) {
     const escapedHome = normalizedHome.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')
-    redacted = redacted.replace(new RegExp(escapedHome, 'g'), REDACTED)
+    redacted = redacted.replace(
+      new RegExp(escapedHome + PATH_END, 'g'),
+      replacement
+    )
   }
 
-  // Replace ~ (home shorthand)
-  redacted = redacted.replace(/^~\//, `${REDACTED}/`)
-  redacted = redacted.replace(/^~$/, REDACTED)
+  // Replace ~ (home shorthand); a no-op when the placeholder is ~ itself
+  redacted = redacted.replace(/^~(?=\/|$)/, replacement)
 
   // Replace /home/username/ pattern
   if (username) {
+    const escapedUser = username.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')
     redacted = redacted.replace(
-      new RegExp(`/home/${username}/`, 'g'),
-      `${REDACTED}/`
-    )
-    redacted = redacted.replace(
-      new RegExp(`/Users/${username}/`, 'g'),
-      `${REDACTED}/`
+      new RegExp(`/(?:home|Users)/${escapedUser}(?=/)`, 'g'),
+      replacement
     )
   }
 
@@ -54,22 +67,39 @@ export function redactPath(path: string): string {
 
 /**
  * Redact username
+ *
+ * @param _username - Username being hidden
+ * @param placeholder - Replacement text
  */
-export function redactUsername(_username: string): string {
-  return REDACTED
+export function redactUsername(
+  _username: string,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): string {
+  return placeholder
 }
 
 /**
  * Redact environment variables in a string
  * Replaces $VAR and ${VAR} patterns
  */
-export function redactEnvVars(text: string, envVars: string[]): string {
+export function redactEnvVars(
+  text: string,
+  envVars: string[],
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): string {
+  const replacement = () => placeholder
   let redacted = text
   for (const envVar of envVars) {
     // Replace ${VAR} pattern
-    redacted = redacted.replace(new RegExp(`\\$\\{${envVar}\\}`, 'g'), REDACTED)
+    redacted = redacted.replace(
+      new RegExp(`\\$\\{${envVar}\\}`, 'g'),
+      replacement
+    )
     // Replace $VAR pattern (word boundary)
-    redacted = redacted.replace(new RegExp(`\\$${envVar}\\b`, 'g'), REDACTED)
+    redacted = redacted.replace(
+      new RegExp(`\\$${envVar}\\b`, 'g'),
+      replacement
+    )
   }
   return redacted
 }
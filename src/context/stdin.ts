@@ -2,12 +2,69 @@
 
 const MAX_STDIN_BYTES = 10 * 1024 // 10 KB
 
+// Share of suspicious bytes above which input is treated as binary
+const BINARY_THRESHOLD = 0.1
+
+/** Placeholder sent instead of binary stdin */
+export const BINARY_STDIN_MARKER = '[binary input omitted]'
+
+/**
+ * Heuristic: does this input look like binary rather than text?
+ * Any NUL byte, or more than 10% control bytes / invalid UTF-8, counts as binary
+ * Tabs, newlines, carriage returns, form feeds and ANSI escapes are text
+ *
+ * @param bytes - Raw input bytes
+ */
+export function looksBinary(bytes: Uint8Array): boolean {
+  if (bytes.length === 0) {
+    return false
+  }
+  if (bytes.includes(0)) {
+    return true
+  }
+
+  let suspicious = 0
+  for (const byte of bytes) {
+    const isControl =
+      (byte < 0x20 && ![0x09, 0x0a, 0x0c, 0x0d, 0x1b].includes(byte)) ||
+      byte === 0x7f
+    if (isControl) {
+      suspicious++
+    }
+  }
+
+  // Each invalid UTF-8 sequence decodes to a replacement character
+  const decoded = Buffer.from(bytes).toString('utf8')
+  for (const char of decoded) {
+    if (char === '\uFFFD') {
+      suspicious++
+    }
+  }
+
+  return suspicious / bytes.length > BINARY_THRESHOLD
+}
+
+/**
+ * Decode piped bytes for the prompt
+ * Text is decoded as UTF-8 (lossy); binary input becomes BINARY_STDIN_MARKER
+ *
+ * @param bytes - Raw input bytes (already truncated to the size limit)
+ * @returns Decoded content ('' for empty input)
+ */
+export function decodeStdin(bytes: Uint8Array): string {
+  if (looksBinary(bytes)) {
+    return BINARY_STDIN_MARKER
+  }
+  return Buffer.from(bytes).toString('utf8')
+}
+
 /**
  * Read stdin if it's not a TTY (i.e., data is being piped)
  * Returns up to 10KB of content as UTF-8 string
  * Returns undefined if stdin is a TTY or empty
  *
  * UTF-8 invalid sequences are replaced with replacement character (lossy decode)
+ * Binary input is replaced by BINARY_STDIN_MARKER
  */
 export async function readStdin(): Promise<string | undefined> {
   // Check if stdin is a TTY
@@ -74,8 +131,8 @@ export async function readStdin(): Promise<string | undefined> {
       }
 
       const buffer = Buffer.concat(chunks)
-      // Convert to UTF-8 with lossy replacement for invalid sequences
-      const content = buffer.toString('utf8')
+      // Convert to UTF-8 with lossy replacement; binary becomes a marker
+      const content = decodeStdin(buffer)
       resolve(content.length > 0 ? content : undefined)
     })
 
@@ -0,0 +1,43 @@
+// Compact context overview for --summarize-context
+// Shows what clai "sees" without dumping the whole prompt
+
+import type { ContextData } from './types.js'
+
+/**
+ * Summarize gathered context in a few lines
+ *
+ * @param context - Gathered context
+ * @param promptChars - Total characters across all prompt messages
+ * @returns Multi-line summary, newline-terminated
+ */
+export function summarizeContext(
+  context: ContextData,
+  promptChars: number
+): string {
+  const { system } = context
+  const stdin =
+    context.stdin !== undefined ? `${context.stdin.length} chars` : 'none'
+  const lines = [
+    'Context summary:',
+    `  OS: ${system.osName} ${system.osVersion} (${system.architecture})`,
+    `  Shell: ${system.shell}`,
+    `  CWD: ${context.cwd}`,
+    `  Files: ${context.files.length}`,
+    `  History: ${context.history.length} line(s)`,
+    `  Stdin: ${stdin}`,
+  ]
+
+  if (context.packageManagers && context.packageManagers.length > 0) {
+    lines.push(`  Package managers: ${context.packageManagers.join(', ')}`)
+  }
+  if (context.projectDescription) {
+    lines.push(`  Project: ${context.projectDescription}`)
+  }
+  if (context.sections) {
+    lines.push(`  Sections: ${context.sections.join(', ')}`)
+  }
+
+  lines.push(`  Prompt size: ~${promptChars} chars`)
+
+  return lines.join('\n') + '\n'
+}
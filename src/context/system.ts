@@ -1,12 +1,16 @@
 // System information gathering - cached per process
 
 import { type, release, totalmem } from 'os'
-import { SystemInfo } from './types.js'
-import { redactUsername } from './redaction.js'
+import { SystemInfo, SystemField, ALL_SYSTEM_FIELDS } from './types.js'
+import {
+  redactUsername,
+  DEFAULT_REDACTION_PLACEHOLDER,
+} from './redaction.js'
 
 // Cached system info to avoid repeated OS calls
 let cachedSystemInfo: SystemInfo | null = null
 let cachedWithRedaction = false
+let cachedPlaceholder = DEFAULT_REDACTION_PLACEHOLDER
 
 /**
  * Get shell name from SHELL environment variable
@@ -29,20 +33,22 @@ function getUsername(): string {
 }
 
 /**
- * Get system information
- * Results are cached for the process lifetime
+ * Collect system information without touching the cache
+ * Used by --no-cache-system-info
+ *
  * @param redactUser - Whether to redact the username
+ * @param placeholder - Replacement for a redacted username
  */
-export function getSystemInfo(redactUser: boolean = false): SystemInfo {
-  // Return cached value if available and redaction matches
-  if (cachedSystemInfo && cachedWithRedaction === redactUser) {
-    return cachedSystemInfo
-  }
-
+export function collectSystemInfo(
+  redactUser: boolean = false,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): SystemInfo {
   const rawUsername = getUsername()
-  const user = redactUser ? redactUsername(rawUsername) : rawUsername
+  const user = redactUser
+    ? redactUsername(rawUsername, placeholder)
+    : rawUsername
 
-  const info: SystemInfo = {
+  return {
     osName: type(),
     osVersion: release(),
     architecture: process.arch,
@@ -50,18 +56,98 @@ export function getSystemInfo(redactUser: boolean = false): SystemInfo {
     user,
     totalMemoryMb: Math.floor(totalmem() / (1024 * 1024)),
   }
+}
+
+/**
+ * Get system information
+ * Results are cached for the process lifetime (see clearSystemCache)
+ * @param redactUser - Whether to redact the username
+ * @param placeholder - Replacement for a redacted username
+ */
+export function getSystemInfo(
+  redactUser: boolean = false,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): SystemInfo {
+  // Return cached value if available and redaction matches
+  if (
+    cachedSystemInfo &&
+    cachedWithRedaction === redactUser &&
+    (!redactUser || cachedPlaceholder === placeholder)
+  ) {
+    return cachedSystemInfo
+  }
+
+  const info = collectSystemInfo(redactUser, placeholder)
 
   // Cache the result
   cachedSystemInfo = info
   cachedWithRedaction = redactUser
+  cachedPlaceholder = placeholder
 
   return info
 }
 
 /**
- * Clear the system info cache (useful for testing)
+ * Format system info as label -> value pairs for the prompt
+ * Only fields in the allowlist are included; os-name and os-version share
+ * the 'OS' line
+ *
+ * @param info - Gathered system info
+ * @param fields - Allowlist of fields to include (default: all)
+ */
+export function formatSystemInfo(
+  info: SystemInfo,
+  fields: readonly SystemField[] = ALL_SYSTEM_FIELDS
+): Record<string, string> {
+  const result: Record<string, string> = {}
+
+  const os = [
+    fields.includes('os-name') ? info.osName : '',
+    fields.includes('os-version') ? info.osVersion : '',
+  ]
+    .filter(Boolean)
+    .join(' ')
+  if (os) {
+    result.OS = os
+  }
+  if (fields.includes('architecture')) {
+    result.Architecture = info.architecture
+  }
+  if (fields.includes('shell')) {
+    result.Shell = info.shell
+  }
+  if (fields.includes('user')) {
+    result.User = info.user
+  }
+  if (fields.includes('total-memory')) {
+    result.Memory = `${info.totalMemoryMb} MB`
+  }
+
+  return result
+}
+
+/**
+ * Clear the system info cache so the next getSystemInfo re-collects
+ * For tests and long-lived callers whose environment changes (SHELL, USER)
  */
 export function clearSystemCache(): void {
   cachedSystemInfo = null
   cachedWithRedaction = false
+  cachedPlaceholder = DEFAULT_REDACTION_PLACEHOLDER
+}
+
+/**
+ * Re-collect system info, replacing the cached value
+ * For when the environment may have changed since the first read
+ * (e.g. the user switched shells); used by --refresh-context
+ *
+ * @param redactUser - Whether to redact the username
+ * @param placeholder - Replacement for a redacted username
+ */
+export function refreshSystemInfo(
+  redactUser: boolean = false,
+  placeholder: string = DEFAULT_REDACTION_PLACEHOLDER
+): SystemInfo {
+  clearSystemCache()
+  return getSystemInfo(redactUser, placeholder)
 }
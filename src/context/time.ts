@@ -0,0 +1,81 @@
+// Current local time, so "modified today" or "the last hour" don't rely on
+// the model guessing the date
+
+import { readlinkSync } from 'fs'
+import type { CurrentTime } from './types.js'
+
+/**
+ * Format a date as local ISO 8601 with the UTC offset
+ * e.g. 2026-03-14T09:26:53+01:00
+ */
+export function formatLocalTimestamp(date: Date): string {
+  const pad = (n: number) => String(n).padStart(2, '0')
+
+  // getTimezoneOffset is minutes *behind* UTC, so flip the sign
+  const offset = -date.getTimezoneOffset()
+  const sign = offset >= 0 ? '+' : '-'
+  const abs = Math.abs(offset)
+
+  const day = [date.getFullYear(), date.getMonth() + 1, date.getDate()].map(
+    pad
+  )
+  const time = [date.getHours(), date.getMinutes(), date.getSeconds()].map(pad)
+
+  return (
+    `${day.join('-')}T${time.join(':')}` +
+    `${sign}${pad(Math.floor(abs / 60))}:${pad(abs % 60)}`
+  )
+}
+
+/**
+ * Zone name from the /etc/localtime symlink (e.g. .../zoneinfo/Europe/Berlin)
+ */
+function zoneFromLocaltime(): string | null {
+  try {
+    const target = readlinkSync('/etc/localtime')
+    const match = target.match(/zoneinfo\/(.+)$/)
+    return match?.[1] ?? null
+  } catch {
+    return null
+  }
+}
+
+/**
+ * Detect the local timezone name
+ * Order: TZ, the runtime's resolved zone, /etc/localtime, then the raw offset
+ *
+ * @param env - Environment to read TZ from
+ * @param date - Date used for the offset fallback
+ */
+export function detectTimezone(
+  env: NodeJS.ProcessEnv = process.env,
+  date: Date = new Date()
+): string {
+  const tz = env.TZ?.trim().replace(/^:/, '')
+  if (tz) {
+    return tz
+  }
+
+  try {
+    const resolved = Intl.DateTimeFormat().resolvedOptions().timeZone
+    if (resolved) {
+      return resolved
+    }
+  } catch {
+    // Fall through to the file-based lookup
+  }
+
+  return zoneFromLocaltime() ?? `UTC${formatLocalTimestamp(date).slice(-6)}`
+}
+
+/**
+ * Current local date/time and timezone for the prompt
+ *
+ * @param date - Point in time (defaults to now)
+ */
+export function getCurrentTime(date: Date = new Date()): CurrentTime {
+  return {
+    local: formatLocalTimestamp(date),
+    timezone: detectTimezone(process.env, date),
+  }
+}
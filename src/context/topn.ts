@@ -0,0 +1,74 @@
+// Bounded top-N selection, so huge inputs never need to be held in memory
+
+/**
+ * Select the first `n` items in `compare` order without collecting the input
+ * Keeps a max-heap of at most `n` items: each new item replaces the current
+ * worst one if it sorts earlier. Memory is O(n) regardless of input size.
+ *
+ * @param items - Items to select from (consumed once)
+ * @param n - Number of items to keep
+ * @param compare - Sort comparator (negative when a sorts before b)
+ * @returns Up to `n` items, sorted by `compare`
+ */
+export function selectTopN<T>(
+  items: Iterable<T>,
+  n: number,
+  compare: (a: T, b: T) => number
+): T[] {
+  if (n <= 0) {
+    return []
+  }
+
+  // heap[0] is the item that sorts last among those kept
+  const heap: T[] = []
+
+  const swap = (i: number, j: number) => {
+    const tmp = heap[i]!
+    heap[i] = heap[j]!
+    heap[j] = tmp
+  }
+
+  const siftUp = (index: number) => {
+    let i = index
+    while (i > 0) {
+      const parent = (i - 1) >> 1
+      if (compare(heap[i]!, heap[parent]!) <= 0) {
+        break
+      }
+      swap(i, parent)
+      i = parent
+    }
+  }
+
+  const siftDown = (index: number) => {
+    let i = index
+    for (;;) {
+      const left = 2 * i + 1
+      const right = left + 1
+      let largest = i
+      if (left < heap.length && compare(heap[left]!, heap[largest]!) > 0) {
+        largest = left
+      }
+      if (right < heap.length && compare(heap[right]!, heap[largest]!) > 0) {
+        largest = right
+      }
+      if (largest === i) {
+        return
+      }
+      swap(i, largest)
+      i = largest
+    }
+  }
+
+  for (const item of items) {
+    if (heap.length < n) {
+      heap.push(item)
+      siftUp(heap.length - 1)
+    } else if (compare(item, heap[0]!) < 0) {
+      heap[0] = item
+      siftDown(0)
+    }
+  }
+
+  return heap.sort(compare)
+}
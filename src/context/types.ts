@@ -1,5 +1,9 @@
 // Context Data Types for the clai CLI
-import { ClaiError } from '../error/index.js'
+import {
+  ClaiError,
+  exitCodeForCategory,
+  type ErrorCategory,
+} from '../error/index.js'
 
 // System Information Interface
 export interface SystemInfo {
@@ -17,6 +21,58 @@ export interface SystemInfo {
   totalMemoryMb: number
 }
 
+/** System info fields that can be sent to the AI (context.system-fields) */
+export type SystemField =
+  | 'os-name'
+  | 'os-version'
+  | 'architecture'
+  | 'shell'
+  | 'user'
+  | 'total-memory'
+
+/** All system fields (the default allowlist) */
+export const ALL_SYSTEM_FIELDS: readonly SystemField[] = [
+  'os-name',
+  'os-version',
+  'architecture',
+  'shell',
+  'user',
+  'total-memory',
+]
+
+/** Context sections that can be included in the prompt */
+export type ContextSection = 'system' | 'directory' | 'history' | 'stdin'
+
+/** All context sections, in default prompt order */
+export const ALL_CONTEXT_SECTIONS: readonly ContextSection[] = [
+  'system',
+  'directory',
+  'history',
+  'stdin',
+]
+
+/**
+ * Context gathering mode
+ * - full: everything (default)
+ * - history: only recent shell history (e.g. "undo the last thing I did")
+ */
+export type ContextMode = 'full' | 'history'
+
+/**
+ * Directory listing order
+ * - name: alphabetical (default, stable)
+ * - mtime: most recently modified first
+ */
+export type FileSort = 'name' | 'mtime'
+
+/** Local date/time when context was gathered (context.include-time) */
+export interface CurrentTime {
+  /** Local ISO 8601 timestamp with UTC offset */
+  local: string
+  /** Timezone name (e.g. 'Europe/Berlin'), or 'UTC+hh:mm' if unknown */
+  timezone: string
+}
+
 export interface ContextData {
   /** System information */
   system: SystemInfo
@@ -28,13 +84,35 @@ export interface ContextData {
   history: string[]
   /** Piped stdin content (only when stdin is not a TTY) */
   stdin?: string
+  /** Current local time and timezone (context.include-time) */
+  time?: CurrentTime
+  /** Active venv/conda/nvm and project markers (context.detect-environments) */
+  environments?: string[]
+  /** Project description from manifest/README (context.include-project-description) */
+  projectDescription?: string
+  /** Package managers found on PATH (context.detect-package-managers) */
+  packageManagers?: string[]
+  /** Filename to present stdin as (--stdin-as-file) */
+  stdinName?: string
+  /** Exit status of the user's previous command (--last-exit) */
+  lastExit?: number
+  /** Extra context given after the instruction (`clai "do X" "we use podman"`) */
+  inlineContext?: string
+  /** Sections to include in the prompt (defaults to all) */
+  sections?: ContextSection[]
+  /** System info fields to include in the prompt (defaults to all) */
+  systemFields?: SystemField[]
 }
 
 /** Error class for context gathering failures */
 export class ContextError extends ClaiError {
-  constructor(message: string, code: number = 1) {
+  constructor(message: string, code: number = exitCodeForCategory('context')) {
     super(message, code)
     this.name = 'ContextError'
     Object.setPrototypeOf(this, ContextError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'context'
+  }
 }
@@ -0,0 +1,58 @@
+// src/error/exit-codes.ts
+// The single source of truth for process exit codes
+import type { ErrorCategory } from './index.js'
+
+/**
+ * Process exit codes
+ *
+ * 0: Success (including help/version and standalone actions)
+ * 1: General/unhandled errors, context errors
+ * 2: Usage errors (invalid CLI arguments)
+ * 3: Config errors (parse failures, permissions, unknown provider or
+ *    missing API key)
+ * 4: API errors (auth, rate limit, timeout, unreachable; --json-errors
+ *    reports "reason": "timeout" or "unreachable" so scripts can retry
+ *    just those)
+ * 5: Safety errors (user abort, --only-if-safe)
+ * 130: Interrupted (SIGINT/SIGTERM, cancelled request)
+ *
+ * Executed commands exit with their own status (126/127/... for spawn
+ * failures), which is passed through unchanged.
+ */
+export const ExitCode = {
+  Success: 0,
+  General: 1,
+  Usage: 2,
+  Config: 3,
+  Api: 4,
+  Safety: 5,
+  Interrupted: 130,
+} as const
+
+export type ExitCode = (typeof ExitCode)[keyof typeof ExitCode]
+
+const CATEGORY_EXIT_CODES: Record<
+  Exclude<ErrorCategory, 'execution'>,
+  ExitCode
+> = {
+  general: ExitCode.General,
+  usage: ExitCode.Usage,
+  config: ExitCode.Config,
+  context: ExitCode.General,
+  api: ExitCode.Api,
+  safety: ExitCode.Safety,
+  interrupt: ExitCode.Interrupted,
+}
+
+/**
+ * Exit code for an error category
+ * Execution errors carry the command's own status, so they have no fixed
+ * code; General is returned for them as the fallback.
+ *
+ * @param category - Error category (ClaiError.category)
+ */
+export function exitCodeForCategory(category: ErrorCategory): ExitCode {
+  return category === 'execution'
+    ? ExitCode.General
+    : CATEGORY_EXIT_CODES[category]
+}
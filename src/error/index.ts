@@ -1,19 +1,29 @@
+import { ExitCode, exitCodeForCategory } from './exit-codes.js'
+
+export { ExitCode, exitCodeForCategory } from './exit-codes.js'
+
 /**
  * Base error class for all clai errors with exit code semantics
- *
- * Exit codes:
- * 0: Success (help/version)
- * 1: General/unhandled errors
- * 2: Usage errors (invalid CLI arguments)
- * 3: Config errors (parse failures, permissions)
- * 4: API errors (auth, rate limit, timeout)
- * 5: Safety errors (user abort)
- * 130: Interrupted (SIGINT/SIGTERM)
+ * Exit codes are defined in exit-codes.ts
  */
+export type ErrorCategory =
+  | 'general'
+  | 'usage'
+  | 'config'
+  | 'context'
+  | 'api'
+  | 'safety'
+  | 'execution'
+  | 'interrupt'
+
 export class ClaiError extends Error {
   public readonly code!: number
 
-  constructor(message: string, code: number = 1, cause?: Error) {
+  constructor(
+    message: string,
+    code: number = ExitCode.General,
+    cause?: Error
+  ) {
     super(message, { cause })
     this.name = 'ClaiError'
 
@@ -28,6 +38,19 @@ export class ClaiError extends Error {
     // Maintain proper prototype chain for instanceof checks
     Object.setPrototypeOf(this, ClaiError.prototype)
   }
+
+  /** Machine-readable category (used by --json-errors) */
+  get category(): ErrorCategory {
+    return 'general'
+  }
+
+  /**
+   * Finer classification within the category (e.g. 'timeout' for API
+   * errors), reported by --json-errors; undefined when there is none
+   */
+  get reason(): string | undefined {
+    return undefined
+  }
 }
 
 /**
@@ -36,10 +59,14 @@ export class ClaiError extends Error {
  */
 export class UsageError extends ClaiError {
   constructor(message: string, cause?: Error) {
-    super(message, 2, cause)
+    super(message, exitCodeForCategory('usage'), cause)
     this.name = 'UsageError'
     Object.setPrototypeOf(this, UsageError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'usage'
+  }
 }
 
 /**
@@ -48,8 +75,54 @@ export class UsageError extends ClaiError {
  */
 export class InterruptError extends ClaiError {
   constructor(message: string = 'Interrupted', cause?: Error) {
-    super(message, 130, cause)
+    super(message, exitCodeForCategory('interrupt'), cause)
     this.name = 'InterruptError'
     Object.setPrototypeOf(this, InterruptError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'interrupt'
+  }
+}
+
+/**
+ * Single-line JSON error shape emitted with --json-errors
+ */
+export interface JsonError {
+  error: string
+  category: ErrorCategory
+  reason?: string
+  exit_code: number
+  status_code?: number
+}
+
+/**
+ * Convert any thrown value into the --json-errors shape
+ * Non-clai errors are reported as general errors with exit code 1
+ */
+export function toJsonError(error: unknown): JsonError {
+  if (error instanceof ClaiError) {
+    const statusCode = (error as { statusCode?: unknown }).statusCode
+    const reason = error.reason
+    return {
+      error: error.message,
+      category: error.category,
+      ...(reason !== undefined && { reason }),
+      exit_code: error.code,
+      ...(typeof statusCode === 'number' && { status_code: statusCode }),
+    }
+  }
+
+  return {
+    error: error instanceof Error ? error.message : String(error),
+    category: 'general',
+    exit_code: 1,
+  }
+}
+
+/**
+ * Serialize an error as a single line of JSON (no trailing newline)
+ */
+export function formatJsonError(error: unknown): string {
+  return JSON.stringify(toJsonError(error))
 }
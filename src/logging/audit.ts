@@ -0,0 +1,87 @@
+// src/logging/audit.ts
+// Audit trail (--record / audit.log-file): one JSON line per run
+
+import { appendJsonLine } from './file-logger.js'
+
+/**
+ * What happened to the generated command
+ * - executed: run after the user confirmed it
+ * - printed: written to stdout (piped, -f, chosen on the prompt, or no
+ *   terminal to run it on)
+ * - dry-run: listed with --dry-run
+ * - aborted: the user declined
+ * - blocked: refused before running, by --only-if-safe or a pre-execution
+ *   check (e.g. the recursive clai guard)
+ * - failed: no command came back (a --batch line printed as an error)
+ */
+export type AuditDecision =
+  | 'executed'
+  | 'printed'
+  | 'dry-run'
+  | 'aborted'
+  | 'blocked'
+  | 'failed'
+
+/**
+ * One audit record (without the timestamp, added when written)
+ */
+export interface AuditRecord {
+  instruction: string
+  provider: string
+  model?: string
+  /** Every command the model generated */
+  commands: string[]
+  /** The command printed or run, if one was chosen */
+  command?: string
+  dangerous: boolean
+  decision: AuditDecision
+  /** Exit status of an executed command */
+  exitCode?: number
+}
+
+// Replaces secrets in recorded text
+const REDACTED = '********'
+
+// API-key-shaped tokens (sk-..., sk-or-v1-..., ghp_..., xoxb-...)
+const TOKEN_PATTERN =
+  /\b(?:sk|pk|rk)-[A-Za-z0-9_-]{16,}|\bgh[pousr]_[A-Za-z0-9]{20,}|\bxox[abprs]-[A-Za-z0-9-]{10,}/g
+
+/**
+ * Mask secrets in text: the given secret values (configured API keys)
+ * and anything that looks like an API token
+ *
+ * @param text - Text to clean
+ * @param secrets - Known secret values
+ */
+export function redactSecrets(text: string, secrets: string[] = []): string {
+  let redacted = text
+  for (const secret of secrets) {
+    if (secret) {
+      redacted = redacted.split(secret).join(REDACTED)
+    }
+  }
+  return redacted.replace(TOKEN_PATTERN, REDACTED)
+}
+
+/**
+ * Append an audit record to a JSON-lines file
+ * Instruction and commands are passed through redactSecrets
+ *
+ * @param filePath - Audit log path
+ * @param record - What was asked, produced and decided
+ * @param secrets - Known secret values to mask
+ */
+export function writeAuditRecord(
+  filePath: string,
+  record: AuditRecord,
+  secrets: string[] = []
+): void {
+  const clean = (text: string) => redactSecrets(text, secrets)
+  appendJsonLine(filePath, {
+    ts: new Date().toISOString(),
+    ...record,
+    instruction: clean(record.instruction),
+    commands: record.commands.map(clean),
+    command: record.command === undefined ? undefined : clean(record.command),
+  })
+}
@@ -0,0 +1,64 @@
+// Terminal color detection
+// shouldUseColor is the on/off gate; colorSupport picks how many colors
+
+export type ColorMode = 'auto' | 'always' | 'never'
+
+/**
+ * Palette a terminal can display
+ * - none: no colors (not a TTY, TERM=dumb, NO_COLOR)
+ * - ansi16: the basic 16 ANSI colors
+ * - ansi256: xterm 256-color palette
+ * - truecolor: 24-bit RGB
+ */
+export type ColorSupport = 'none' | 'ansi16' | 'ansi256' | 'truecolor'
+
+/**
+ * Decide whether to emit colors at all
+ *
+ * NO_COLOR wins over every mode; 'auto' requires a TTY and TERM != dumb
+ *
+ * @param mode - --color / ui.color setting
+ * @param env - Environment to inspect
+ * @param isTTY - Whether the output stream is a terminal
+ */
+export function shouldUseColor(
+  mode: ColorMode,
+  env: NodeJS.ProcessEnv = process.env,
+  isTTY: boolean = process.stderr.isTTY === true
+): boolean {
+  if (mode === 'never' || env.NO_COLOR) return false
+  if (mode === 'always') return true
+  return isTTY && env.TERM !== 'dumb'
+}
+
+/**
+ * Classify the terminal's color depth from COLORTERM, TERM and TTY status
+ *
+ * - COLORTERM=truecolor|24bit, or a TERM ending in -direct: truecolor
+ * - TERM containing 256color: ansi256
+ * - any other TERM on a TTY: ansi16
+ *
+ * @param env - Environment to inspect
+ * @param isTTY - Whether the output stream is a terminal
+ */
+export function colorSupport(
+  env: NodeJS.ProcessEnv = process.env,
+  isTTY: boolean = process.stderr.isTTY === true
+): ColorSupport {
+  const term = env.TERM ?? ''
+  if (!isTTY || env.NO_COLOR || term === 'dumb') {
+    return 'none'
+  }
+
+  const colorterm = (env.COLORTERM ?? '').toLowerCase()
+  if (colorterm === 'truecolor' || colorterm === '24bit') {
+    return 'truecolor'
+  }
+  if (/-direct$/.test(term)) {
+    return 'truecolor'
+  }
+  if (/256colou?r/.test(term)) {
+    return 'ansi256'
+  }
+  return 'ansi16'
+}
@@ -4,54 +4,52 @@ import { Logger, LogLevel } from './logger.js'
 
 const MAX_FILE_SIZE = 10 * 1024 * 1024 // 10MB
 
+/**
+ * Append one JSON object as a line to a JSON-lines file
+ * Creates the directory as needed and starts over once the file passes
+ * 10MB; write failures are ignored so logging never crashes the app
+ */
+export function appendJsonLine(filePath: string, entry: object): void {
+  try {
+    mkdirSync(dirname(filePath), { recursive: true })
+  } catch (error) {
+    // Directory may already exist
+  }
+
+  try {
+    const stats = statSync(filePath)
+    if (stats.size > MAX_FILE_SIZE) {
+      truncateSync(filePath, 0)
+    }
+  } catch (error) {
+    // File doesn't exist yet, will be created on first write
+  }
+
+  try {
+    appendFileSync(filePath, JSON.stringify(entry) + '\n', 'utf-8')
+  } catch (error) {
+    // Silent fail - don't crash app if file logging fails
+  }
+}
+
 export class FileLogger extends Logger {
   private filePath: string
 
   constructor(filePath: string, level: LogLevel = 'verbose') {
     super(level, 'never') // File logging never uses color
     this.filePath = filePath
-    this.ensureDirectory()
-  }
-
-  private ensureDirectory(): void {
-    try {
-      mkdirSync(dirname(this.filePath), { recursive: true })
-    } catch (error) {
-      // Directory may already exist
-    }
-  }
-
-  private checkAndTruncate(): void {
-    try {
-      const stats = statSync(this.filePath)
-      if (stats.size > MAX_FILE_SIZE) {
-        truncateSync(this.filePath, 0)
-      }
-    } catch (error) {
-      // File doesn't exist yet, will be created on first write
-    }
   }
 
   private writeToFile(level: string, msg: string): void {
-    this.checkAndTruncate()
-
-    const entry = {
+    appendJsonLine(this.filePath, {
       ts: new Date().toISOString(),
       level,
       msg,
-    }
-
-    const line = JSON.stringify(entry) + '\n'
-
-    try {
-      appendFileSync(this.filePath, line, 'utf-8')
-    } catch (error) {
-      // Silent fail - don't crash app if file logging fails
-    }
+    })
   }
 
   override error(msg: string): void {
-    if (this.shouldLog('normal')) {
+    if (this.shouldLog('error')) {
       this.writeToFile('error', msg)
     }
   }
@@ -63,7 +61,7 @@ export class FileLogger extends Logger {
   }
 
   override info(msg: string): void {
-    if (this.shouldLog('verbose')) {
+    if (this.shouldLog('info')) {
       this.writeToFile('info', msg)
     }
   }
@@ -1,9 +1,34 @@
-import { Logger } from './logger.js'
-import type { LogLevel } from './logger.js'
-import { FileLogger } from './file-logger.js'
+import {
+  Logger,
+  stderrSink,
+  createMemorySink,
+  logLevelFromName,
+  resolveLogLevel,
+  LOG_LEVEL_NAMES,
+} from './logger.js'
+import type { LogLevel, LogLevelName, LogSink } from './logger.js'
+import { FileLogger, appendJsonLine } from './file-logger.js'
+import { writeAuditRecord, redactSecrets } from './audit.js'
+import type { AuditRecord, AuditDecision } from './audit.js'
+import { shouldUseColor, colorSupport } from './color.js'
+import type { ColorMode, ColorSupport } from './color.js'
 
-export { Logger, FileLogger }
-export type { LogLevel }
+export {
+  Logger,
+  FileLogger,
+  appendJsonLine,
+  writeAuditRecord,
+  redactSecrets,
+  stderrSink,
+  createMemorySink,
+  logLevelFromName,
+  resolveLogLevel,
+  LOG_LEVEL_NAMES,
+  shouldUseColor,
+  colorSupport,
+}
+export type { LogLevel, LogLevelName, LogSink, ColorMode, ColorSupport }
+export type { AuditRecord, AuditDecision }
 
 // Multi-target logger that writes to both stderr and file
 export class CombinedLogger {
@@ -13,9 +38,10 @@ export class CombinedLogger {
   constructor(
     level: LogLevel,
     colorMode: 'auto' | 'always' | 'never',
-    filePath?: string
+    filePath?: string,
+    sink: LogSink = stderrSink
   ) {
-    this.stderrLogger = new Logger(level, colorMode)
+    this.stderrLogger = new Logger(level, colorMode, sink)
     if (filePath) {
       this.fileLogger = new FileLogger(filePath, level)
     }
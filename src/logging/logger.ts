@@ -1,28 +1,91 @@
 import chalk, { Chalk } from 'chalk'
+import { shouldUseColor, type ColorMode } from './color.js'
 
-export type LogLevel = 'quiet' | 'normal' | 'verbose'
+export type LogLevel = 'quiet' | 'error' | 'normal' | 'info' | 'verbose'
+
+// Level names accepted by --log-level
+export type LogLevelName = 'error' | 'warn' | 'info' | 'debug' | 'trace'
+
+export const LOG_LEVEL_NAMES: readonly LogLevelName[] = [
+  'error',
+  'warn',
+  'info',
+  'debug',
+  'trace',
+]
+
+// Map a --log-level name to a LogLevel (trace has no extra output yet)
+export function logLevelFromName(name: LogLevelName): LogLevel {
+  switch (name) {
+    case 'error':
+      return 'error'
+    case 'warn':
+      return 'normal'
+    case 'info':
+      return 'info'
+    case 'debug':
+    case 'trace':
+      return 'verbose'
+  }
+}
+
+/**
+ * Resolve the effective log level from CLI/config flags
+ * An explicit --log-level wins over -q/-v/-d
+ */
+export function resolveLogLevel(options: {
+  logLevel?: LogLevelName
+  quiet: boolean
+  verbose: number
+  debug: boolean
+}): LogLevel {
+  if (options.logLevel) {
+    return logLevelFromName(options.logLevel)
+  }
+  if (options.quiet) {
+    return 'quiet'
+  }
+  return options.debug || options.verbose > 0 ? 'verbose' : 'normal'
+}
+
+// Destination for formatted log lines (without trailing newline)
+export type LogSink = (line: string) => void
+
+// Default sink: one write per line to stderr
+export const stderrSink: LogSink = (line) => {
+  process.stderr.write(line + '\n')
+}
+
+// In-memory sink for capturing log output in tests
+export function createMemorySink(): { sink: LogSink; lines: string[] } {
+  const lines: string[] = []
+  return { sink: (line) => lines.push(line), lines }
+}
 
 export class Logger {
   private level: LogLevel
   private colorEnabled: boolean
   private chalk: InstanceType<typeof Chalk>
+  private sink: LogSink
 
-  constructor(level: LogLevel = 'normal', colorMode: 'auto' | 'always' | 'never' = 'auto') {
+  constructor(
+    level: LogLevel = 'normal',
+    colorMode: 'auto' | 'always' | 'never' = 'auto',
+    sink: LogSink = stderrSink
+  ) {
     this.level = level
+    this.sink = sink
     this.colorEnabled = this.resolveColorMode(colorMode)
     // Create chalk instance with explicit level
     this.chalk = this.colorEnabled ? new Chalk({ level: 3 }) : new Chalk({ level: 0 })
   }
 
-  private resolveColorMode(mode: 'auto' | 'always' | 'never'): boolean {
-    if (mode === 'never' || process.env.NO_COLOR) return false
-    if (mode === 'always') return true
-    // auto: use TTY + TERM check
-    return process.stderr.isTTY && process.env.TERM !== 'dumb'
+  private resolveColorMode(mode: ColorMode): boolean {
+    return shouldUseColor(mode, process.env, process.stderr.isTTY === true)
   }
 
   protected shouldLog(messageLevel: LogLevel): boolean {
-    const levels = { quiet: 0, normal: 1, verbose: 2 }
+    const levels = { quiet: 0, error: 1, normal: 2, info: 3, verbose: 4 }
     return levels[messageLevel] <= levels[this.level]
   }
 
@@ -41,26 +104,26 @@ export class Logger {
   }
 
   error(msg: string): void {
-    if (this.shouldLog('normal')) {
-      process.stderr.write(this.formatMessage('error', msg) + '\n')
+    if (this.shouldLog('error')) {
+      this.sink(this.formatMessage('error', msg))
     }
   }
 
   warn(msg: string): void {
     if (this.shouldLog('normal')) {
-      process.stderr.write(this.formatMessage('warn', msg) + '\n')
+      this.sink(this.formatMessage('warn', msg))
     }
   }
 
   info(msg: string): void {
-    if (this.shouldLog('verbose')) {
-      process.stderr.write(this.formatMessage('info', msg) + '\n')
+    if (this.shouldLog('info')) {
+      this.sink(this.formatMessage('info', msg))
     }
   }
 
   debug(msg: string): void {
     if (this.shouldLog('verbose')) {
-      process.stderr.write(this.formatMessage('debug', msg) + '\n')
+      this.sink(this.formatMessage('debug', msg))
     }
   }
 
@@ -1,29 +1,379 @@
 #!/usr/bin/env node
 // clai - CLI for converting natural language to shell commands
 
-import { parseCli } from './cli/index.js'
-import { getConfig, ConfigError } from './config/index.js'
-import { gatherContext, ContextError } from './context/index.js'
+import { existsSync, writeFileSync } from 'fs'
+import { parseCli, type ConfigCommand } from './cli/index.js'
+import {
+  getConfig,
+  ConfigError,
+  findInsecureConfigFiles,
+  fixConfigPermissions,
+  getConfigWarnings,
+  missingConfigNotice,
+  setConfigFile,
+  loadFileConfig,
+  resolveNumOptions,
+  resolveProviderName,
+  getProviderModel,
+  formatFileConfig,
+  setConfigValues,
+  maskConfigAssignment,
+  getUserConfigPath,
+  getProviderApiKey,
+  getConfigPaths,
+  getConfigValue,
+  initUserConfig,
+} from './config/index.js'
+import {
+  gatherContext,
+  readStdin,
+  ContextError,
+  BINARY_STDIN_MARKER,
+  summarizeContext,
+  getHomeDir,
+} from './context/index.js'
+import type { Config } from './config/types.js'
+import {
+  runBatch,
+  parseBatchInput,
+  formatBatchLine,
+  finishBatchCommand,
+} from './batch/index.js'
 import {
   generateCommands,
+  generateCommandsWithRaw,
   AIError,
+  RequestDumpedError,
   buildPrompt,
   formatPromptForDebug,
+  explainDanger,
+  explainCommand,
+  getPromptOptions,
+  formatModelInfo,
 } from './ai/index.js'
-import { checkSafety, SafetyError } from './safety/index.js'
+import {
+  assertOnlyIfSafe,
+  checkSafety,
+  traceSafety,
+  checkCommandLength,
+  isCommandDangerous,
+  isJoinedCommandDangerous,
+  shouldPrompt,
+  flagsIndirection,
+  loadPatterns,
+  testPattern,
+  SafetyError,
+} from './safety/index.js'
 import {
   renderUI,
   UserAction,
   withSpinner,
-  printCommand,
+  isSpinnerEnabled,
   printWarning,
+  printSuccess,
+  printInfo,
+  confirm,
+  canRegenerate,
+  buildRegenerateInstruction,
+  formatRunStats,
 } from './ui/index.js'
-import { executeCommand, ExecutionError } from './output/index.js'
-import { UsageError, InterruptError } from './error/index.js'
-import { registerSignalHandlers, checkInterrupt } from './signals/index.js'
-import { CombinedLogger, LogLevel } from './logging/index.js'
+import {
+  executeCommand,
+  applyCommandTemplate,
+  resolveShell,
+  writePreviewScript,
+  removePreviewScript,
+  quoteScriptPath,
+  writeCommands,
+  canExecuteInteractively,
+  isInteractiveCommand,
+  ExecutionError,
+  type ExecutionResult,
+  type OutputFormat,
+} from './output/index.js'
+import {
+  ClaiError,
+  UsageError,
+  InterruptError,
+  ExitCode,
+  formatJsonError,
+} from './error/index.js'
+import {
+  registerSignalHandlers,
+  checkInterrupt,
+  cancellable,
+  runCleanup,
+} from './signals/index.js'
+import {
+  CombinedLogger,
+  resolveLogLevel,
+  writeAuditRecord,
+  type AuditDecision,
+} from './logging/index.js'
+import { getVersionInfo } from './version.js'
+import { runSetupWizard, createTerminalIO } from './wizard/index.js'
+import { shellIntegration } from './shell/index.js'
+
+/**
+ * Restrict insecure config files to 0600 after confirmation (or -f)
+ */
+async function runFixPermissions(force: boolean): Promise<void> {
+  const paths = findInsecureConfigFiles()
+  if (paths.length === 0) {
+    printInfo('All config files already have secure permissions.')
+    return
+  }
+
+  for (const path of paths) {
+    if (force || (await confirm(`Run chmod 600 ${path}?`))) {
+      fixConfigPermissions(path)
+      printSuccess(`Fixed permissions on ${path}`)
+    } else {
+      printWarning(`Skipped ${path}`)
+    }
+  }
+}
+
+/**
+ * Update the user config file with section.key=value assignments
+ */
+function runSetConfig(assignments: string[]): void {
+  const path = setConfigValues(assignments)
+  assignments.forEach((assignment) =>
+    printSuccess(`Set ${maskConfigAssignment(assignment)} in ${path}`)
+  )
+}
+
+/**
+ * Load config files (throws ConfigError on hard errors) and report warnings
+ */
+function runValidateConfig(): void {
+  loadFileConfig()
+  const warnings = getConfigWarnings()
+  warnings.forEach((warning) => printWarning(warning))
+  printSuccess(
+    warnings.length > 0
+      ? `Config is valid (${warnings.length} warning(s))`
+      : 'Config is valid'
+  )
+}
+
+/**
+ * Print the merged file + env config with secrets masked
+ */
+function runPrintConfig(format: OutputFormat): void {
+  process.stdout.write(
+    formatFileConfig(
+      loadFileConfig(),
+      format === 'json' || format === 'jsonl' ? 'json' : 'toml'
+    )
+  )
+}
+
+/**
+ * `clai config <action>`: the subcommand form of the config flags
+ */
+function runConfigCommand(
+  command: ConfigCommand,
+  format: OutputFormat
+): void {
+  switch (command.action) {
+    case 'get':
+      process.stdout.write(getConfigValue(loadFileConfig(), command.args[0]!))
+      return
+    case 'set':
+      runSetConfig(command.args)
+      return
+    case 'list':
+      runPrintConfig(format)
+      return
+    case 'validate':
+      runValidateConfig()
+      return
+    case 'init':
+      printSuccess(`Created ${initUserConfig()}`)
+      return
+    case 'path':
+      for (const path of getConfigPaths()) {
+        process.stdout.write(
+          existsSync(path) ? `${path} (found)\n` : `${path}\n`
+        )
+      }
+      return
+  }
+}
+
+/**
+ * First run on a terminal with no API key: offer to set one up
+ * Applies the answer to `config` so this run can proceed; skipping leaves
+ * config untouched and the usual missing-key error follows
+ */
+async function runFirstRunSetup(config: Config): Promise<void> {
+  const provider = resolveProviderName(config)
+  const interactive = process.stdin.isTTY && process.stderr.isTTY
+  if (
+    !interactive ||
+    process.env.MOCK_AI === '1' ||
+    getProviderApiKey(provider, config)
+  ) {
+    return
+  }
+
+  const result = await runSetupWizard(
+    createTerminalIO(),
+    provider,
+    // setConfigValues restricts the file to 0600 once it holds the key
+    (assignments) => setConfigValues(assignments),
+    getUserConfigPath()
+  )
+  if (!result) {
+    return
+  }
+
+  config.providerName = result.provider
+  config.apiKeyEnv = undefined
+  config.providers = {
+    ...config.providers,
+    [result.provider]: {
+      ...config.providers[result.provider],
+      apiKey: result.apiKey,
+    },
+  }
+}
+
+/**
+ * --preview-script on a terminal: write the command to a temp script,
+ * offer to open it in $VISUAL/$EDITOR, and run it only if confirmed
+ * The script is removed afterwards
+ *
+ * @throws SafetyError if the user declines to run it
+ */
+async function runPreviewScript(command: string): Promise<ExecutionResult> {
+  const { shell, warning } = resolveShell()
+  if (warning) {
+    printWarning(warning)
+  }
+  const path = writePreviewScript([command], shell)
+  printInfo(`Script written to ${path}`)
+
+  try {
+    const editor = process.env.VISUAL || process.env.EDITOR
+    if (editor && (await confirm(`Open it in ${editor}?`))) {
+      await executeCommand(`${editor} ${quoteScriptPath(path)}`, { shell })
+    }
+    if (!(await confirm('Run the script?'))) {
+      throw new SafetyError('Command execution aborted by user')
+    }
+    return await executeCommand(quoteScriptPath(path), { shell })
+  } finally {
+    removePreviewScript(path)
+  }
+}
+
+/**
+ * API keys to mask in audit records: the serving provider and its fallbacks
+ */
+function auditSecrets(provider: string, config: Config): string[] {
+  return [provider, ...config.provider.fallback]
+    .map((name) => getProviderApiKey(name, config))
+    .filter((key): key is string => !!key)
+}
+
+/**
+ * --batch: one instruction per stdin line, one command per stdout line
+ * Failed lines are reported without aborting the rest; --record gets one
+ * audit record per line
+ *
+ * @returns Exit code: ExitCode.Safety if --only-if-safe blocked any line
+ */
+async function runBatchMode(
+  config: Config,
+  logger: CombinedLogger
+): Promise<ExitCode> {
+  const instructions = parseBatchInput((await readStdin()) ?? '')
+  if (instructions.length === 0) {
+    throw new UsageError('--batch expects instructions on stdin, one per line')
+  }
+
+  // Context is shared; each line only needs a single command
+  const context = await gatherContext(config)
+  const batchConfig: Config = { ...config, ui: { ...config.ui, numOptions: 1 } }
+
+  // Raw command per line, so blocked lines can still be recorded
+  const generated: string[] = []
+
+  // At most --concurrency requests in flight; provider.min-interval-ms
+  // still spaces out their starts (the throttle is process-wide)
+  const results = await cancellable((signal) =>
+    runBatch(
+      instructions,
+      async (instruction, index) => {
+        checkInterrupt()
+        const [command = ''] = await generateCommands(
+          context,
+          instruction,
+          batchConfig,
+          logger,
+          undefined,
+          signal
+        )
+        generated[index] = command
+        return finishBatchCommand(command, config)
+      },
+      config.batchConcurrency
+    )
+  )
+  // Cancelled lines were recorded as failures; don't print a partial batch
+  checkInterrupt()
+
+  for (const result of results) {
+    if ('error' in result) {
+      logger.warn(`Failed to generate "${result.instruction}": ${result.error}`)
+    }
+  }
+
+  process.stdout.write(results.map(formatBatchLine).join('\n') + '\n')
+
+  const { recordFile } = config
+  if (recordFile) {
+    const provider = resolveProviderName(config)
+    const model = getProviderModel(provider, config)
+    const secrets = auditSecrets(provider, config)
+    results.forEach((result, index) => {
+      const raw = generated[index]
+      const commands = raw === undefined ? [] : [raw]
+      const command = 'command' in result ? result.command : undefined
+      writeAuditRecord(
+        recordFile,
+        {
+          instruction: result.instruction,
+          provider,
+          model,
+          commands,
+          command,
+          dangerous: checkSafety(command ? [command] : commands, config)
+            .isDangerous,
+          decision:
+            'command' in result
+              ? 'printed'
+              : result.blocked
+                ? 'blocked'
+                : 'failed',
+        },
+        secrets
+      )
+    })
+  }
+
+  return results.some((result) => 'error' in result && result.blocked)
+    ? ExitCode.Safety
+    : ExitCode.Success
+}
 
 async function main(): Promise<void> {
+  // Checked on raw argv so errors from CLI parsing itself are reported as JSON too
+  const jsonErrors = process.argv.includes('--json-errors')
+
   try {
     // Register signal handlers first
     registerSignalHandlers()
@@ -31,35 +381,163 @@ async function main(): Promise<void> {
     // Parse CLI arguments
     const cli = parseCli(process.argv)
 
+    // --config: every config read below uses only that file
+    if (cli.configFile !== undefined) {
+      setConfigFile(cli.configFile)
+    }
+
+    // --version-json: build info for tooling, no config needed
+    if (cli.versionJson) {
+      process.stdout.write(JSON.stringify(getVersionInfo()) + '\n')
+      process.exit(ExitCode.Success)
+    }
+
+    // --shell-integration: key-binding snippet to eval/source, no config needed
+    if (cli.shellIntegration) {
+      process.stdout.write(shellIntegration(cli.shellIntegration))
+      process.exit(ExitCode.Success)
+    }
+
+    // clai config <action>: manage config files instead of translating
+    if (cli.configCommand) {
+      runConfigCommand(cli.configCommand, cli.format)
+      process.exit(ExitCode.Success)
+    }
+
+    // --set: update the user config file (before loading, which may reject it)
+    if (cli.set.length > 0) {
+      runSetConfig(cli.set)
+      process.exit(ExitCode.Success)
+    }
+
+    // --fix-permissions runs before config loading (which would reject the files)
+    if (cli.fixPermissions) {
+      await runFixPermissions(cli.force)
+      process.exit(ExitCode.Success)
+    }
+
+    // --validate-config: load (throws ConfigError on hard errors) and report
+    if (cli.validateConfig) {
+      runValidateConfig()
+      process.exit(ExitCode.Success)
+    }
+
+    // --print-config: show the merged file + env config with secrets masked
+    if (cli.printConfig) {
+      runPrintConfig(cli.format)
+      process.exit(ExitCode.Success)
+    }
+
     // Load and merge config (file + env + CLI)
     const config = getConfig(cli)
 
+    // --model-info: show what the run would use without calling the AI
+    if (cli.modelInfo) {
+      process.stderr.write(formatModelInfo(config))
+      process.exit(ExitCode.Success)
+    }
+
+    // --test-pattern: report how the positional command is classified
+    if (cli.testPattern) {
+      const result = testPattern(cli.instruction, config)
+      result.invalidPatterns.forEach((pattern) =>
+        printWarning(`Invalid pattern (all commands flagged): ${pattern}`)
+      )
+      if (result.matchedPattern) {
+        printWarning(`Dangerous: matches pattern ${result.matchedPattern}`)
+      } else if (result.pipesToInterpreter) {
+        printWarning('Dangerous: pipes into a script interpreter')
+      } else if (result.dangerous) {
+        printWarning('Dangerous: flagged because a pattern is invalid')
+      } else {
+        printSuccess('Safe: no dangerous pattern matches')
+      }
+      process.exit(ExitCode.Success)
+    }
+
     // Determine log level
-    const logLevel: LogLevel = config.quiet
-      ? 'quiet'
-      : config.debug || config.verbose > 0
-        ? 'verbose'
-        : 'normal'
+    const logLevel = resolveLogLevel(config)
 
     // Create logger
     const logger = new CombinedLogger(logLevel, config.ui.color, config.debugFile)
 
+    // Surface values that were adjusted during config validation
+    getConfigWarnings().forEach((warning) => logger.warn(warning))
+
+    const notice = missingConfigNotice(config)
+    if (notice) {
+      logger.info(notice)
+    }
+
     // Handle offline mode (not yet implemented)
     if (config.offline) {
       logger.error('Offline mode is not yet supported')
-      process.exit(1)
+      process.exit(ExitCode.General)
+    }
+
+    // No API key yet: on a terminal, offer to configure one
+    await runFirstRunSetup(config)
+
+    // --explain-only: the positional is a command; the explanation is the output
+    if (cli.explainOnly) {
+      const explanation = await withSpinner(
+        'Explaining...',
+        () => explainCommand(config.instruction, config, logger),
+        undefined,
+        isSpinnerEnabled(config)
+      )
+      process.stdout.write(explanation + '\n')
+      process.exit(ExitCode.Success)
     }
 
+    if (cli.batch) {
+      process.exit(await runBatchMode(config, logger))
+    }
+
+    // Determine if we should show interactive UI
+    // Always show in TTY mode (we're executing, not just copying)
+    // Skip only if: piped, force flag, or dry-run
+    const isTTY = process.stdin.isTTY && process.stdout.isTTY
+    const showUI = isTTY && !config.force
+
+    // Only generate extra options when the user gets to pick one
+    config.ui.numOptions = resolveNumOptions(config, showUI && !config.dryRun)
+
     // Check for interrupts before context gathering
     checkInterrupt()
 
     // Gather context for AI prompt
     const context = await gatherContext(config)
+    if (!getHomeDir()) {
+      logger.debug(
+        'Home directory could not be determined; skipping shell history and home path redaction'
+      )
+    }
+    if (context.stdin === BINARY_STDIN_MARKER) {
+      logger.warn(
+        'Piped stdin looks like binary data; omitting it from the prompt'
+      )
+    }
+
+    // --summarize-context: short overview of what the model will see
+    if (config.summarizeContext && !config.quiet) {
+      const messages = buildPrompt(
+        context,
+        config.instruction,
+        config.ui.numOptions,
+        getPromptOptions(config)
+      )
+      const promptChars = messages.reduce(
+        (total, message) => total + message.content.length,
+        0
+      )
+      process.stderr.write(summarizeContext(context, promptChars))
+    }
 
     // Debug output
     if (config.debug) {
       logger.debug('=== Loaded Config ===')
-      logger.debug(`Provider: ${config.provider.default}`)
+      logger.debug(`Provider: ${resolveProviderName(config)}`)
       logger.debug(`Context maxFiles: ${config.context.maxFiles}`)
       logger.debug(`Context maxHistory: ${config.context.maxHistory}`)
       logger.debug(`Safety confirmDangerous: ${config.safety.confirmDangerous}`)
@@ -101,7 +579,8 @@ async function main(): Promise<void> {
       const messages = buildPrompt(
         context,
         config.instruction,
-        config.ui.numOptions
+        config.ui.numOptions,
+        getPromptOptions(config)
       )
       logger.debug('=== AI Prompt ===')
       logger.debug(formatPromptForDebug(messages))
@@ -111,24 +590,128 @@ async function main(): Promise<void> {
     // Check for interrupts before AI generation
     checkInterrupt()
 
-    // Generate commands from AI (with spinner)
-    const commands = await withSpinner(
-      'Thinking...',
-      () => generateCommands(context, config.instruction, config)
-    )
+    // Generate commands from AI (with spinner unless disabled)
+    // With fallbacks configured, show which provider is being tried
+    const spinnerEnabled = isSpinnerEnabled(config)
+    // Provider and model that served the latest generation (--record)
+    let served: { provider: string; model?: string } | undefined
+    const generate = async (instruction: string): Promise<string[]> => {
+      const startedAt = Date.now()
+      // Ctrl+C cancels the request and exits through the error path
+      const result = await withSpinner(
+        'Thinking...',
+        (spinner) =>
+          cancellable((signal) =>
+            generateCommandsWithRaw(
+              context,
+              instruction,
+              config,
+              logger,
+              (name, attempt, total) => {
+                if (total > 1) {
+                  spinner.update(`Trying ${name} (${attempt}/${total})...`)
+                }
+              },
+              signal
+            )
+          ),
+        undefined,
+        spinnerEnabled
+      )
+
+      const { commands: generated, raw } = result
+      served = { provider: result.provider, model: result.model }
+
+      // --stats (or -v): what this generation cost
+      if ((config.stats || config.verbose > 0) && !config.quiet) {
+        process.stderr.write(
+          formatRunStats({ ...result, durationMs: Date.now() - startedAt }) +
+            '\n'
+        )
+      }
+
+      // --show-raw: exact model output, kept off stdout
+      if (config.rawFile) {
+        writeFileSync(config.rawFile, raw + '\n')
+      } else if (config.showRaw && !config.quiet) {
+        process.stderr.write(raw + '\n')
+      }
+
+      // Warn (but don't block) on runaway output
+      for (const cmd of generated) {
+        const lengthWarning = checkCommandLength(cmd, config)
+        if (lengthWarning) {
+          logger.warn(lengthWarning)
+        }
+      }
+
+      return generated
+    }
+
+    // -vv: log how the safety decision was reached
+    const logSafetyTrace = (checked: string[]) => {
+      if (config.verbose >= 2) {
+        traceSafety(checked, config).forEach((line) => logger.debug(line))
+      }
+    }
+
+    let commands = await generate(config.instruction)
+    logSafetyTrace(commands)
+
+    // --record / audit.log-file: one record per run, secrets masked
+    const recordRun = (
+      decision: AuditDecision,
+      command?: string,
+      exitCode?: number
+    ) => {
+      if (!config.recordFile) {
+        return
+      }
+      const provider = served?.provider ?? resolveProviderName(config)
+      writeAuditRecord(
+        config.recordFile,
+        {
+          instruction: config.instruction,
+          provider,
+          model: served?.model ?? getProviderModel(provider, config),
+          commands,
+          command,
+          // Judge what was printed or run; all options when none was chosen
+          dangerous: checkSafety(command ? [command] : commands, config)
+            .isDangerous,
+          decision,
+          exitCode,
+        },
+        auditSecrets(provider, config)
+      )
+    }
+
+    // --only-if-safe: bail out before anything reaches stdout
+    const enforceOnlyIfSafe = () => {
+      try {
+        assertOnlyIfSafe(commands, config)
+      } catch (error) {
+        recordRun('blocked')
+        throw error
+      }
+    }
+    enforceOnlyIfSafe()
 
     // Output the generated commands
     if (config.dryRun) {
-      // Dry-run: show all commands with comments
-      process.stdout.write(`# Generated ${commands.length} command(s):\n`)
-      commands.forEach((cmd, i) => {
-        process.stdout.write(`# Option ${i + 1}:\n${cmd}\n\n`)
-      })
-      process.exit(0)
+      // Dry-run: show all commands (annotated on a terminal, one per line when piped)
+      const template = config.postprocess?.commandTemplate
+      writeCommands(
+        commands.map((cmd) => applyCommandTemplate(cmd, template)),
+        config.outputFormat,
+        { annotate: true, piped: !process.stdout.isTTY }
+      )
+      recordRun('dry-run')
+      process.exit(ExitCode.Success)
     }
 
     // Check safety of generated commands
-    const safety = checkSafety(commands, config)
+    let safety = checkSafety(commands, config)
 
     if (config.debug) {
       logger.debug('=== Safety Check ===')
@@ -137,27 +720,86 @@ async function main(): Promise<void> {
       logger.debug('===========================')
     }
 
-    // Determine if we should show interactive UI
-    // Always show in TTY mode (we're executing, not just copying)
-    // Skip only if: piped, force flag, or dry-run
-    const isTTY = process.stdin.isTTY && process.stdout.isTTY
-    const showUI = isTTY && !config.force
-
     let selectedCommand: string
+    // Print instead of run (Enter with ui.interactive-default-action = "output")
+    let outputChosen = false
 
     if (showUI) {
-      // Show interactive UI for command selection
-      const result = await renderUI({
-        commands,
-        config,
-        isDangerous: safety.isDangerous,
-      })
+      let regenerations = 0
+      const rejected: string[] = []
+
+      const patterns = loadPatterns(config)
+
+      const promptUser = async () => {
+        // Optionally ask the AI why the flagged command is risky
+        let dangerExplanation: string | undefined
+        if (safety.isDangerous && config.explainDanger) {
+          const flagged = commands.find((cmd) =>
+            isCommandDangerous(cmd, patterns, config)
+          )
+          if (flagged) {
+            dangerExplanation =
+              (await withSpinner(
+                'Assessing risk...',
+                () => explainDanger(flagged, config),
+                undefined,
+                spinnerEnabled
+              )) ?? undefined
+          }
+        }
+
+        // Show interactive UI for command selection
+        return renderUI({
+          commands,
+          config,
+          isDangerous: safety.isDangerous,
+          dangerousCommands: commands.map((cmd) =>
+            isCommandDangerous(cmd, patterns, config)
+          ),
+          indirectionWarnings: commands.map(flagsIndirection),
+          dangerExplanation,
+          allowRegenerate: canRegenerate(regenerations),
+        })
+      }
+
+      let result = await promptUser()
+
+      // Regenerate: ask for different options and prompt again (bounded)
+      while (result.action === UserAction.Regenerate) {
+        regenerations++
+        rejected.push(...commands)
+        commands = await generate(
+          buildRegenerateInstruction(config.instruction, rejected)
+        )
+        logSafetyTrace(commands)
+        enforceOnlyIfSafe()
+        safety = checkSafety(commands, config)
+        result = await promptUser()
+      }
 
       if (result.action === UserAction.Abort) {
+        recordRun('aborted')
         throw new SafetyError('Command execution aborted by user')
       }
 
       selectedCommand = result.command
+      outputChosen = result.action === UserAction.Output
+
+      // Marked options were checked one by one; joined they can turn
+      // dangerous (`curl url` | `sh`), so confirm before running them
+      if (
+        !outputChosen &&
+        shouldPrompt(config) &&
+        isJoinedCommandDangerous(selectedCommand, commands, config)
+      ) {
+        printWarning(
+          `Together the marked options may be dangerous: ${selectedCommand}`
+        )
+        if (!(await confirm('Run them anyway?'))) {
+          recordRun('aborted', selectedCommand)
+          throw new SafetyError('Command execution aborted by user')
+        }
+      }
 
       // Check for interrupts after UI interaction
       checkInterrupt()
@@ -173,28 +815,97 @@ async function main(): Promise<void> {
 
     // Execute or output the selected command
     if (selectedCommand) {
+      // postprocess.command-template is applied after the safety check:
+      // the wrapper is the user's own, only the generated part is judged
+      selectedCommand = applyCommandTemplate(
+        selectedCommand,
+        config.postprocess?.commandTemplate
+      )
+
       // Check for interrupts before command execution
       checkInterrupt()
 
+      // Print when chosen on the prompt; defense in depth: never execute
+      // unless stdin is still a terminal
+      if (showUI && (outputChosen || !canExecuteInteractively())) {
+        if (!outputChosen) {
+          printWarning(
+            'stdin is not a terminal; printing the command instead of running it'
+          )
+        }
+        writeCommands([selectedCommand], config.outputFormat, {
+          piped: !process.stdout.isTTY,
+          dangerous: safety.isDangerous,
+        })
+        recordRun('printed', selectedCommand)
+        process.exit(ExitCode.Success)
+      }
+
       if (showUI) {
-        // Interactive: execute the command
-        const result = await executeCommand(selectedCommand)
+        // Informational: the child inherits the terminal, but a full-screen
+        // program appearing mid-flow can be surprising
+        if (isInteractiveCommand(selectedCommand)) {
+          printWarning('This will launch an interactive program')
+        }
+
+        // Interactive: execute the command (stdio inherited, so the child
+        // gets the real TTY)
+        const result = config.previewScript
+          ? await runPreviewScript(selectedCommand).catch((error: unknown) => {
+              recordRun('aborted', selectedCommand)
+              throw error
+            })
+          : await executeCommand(selectedCommand, { onWarning: printWarning })
 
         if (!result.success) {
-          logger.error(result.error.message)
+          // A failed pre-execution check (e.g. recursive clai) ran nothing
+          recordRun(
+            result.error.ran ? 'executed' : 'blocked',
+            selectedCommand,
+            result.error.code
+          )
+          logger.error(
+            result.error.ran
+              ? result.error.message
+              : `${result.error.message} (the command was not run)`
+          )
           process.exit(result.error.code)
         }
 
+        recordRun('executed', selectedCommand, result.exitCode)
         process.exit(result.exitCode)
       } else {
+        // --preview-script without a terminal: nothing would run or clean
+        // up the script, so the printed command is the preview
+        if (config.previewScript) {
+          printWarning(
+            '--preview-script needs a terminal; printing the command instead'
+          )
+        }
+
         // Non-interactive (piped): just output the command
-        printCommand(selectedCommand, safety.isDangerous)
-        process.exit(0)
+        writeCommands([selectedCommand], config.outputFormat, {
+          piped: !process.stdout.isTTY,
+          dangerous: safety.isDangerous,
+        })
+        recordRun('printed', selectedCommand)
+        process.exit(ExitCode.Success)
       }
     } else {
-      process.exit(0)
+      process.exit(ExitCode.Success)
     }
   } catch (error) {
+    // --dump-request: the provider stopped short of sending; not an error
+    if (error instanceof RequestDumpedError) {
+      process.stdout.write(error.dump)
+      process.exit(ExitCode.Success)
+    }
+
+    if (jsonErrors) {
+      process.stderr.write(formatJsonError(error) + '\n')
+      process.exit(error instanceof ClaiError ? error.code : ExitCode.General)
+    }
+
     // Note: logger may not be available if error occurs before config loading
     if (error instanceof UsageError) {
       process.stderr.write(`Error: ${error.message}\n`)
@@ -228,16 +939,18 @@ async function main(): Promise<void> {
     }
 
     if (error instanceof InterruptError) {
+      // A cancelled request gets here instead of the signal handler's exit
+      runCleanup()
       process.stderr.write(`\n${error.message}\n`)
       process.exit(error.code)
     }
 
     if (error instanceof Error) {
       process.stderr.write(`Error: ${error.message}\n`)
-      process.exit(1)
+      process.exit(ExitCode.General)
     }
 
-    process.exit(1)
+    process.exit(ExitCode.General)
   }
 }
 
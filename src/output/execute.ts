@@ -2,14 +2,61 @@
 // Command execution with proper error handling and exit code propagation
 
 import { spawn, type ChildProcess } from 'child_process'
+import { accessSync, constants, statSync } from 'fs'
 import { Errors, type ExecutionResult } from './types.js'
 import { validateCommand } from './validate.js'
 
+/** Shell used when $SHELL is unset or unusable */
+export const FALLBACK_SHELL = '/bin/sh'
+
 /**
  * Get the user's shell from environment, falling back to /bin/sh
  */
 export function getShell(): string {
-  return process.env.SHELL || '/bin/sh'
+  return process.env.SHELL || FALLBACK_SHELL
+}
+
+// Whether a path is a regular file we may execute
+function isExecutableFile(path: string): boolean {
+  try {
+    accessSync(path, constants.X_OK)
+    return statSync(path).isFile()
+  } catch {
+    return false
+  }
+}
+
+/**
+ * Pick the shell for executeCommand: $SHELL if it is an executable file,
+ * otherwise /bin/sh together with a warning saying why
+ * A bare name (SHELL=zsh) is left to the PATH lookup in spawn
+ */
+export function resolveShell(): { shell: string; warning?: string } {
+  const shell = getShell()
+  if (
+    shell === FALLBACK_SHELL ||
+    !shell.includes('/') ||
+    isExecutableFile(shell)
+  ) {
+    return { shell }
+  }
+  return {
+    shell: FALLBACK_SHELL,
+    warning: `SHELL=${shell} is not an executable file; running the command with ${FALLBACK_SHELL}`,
+  }
+}
+
+// resolveShell, reporting a fallback to /bin/sh
+function userShell(onWarning?: (message: string) => void): string {
+  const { shell, warning } = resolveShell()
+  if (warning) {
+    if (onWarning) {
+      onWarning(warning)
+    } else {
+      process.stderr.write(`Warning: ${warning}\n`)
+    }
+  }
+  return shell
 }
 
 /**
@@ -18,6 +65,8 @@ export function getShell(): string {
 export interface ExecuteOptions {
   /** Shell to use (defaults to user's SHELL or /bin/sh) */
   shell?: string
+  /** Reports falling back to /bin/sh for an unusable $SHELL (default: stderr) */
+  onWarning?: (message: string) => void
   /** Timeout in milliseconds (0 = no timeout) */
   timeout?: number
   /** Whether to inherit stdio (default: true for interactive) */
@@ -30,12 +79,18 @@ export interface ExecuteOptions {
  * Handles various edge cases:
  * - Empty command → error with code 1
  * - Recursive clai call → error with code 5 (safety)
- * - Shell not found → error with code 127
+ * - $SHELL missing or not executable → runs with /bin/sh, with a warning
+ * - Shell not found (explicit `shell`, or /bin/sh) → error with code 127
+ * - Shell not executable → error with code 126
  * - Command not found → error with code 127
  * - Permission denied → error with code 126
  * - Signal termination → error with code 128
  * - Timeout → error with code 124
  * - Spawn failure → error with code 1
+ *
+ * Any other exit code, including non-zero, is a success result: the
+ * command ran. error.kind tells a shell that never started ('spawn')
+ * apart from a command that ran and failed ('exit', 'signal', 'timeout').
  */
 export function executeCommand(
   command: string,
@@ -49,7 +104,7 @@ export function executeCommand(
       return
     }
 
-    const shell = options.shell ?? getShell()
+    const shell = options.shell ?? userShell(options.onWarning)
     const inheritStdio = options.inheritStdio ?? true
 
     let child: ChildProcess
@@ -134,34 +189,27 @@ export function executeCommand(
       resolve({ success: true, exitCode })
     })
 
+    // 'error' means the shell itself could not be started (the command
+    // is only ever passed to it via -c)
     child.on('error', (err) => {
       if (timeoutId) {
         clearTimeout(timeoutId)
       }
 
-      // Check for specific error types
-      const errMsg = err.message.toLowerCase()
-
-      if (errMsg.includes('enoent') || errMsg.includes('not found')) {
-        // Shell or command not found
-        if (errMsg.includes(shell)) {
-          resolve({
-            success: false,
-            error: Errors.shellNotFound(shell),
-          })
-        } else {
-          resolve({
-            success: false,
-            error: Errors.commandNotFound(command.split(/\s+/)[0] || command),
-          })
-        }
+      const errCode = (err as NodeJS.ErrnoException).code
+
+      if (errCode === 'ENOENT') {
+        resolve({
+          success: false,
+          error: Errors.shellNotFound(shell, err),
+        })
         return
       }
 
-      if (errMsg.includes('eacces') || errMsg.includes('permission')) {
+      if (errCode === 'EACCES') {
         resolve({
           success: false,
-          error: Errors.permissionDenied(command.split(/\s+/)[0] || command),
+          error: Errors.shellNotExecutable(shell, err),
         })
         return
       }
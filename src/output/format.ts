@@ -0,0 +1,111 @@
+// src/output/format.ts
+// Single place for writing generated commands to stdout
+
+/**
+ * How commands are written to stdout
+ * - plain: human/shell friendly text (default)
+ * - json: one JSON object {"commands": [...]}
+ * - jsonl: one {"command": "..."} object per line
+ * - null: NUL-terminated commands (for `xargs -0`)
+ */
+export type OutputFormat = 'plain' | 'json' | 'jsonl' | 'null'
+
+export const OUTPUT_FORMATS: readonly OutputFormat[] = [
+  'plain',
+  'json',
+  'jsonl',
+  'null',
+]
+
+export interface OutputOptions {
+  /**
+   * Dry-run listing; plain format only. Always ends in exactly one newline:
+   * - terminal: "# Option N:" comments, options separated by a blank line
+   * - piped: one command per line, no comments
+   */
+  annotate?: boolean
+  /** stdout is not a terminal: no colors or prompt marker */
+  piped?: boolean
+  /** Highlight commands as dangerous; plain format on a terminal only */
+  dangerous?: boolean
+}
+
+// ANSI color codes for the terminal prompt marker
+const colors = {
+  reset: '\x1b[0m',
+  bold: '\x1b[1m',
+  green: '\x1b[32m',
+  cyan: '\x1b[36m',
+  red: '\x1b[31m',
+}
+
+function formatPlain(commands: string[], options: OutputOptions): string {
+  if (options.annotate) {
+    if (commands.length === 0) {
+      return ''
+    }
+    if (options.piped) {
+      return commands.join('\n') + '\n'
+    }
+    const header = `# Generated ${commands.length} command(s):\n`
+    return (
+      header +
+      commands.map((cmd, i) => `# Option ${i + 1}:\n${cmd}`).join('\n\n') +
+      '\n'
+    )
+  }
+
+  if (options.piped) {
+    // Clean output for piping (no trailing newline)
+    return commands.join('\n')
+  }
+
+  const promptColor = options.dangerous ? colors.red : colors.green
+  const cmdColor = options.dangerous ? colors.red : colors.cyan
+  return commands
+    .map(
+      (cmd) =>
+        `${promptColor}${colors.bold}$${colors.reset} ${cmdColor}${cmd}${colors.reset}\n`
+    )
+    .join('')
+}
+
+/**
+ * Render commands in the given format
+ *
+ * @param commands - Commands to write
+ * @param format - Output format
+ * @param options - Annotation / terminal options
+ * @returns Exact text to write to stdout
+ */
+export function formatCommands(
+  commands: string[],
+  format: OutputFormat,
+  options: OutputOptions = {}
+): string {
+  switch (format) {
+    case 'json':
+      return JSON.stringify({ commands }) + '\n'
+    case 'jsonl':
+      return commands.map((command) => JSON.stringify({ command }) + '\n').join('')
+    case 'null':
+      return commands.map((command) => `${command}\0`).join('')
+    case 'plain':
+      return formatPlain(commands, options)
+  }
+}
+
+/**
+ * Write commands to stdout in the given format
+ */
+export function writeCommands(
+  commands: string[],
+  format: OutputFormat,
+  options: OutputOptions = {},
+  stream: NodeJS.WritableStream = process.stdout
+): void {
+  const output = formatCommands(commands, format, options)
+  if (output) {
+    stream.write(output)
+  }
+}
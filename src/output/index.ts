@@ -5,20 +5,48 @@ export {
   ExecutionError,
   Errors,
   type ExecutionResult,
+  type ExecutionErrorKind,
   type ValidationResult,
 } from './types.js'
 
 export {
   isRecursiveCall,
+  isInteractiveCommand,
   validateCommand,
+  canExecuteInteractively,
 } from './validate.js'
 
+export {
+  formatCommands,
+  writeCommands,
+  OUTPUT_FORMATS,
+  type OutputFormat,
+  type OutputOptions,
+} from './format.js'
+
 export {
   getShell,
+  resolveShell,
+  FALLBACK_SHELL,
   executeCommand,
   type ExecuteOptions,
 } from './execute.js'
 
+export {
+  COMMAND_PLACEHOLDER,
+  validateCommandTemplate,
+  applyCommandTemplate,
+} from './template.js'
+
+export {
+  PREVIEW_SCRIPT_NAME,
+  shebangFor,
+  buildPreviewScript,
+  writePreviewScript,
+  removePreviewScript,
+  quoteScriptPath,
+} from './script.js'
+
 // Re-export print functions from ui/output for convenience
 export {
   printCommand,
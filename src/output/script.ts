@@ -0,0 +1,63 @@
+// src/output/script.ts
+// --preview-script: write the selected command to a temp script to inspect
+
+import { mkdtempSync, rmSync, writeFileSync } from 'fs'
+import { tmpdir } from 'os'
+import { join } from 'path'
+
+/** File name of the script inside its private temp directory */
+export const PREVIEW_SCRIPT_NAME = 'command.sh'
+
+/**
+ * Shebang line for a shell
+ * A bare name (SHELL=zsh) goes through env so it is looked up on PATH
+ */
+export function shebangFor(shell: string): string {
+  return shell.includes('/') ? `#!${shell}` : `#!/usr/bin/env ${shell}`
+}
+
+/**
+ * Script text: shebang, then one command per line
+ *
+ * @param commands - Commands to run, in order
+ * @param shell - Shell that should run the script
+ */
+export function buildPreviewScript(commands: string[], shell: string): string {
+  return `${shebangFor(shell)}\n${commands.join('\n')}\n`
+}
+
+/**
+ * Write commands to an executable script in a fresh temp directory
+ * mkdtemp makes the directory private (0700), so nothing else can swap
+ * the script between review and execution
+ *
+ * @param commands - Commands to write
+ * @param shell - Shell for the shebang
+ * @param dir - Parent directory (defaults to the OS temp dir)
+ * @returns Path of the script
+ */
+export function writePreviewScript(
+  commands: string[],
+  shell: string,
+  dir: string = tmpdir()
+): string {
+  const scriptDir = mkdtempSync(join(dir, 'clai-preview-'))
+  const path = join(scriptDir, PREVIEW_SCRIPT_NAME)
+  writeFileSync(path, buildPreviewScript(commands, shell), { mode: 0o700 })
+  return path
+}
+
+/**
+ * Remove a script written by writePreviewScript, with its temp directory
+ * Best effort: a script that is already gone is not an error
+ */
+export function removePreviewScript(path: string): void {
+  rmSync(join(path, '..'), { recursive: true, force: true })
+}
+
+/**
+ * Quote a path as a single shell word
+ */
+export function quoteScriptPath(path: string): string {
+  return `'${path.replace(/'/g, `'\\''`)}'`
+}
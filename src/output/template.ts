@@ -0,0 +1,36 @@
+// src/output/template.ts
+// postprocess.command-template: wrap the chosen command before use
+
+/** Placeholder replaced by the generated command */
+export const COMMAND_PLACEHOLDER = '{command}'
+
+/**
+ * Check a command template
+ *
+ * @returns Problem description, or null if the template is usable
+ */
+export function validateCommandTemplate(template: string): string | null {
+  return template.includes(COMMAND_PLACEHOLDER)
+    ? null
+    : `must contain ${COMMAND_PLACEHOLDER}`
+}
+
+/**
+ * Wrap a command in a template, e.g. `time {command}` or `nice {command}`
+ * Every placeholder is replaced; a multi-line command is grouped in
+ * `{ ... }` so the wrapper applies to all of it
+ *
+ * @param command - Generated command (as safety-checked)
+ * @param template - Template from config, or undefined for no change
+ * @returns Command to print or execute
+ */
+export function applyCommandTemplate(
+  command: string,
+  template?: string
+): string {
+  if (!template) {
+    return command
+  }
+  const body = command.includes('\n') ? `{ ${command}\n}` : command
+  return template.split(COMMAND_PLACEHOLDER).join(body)
+}
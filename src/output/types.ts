@@ -1,6 +1,6 @@
 // src/output/types.ts
 // Types for command execution and result handling
-import { ClaiError } from '../error/index.js'
+import { ClaiError, type ErrorCategory } from '../error/index.js'
 
 /**
  * Execution result type (functional Result pattern)
@@ -17,20 +17,48 @@ export type ValidationResult =
   | { valid: true }
   | { valid: false; error: ExecutionError }
 
+/**
+ * What went wrong, so callers can tell "never ran" from "ran and failed"
+ * - rejected: refused before spawning (empty or recursive command)
+ * - spawn: the shell could not be started; the command did not run
+ * - exit: the command ran and the shell reported 126/127
+ * - signal: the command was killed by a signal
+ * - timeout: the command was killed after the timeout
+ */
+export type ExecutionErrorKind =
+  | 'rejected'
+  | 'spawn'
+  | 'exit'
+  | 'signal'
+  | 'timeout'
+
 /**
  * ExecutionError represents failures during command execution.
  * Each error type has a specific exit code following shell conventions.
  */
 export class ExecutionError extends ClaiError {
+  public readonly kind: ExecutionErrorKind
+
   constructor(
     message: string,
     code: number,
-    cause?: Error
+    cause?: Error,
+    kind: ExecutionErrorKind = 'spawn'
   ) {
     super(message, code, cause)
     this.name = 'ExecutionError'
+    this.kind = kind
     Object.setPrototypeOf(this, ExecutionError.prototype)
   }
+
+  /** Whether the command was started at all */
+  get ran(): boolean {
+    return this.kind !== 'rejected' && this.kind !== 'spawn'
+  }
+
+  override get category(): ErrorCategory {
+    return 'execution'
+  }
 }
 
 /**
@@ -45,29 +73,39 @@ export class ExecutionError extends ClaiError {
  */
 export const Errors = {
   emptyCommand: () =>
-    new ExecutionError('Empty command', 1),
+    new ExecutionError('Empty command', 1, undefined, 'rejected'),
 
   spawnFailed: (cmd: string, err: Error) =>
-    new ExecutionError(`Failed to spawn: ${cmd}`, 1, err),
+    new ExecutionError(`Failed to spawn: ${cmd}`, 1, err, 'spawn'),
+
+  shellNotFound: (shell: string, err?: Error) =>
+    new ExecutionError(`Shell not found: ${shell}`, 127, err, 'spawn'),
 
-  shellNotFound: (shell: string) =>
-    new ExecutionError(`Shell not found: ${shell}`, 127),
+  shellNotExecutable: (shell: string, err?: Error) =>
+    new ExecutionError(`Shell is not executable: ${shell}`, 126, err, 'spawn'),
 
   commandNotFound: (cmd: string) =>
-    new ExecutionError(`Command not found: ${cmd}`, 127),
+    new ExecutionError(`Command not found: ${cmd}`, 127, undefined, 'exit'),
 
   permissionDenied: (cmd: string) =>
-    new ExecutionError(`Permission denied: ${cmd}`, 126),
+    new ExecutionError(`Permission denied: ${cmd}`, 126, undefined, 'exit'),
 
   signalKilled: (signal: string) =>
-    new ExecutionError(`Killed by signal: ${signal}`, 128),
+    new ExecutionError(`Killed by signal: ${signal}`, 128, undefined, 'signal'),
 
   timeout: (timeoutMs: number) =>
-    new ExecutionError(`Command timed out after ${timeoutMs}ms`, 124),
+    new ExecutionError(
+      `Command timed out after ${timeoutMs}ms`,
+      124,
+      undefined,
+      'timeout'
+    ),
 
   recursiveCall: () =>
     new ExecutionError(
       'Refusing to execute clai recursively (would cause infinite loop)',
-      5
+      5,
+      undefined,
+      'rejected'
     ),
 } as const
@@ -29,6 +29,59 @@ export function isRecursiveCall(command: string): boolean {
   return claiPattern.test(command)
 }
 
+// Full-screen, pager or session programs that take over the terminal
+const INTERACTIVE_PROGRAMS = new Set([
+  'vi',
+  'vim',
+  'nvim',
+  'nano',
+  'emacs',
+  'less',
+  'more',
+  'man',
+  'top',
+  'htop',
+  'btop',
+  'watch',
+  'ssh',
+  'mosh',
+  'telnet',
+  'tmux',
+  'screen',
+])
+
+/**
+ * Detect commands that launch an interactive program (editor, pager,
+ * monitor, remote shell) in any `|`, `&&`, `||` or `;` segment
+ * `sudo`/`env` wrappers and VAR=value prefixes are skipped
+ *
+ * Matches: "vim foo.txt", "sudo nano /etc/hosts", "git log | less"
+ * Does NOT match: "ls", "echo vim", "cat top.txt"
+ */
+export function isInteractiveCommand(command: string): boolean {
+  return command.split(/&&|\|\||[|;\n]/).some((segment) => {
+    const words = segment.trim().split(/\s+/)
+    const program = words.find(
+      (word) => word !== 'sudo' && word !== 'env' && !word.includes('=')
+    )
+    return INTERACTIVE_PROGRAMS.has(program?.split('/').pop() ?? '')
+  })
+}
+
+/**
+ * Final interactivity check before executing from the interactive path
+ * TTY detection can be wrong under some terminal multiplexers or SSH setups,
+ * so stdin is re-checked right before running; callers print the command
+ * instead of executing it when this returns false
+ *
+ * @param stdin - Stream to check (defaults to process.stdin)
+ */
+export function canExecuteInteractively(
+  stdin: { isTTY?: boolean } = process.stdin
+): boolean {
+  return stdin.isTTY === true
+}
+
 /**
  * Validate command before execution
  * Returns validation result with typed error if invalid
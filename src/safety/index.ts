@@ -3,26 +3,69 @@
 
 import type { Config } from '../config/types.js'
 import type { CompiledPattern } from './types.js'
-import { DEFAULT_DANGEROUS_PATTERNS, compilePatterns, isDangerous } from './patterns.js'
+import {
+  DEFAULT_DANGEROUS_PATTERNS,
+  composePatterns,
+  compilePatterns,
+  isDangerous,
+  getMatchingPattern,
+  pipesToInterpreter,
+} from './patterns.js'
 
 // Re-export types and functions
 export { SafetyError } from './types.js'
 export type { CompiledPattern } from './types.js'
-export { DEFAULT_DANGEROUS_PATTERNS, compilePatterns, isDangerous } from './patterns.js'
+export {
+  DEFAULT_DANGEROUS_PATTERNS,
+  composePatterns,
+  compilePatterns,
+  isDangerous,
+  getMatchingPattern,
+  pipesToInterpreter,
+  flagsIndirection,
+} from './patterns.js'
 
 /**
  * Load and compile dangerous patterns from config or defaults
+ * `disablePatterns` and `extraPatterns` adjust the base list rather than
+ * replacing it
  *
  * @param config - Application config
  * @returns Compiled patterns ready for matching
  */
 export function loadPatterns(config: Config): CompiledPattern[] {
-  const patterns =
+  const base =
     config.safety.dangerousPatterns.length > 0
       ? config.safety.dangerousPatterns
       : DEFAULT_DANGEROUS_PATTERNS
 
-  return compilePatterns(patterns)
+  return compilePatterns(
+    composePatterns(
+      base,
+      config.safety.disablePatterns,
+      config.safety.extraPatterns
+    )
+  )
+}
+
+/**
+ * Full danger decision for one command: the pattern list, plus
+ * `safety.confirm-pipe-to-interpreter` (piping into sh/python/node/...)
+ *
+ * @param command - Command to check
+ * @param patterns - Compiled patterns (from loadPatterns)
+ * @param config - Application config
+ */
+export function isCommandDangerous(
+  command: string,
+  patterns: CompiledPattern[],
+  config: Config
+): boolean {
+  return (
+    isDangerous(command, patterns) ||
+    (config.safety.confirmPipeToInterpreter === true &&
+      pipesToInterpreter(command))
+  )
 }
 
 /**
@@ -68,10 +111,175 @@ export function checkSafety(
   const patterns = loadPatterns(config)
 
   // Check if any command is dangerous
-  const dangerous = commands.some((cmd) => isDangerous(cmd, patterns))
+  const dangerous = commands.some((cmd) =>
+    isCommandDangerous(cmd, patterns, config)
+  )
 
   return {
     isDangerous: dangerous,
     shouldPrompt: dangerous && shouldPrompt(config),
   }
 }
+
+/**
+ * Whether a command joined from several options (marked with Space, see
+ * ui.command-join) is dangerous although none of the options was
+ * The options are only checked one by one, but `curl url` and `sh` joined
+ * with `|` are dangerous together, and no warning was shown for them
+ *
+ * @param command - Command the user chose
+ * @param commands - Options that were shown (and checked)
+ * @param config - Application config
+ */
+export function isJoinedCommandDangerous(
+  command: string,
+  commands: string[],
+  config: Config
+): boolean {
+  if (commands.includes(command) || checkSafety(commands, config).isDangerous) {
+    return false
+  }
+  return checkSafety([command], config).isDangerous
+}
+
+/**
+ * Step-by-step account of the safety decision, logged at -vv
+ * Lists how many patterns were checked, what each command matched and the
+ * resulting action (block for --only-if-safe, prompt, or output)
+ *
+ * @param commands - Generated commands
+ * @param config - Application config
+ * @returns Trace lines, without the log prefix
+ */
+export function traceSafety(commands: string[], config: Config): string[] {
+  const patterns = loadPatterns(config)
+  const invalid = patterns.filter((p) => !p.isValid).length
+  const lines = [
+    invalid > 0
+      ? `Safety: checking ${patterns.length} patterns (${invalid} invalid)`
+      : `Safety: checking ${patterns.length} patterns`,
+  ]
+
+  let dangerous = false
+  for (const command of commands) {
+    const pattern = getMatchingPattern(command, patterns)
+    const piped =
+      config.safety.confirmPipeToInterpreter === true &&
+      pipesToInterpreter(command)
+    if (pattern) {
+      lines.push(`  ${command}: matches ${pattern}`)
+    } else if (piped) {
+      lines.push(`  ${command}: pipes into an interpreter`)
+    } else if (isDangerous(command, patterns)) {
+      lines.push(`  ${command}: flagged (invalid pattern fail-safe)`)
+    } else {
+      lines.push(`  ${command}: no match`)
+    }
+    dangerous ||= isCommandDangerous(command, patterns, config)
+  }
+
+  let action = 'output'
+  if (dangerous && config.onlyIfSafe) {
+    action = 'block (--only-if-safe)'
+  } else if (dangerous && shouldPrompt(config)) {
+    action = 'prompt'
+  }
+  lines.push(`Safety: action ${action}`)
+
+  return lines
+}
+
+/**
+ * Enforce --only-if-safe: refuse generated commands that look dangerous
+ * Runs before anything is written to stdout, so scripts never see a
+ * flagged command
+ *
+ * @param commands - Generated commands
+ * @param config - Application config
+ * @throws SafetyError if any command matches a dangerous pattern
+ */
+export function assertOnlyIfSafe(commands: string[], config: Config): void {
+  if (!config.onlyIfSafe) {
+    return
+  }
+
+  const patterns = loadPatterns(config)
+  const flagged = commands.find((cmd) =>
+    isCommandDangerous(cmd, patterns, config)
+  )
+  if (flagged !== undefined) {
+    const pattern = getMatchingPattern(flagged, patterns)
+    throw new SafetyError(
+      pattern
+        ? `generated command is dangerous (matches ${pattern}) and --only-if-safe is set`
+        : 'generated command is dangerous and --only-if-safe is set'
+    )
+  }
+}
+
+/**
+ * Check a generated command against the configured size limits
+ * Runaway model output (hundreds of lines) is usually a sign of a bad
+ * generation, so we warn rather than block
+ *
+ * @param command - Generated command to check
+ * @param config - Application config
+ * @returns Warning message if a limit is exceeded, null otherwise
+ */
+export function checkCommandLength(
+  command: string,
+  config: Config
+): string | null {
+  const { maxCommandLength, maxCommandLines } = config.safety
+
+  if (maxCommandLength > 0 && command.length > maxCommandLength) {
+    return (
+      `Generated command is unusually long (${command.length} characters, limit ${maxCommandLength}). ` +
+      'Inspect it carefully or re-run the instruction.'
+    )
+  }
+
+  const lineCount = command.split('\n').length
+  if (maxCommandLines > 0 && lineCount > maxCommandLines) {
+    return (
+      `Generated command is unusually long (${lineCount} lines, limit ${maxCommandLines}). ` +
+      'Inspect it carefully or re-run the instruction.'
+    )
+  }
+
+  return null
+}
+
+/**
+ * Result of checking a command against the configured patterns (--test-pattern)
+ */
+export interface PatternTestResult {
+  /** Whether the command would be flagged (includes the invalid-pattern fail-safe) */
+  dangerous: boolean
+  /** First pattern that matched, if any */
+  matchedPattern: string | null
+  /** Flagged for piping into an interpreter (confirm-pipe-to-interpreter) */
+  pipesToInterpreter: boolean
+  /** Patterns that failed to compile */
+  invalidPatterns: string[]
+}
+
+/**
+ * Check a command against the configured dangerous patterns
+ * Used to preview custom patterns without running anything
+ *
+ * @param command - Command under test
+ * @param config - Application config
+ */
+export function testPattern(command: string, config: Config): PatternTestResult {
+  const patterns = loadPatterns(config)
+
+  return {
+    dangerous: isCommandDangerous(command, patterns, config),
+    matchedPattern: getMatchingPattern(command, patterns),
+    pipesToInterpreter:
+      config.safety.confirmPipeToInterpreter === true &&
+      pipesToInterpreter(command),
+    invalidPatterns: patterns.filter((p) => !p.isValid).map((p) => p.pattern),
+  }
+}
@@ -53,6 +53,27 @@ export const DEFAULT_DANGEROUS_PATTERNS: readonly string[] = [
   'rd\\s+/s\\s+/q',                         // rmdir /s /q
 ] as const
 
+/**
+ * Compose the effective pattern list from a base list
+ * Disabled entries (exact pattern strings or 0-based indices into `base`)
+ * are removed, then extra patterns are appended
+ *
+ * @param base - Default or configured patterns
+ * @param disable - Patterns or indices to drop from `base`
+ * @param extra - Patterns to append
+ * @returns Effective pattern strings
+ */
+export function composePatterns(
+  base: readonly string[],
+  disable: readonly (string | number)[] = [],
+  extra: readonly string[] = []
+): string[] {
+  const kept = base.filter(
+    (pattern, index) => !disable.includes(pattern) && !disable.includes(index)
+  )
+  return [...kept, ...extra]
+}
+
 /**
  * Compile pattern strings into RegExp objects
  * Invalid patterns are marked with isValid: false
@@ -111,3 +132,124 @@ export function isDangerous(
 
   return false
 }
+
+/**
+ * Find the first valid pattern that matches a command
+ * Unlike isDangerous, invalid patterns are ignored (callers report them)
+ *
+ * @param command - The command string to check
+ * @param compiledPatterns - Array of compiled patterns
+ * @returns The matching pattern string, or null if none matches
+ */
+export function getMatchingPattern(
+  command: string,
+  compiledPatterns: CompiledPattern[]
+): string | null {
+  if (!command.trim()) {
+    return null
+  }
+
+  for (const { pattern, regex } of compiledPatterns) {
+    if (regex && regex.test(command)) {
+      return pattern
+    }
+  }
+
+  return null
+}
+
+// Programs that execute whatever script they read from stdin
+const INTERPRETER_NAME =
+  /^(?:sh|bash|zsh|dash|ksh|fish|python[\d.]*|perl|ruby|node|nodejs|php|pwsh|powershell)$/i
+
+// Words that run the following word as the actual program
+const COMMAND_WRAPPERS = new Set(['sudo', 'env', 'exec', 'command', 'nohup'])
+
+// Wrapper options whose value is the next word (`sudo -u root bash`)
+const WRAPPER_VALUE_OPTIONS: Record<string, ReadonlySet<string>> = {
+  sudo: new Set(['-u', '-g', '-C', '-D', '-h', '--user', '--group']),
+  env: new Set(['-u', '-C', '--unset', '--chdir']),
+  exec: new Set(['-a']),
+}
+
+/**
+ * Check if a command pipes into a script interpreter (`... | sh`,
+ * `... | python3`, `... | sudo bash`, `... | node -e ...`)
+ * Independent of the regex list; `||` is not a pipe
+ *
+ * @param command - The command string to check
+ * @returns true if any pipeline stage after the first is an interpreter
+ */
+export function pipesToInterpreter(command: string): boolean {
+  // Drop `||` first so it isn't mistaken for two pipes
+  const stages = command.replace(/\|\|/g, '\n').split(/\|&?/).slice(1)
+
+  return stages.some((stage) => {
+    const program = stageProgram(stage).split('/').pop() ?? ''
+    return INTERPRETER_NAME.test(program)
+  })
+}
+
+// The word a pipeline stage actually runs, past wrappers, their flags
+// (and the values some of them take), and VAR=value assignments
+function stageProgram(stage: string): string {
+  const words = stage.trim().split(/\s+/)
+  let wrapper = ''
+  let i = 0
+  while (i < words.length) {
+    const word = words[i]!
+    if (COMMAND_WRAPPERS.has(word)) {
+      wrapper = word
+    } else if (WRAPPER_VALUE_OPTIONS[wrapper]?.has(word)) {
+      i++
+    } else if (!word.startsWith('-') && !word.includes('=')) {
+      break
+    }
+    i++
+  }
+  return words[i] ?? ''
+}
+
+/**
+ * Check for indirection that hides what a command really runs, where the
+ * literal-text patterns can't see the danger: `eval`, command substitution,
+ * a program taken from a variable, or an alias/function defined in the
+ * command and then invoked
+ * Heuristic only; used for a softer warning, not as a dangerous match
+ *
+ * @param command - The command string to check
+ * @returns Why the command was flagged, or null
+ */
+export function flagsIndirection(command: string): string | null {
+  const programs = command
+    .split(/&&|\|\||[;|\n]/)
+    .map((stage) => stageProgram(stage))
+    .filter(Boolean)
+
+  if (programs.includes('eval')) {
+    return 'runs eval'
+  }
+  if (command.includes('`')) {
+    return 'uses backtick command substitution'
+  }
+  if (command.includes('$(')) {
+    return 'uses $(...) command substitution'
+  }
+  if (programs.some((program) => program.startsWith('$'))) {
+    return 'runs a command taken from a variable'
+  }
+
+  // alias x='...' or x() { ... } defined earlier in the same command
+  const defined = [
+    ...command.matchAll(/\balias\s+([\w.-]+)=/g),
+    ...command.matchAll(
+      /(?:^|[;&|\n]\s*)(?:function\s+)?([\w.-]+)\s*\(\)/g
+    ),
+  ].map((match) => match[1])
+  const invoked = programs.find((program) => defined.includes(program))
+  if (invoked) {
+    return `invokes ${invoked}, which the command itself defines`
+  }
+
+  return null
+}
@@ -1,6 +1,10 @@
 // src/safety/types.ts
 // Safety error type for abort/timeout scenarios
-import { ClaiError } from '../error/index.js'
+import {
+  ClaiError,
+  exitCodeForCategory,
+  type ErrorCategory,
+} from '../error/index.js'
 
 /**
  * SafetyError is thrown when:
@@ -12,10 +16,14 @@ import { ClaiError } from '../error/index.js'
  */
 export class SafetyError extends ClaiError {
   constructor(message: string, cause?: Error) {
-    super(message, 5, cause)
+    super(message, exitCodeForCategory('safety'), cause)
     this.name = 'SafetyError'
     Object.setPrototypeOf(this, SafetyError.prototype)
   }
+
+  override get category(): ErrorCategory {
+    return 'safety'
+  }
 }
 
 /**
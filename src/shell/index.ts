@@ -0,0 +1,67 @@
+// Shell integration snippets (--shell-integration)
+// A key binding that turns the instruction typed at the prompt into a
+// command, left in the line editor for review before running it
+
+/** Shells with an integration snippet */
+export const INTEGRATION_SHELLS = ['bash', 'zsh', 'fish'] as const
+
+export type IntegrationShell = (typeof INTEGRATION_SHELLS)[number]
+
+/** Name of the shell function the snippets define */
+export const WIDGET_NAME = '_clai_widget'
+
+// Shared flags: the instruction is the whole argument, stdin is the
+// terminal (not context) and the spinner would draw over the prompt
+const CLAI_INVOCATION = 'clai --no-stdin --no-spinner --'
+
+const SNIPPETS: Record<IntegrationShell, string> = {
+  bash: `# clai shell integration for bash
+# Add to ~/.bashrc: eval "$(clai --shell-integration bash)"
+# Type an instruction, press Ctrl+G, review the command, press Enter
+${WIDGET_NAME}() {
+  [[ -z "$READLINE_LINE" ]] && return
+  local cmd
+  cmd="$(${CLAI_INVOCATION} "$READLINE_LINE")" || return
+  READLINE_LINE="$cmd"
+  READLINE_POINT=\${#READLINE_LINE}
+}
+bind -x '"\\C-g": ${WIDGET_NAME}'
+`,
+  zsh: `# clai shell integration for zsh
+# Add to ~/.zshrc: eval "$(clai --shell-integration zsh)"
+# Type an instruction, press Ctrl+G, review the command, press Enter
+${WIDGET_NAME}() {
+  [[ -z "$BUFFER" ]] && return
+  local cmd
+  if cmd="$(${CLAI_INVOCATION} "$BUFFER")"; then
+    BUFFER="$cmd"
+    CURSOR=\${#BUFFER}
+  fi
+  zle reset-prompt
+}
+zle -N ${WIDGET_NAME}
+bindkey '^G' ${WIDGET_NAME}
+`,
+  fish: `# clai shell integration for fish
+# Add to ~/.config/fish/config.fish: clai --shell-integration fish | source
+# Type an instruction, press Ctrl+G, review the command, press Enter
+function ${WIDGET_NAME}
+    set -l instruction (commandline | string collect)
+    test -z "$instruction"; and return
+    set -l cmd (${CLAI_INVOCATION} "$instruction" | string collect)
+    and commandline -r -- $cmd
+    commandline -f repaint
+end
+bind \\cg ${WIDGET_NAME}
+`,
+}
+
+/**
+ * Shell code defining a Ctrl+G binding that replaces the current line
+ * with the command clai generates for it
+ *
+ * @param shell - Target shell
+ */
+export function shellIntegration(shell: IntegrationShell): string {
+  return SNIPPETS[shell]
+}
@@ -1,8 +1,17 @@
-import { InterruptError } from '../error/index.js'
+import { ExitCode, InterruptError } from '../error/index.js'
 
 // Interrupt flag set by signal handlers
 let interrupted = false
 
+// Cleanup callbacks run before exiting on SIGINT/SIGTERM
+const cleanupHandlers = new Set<() => void>()
+
+// Aborted on SIGINT/SIGTERM to cancel in-flight requests
+const interruptController = new AbortController()
+
+// Cancellable operations currently running (see cancellable)
+let inFlight = 0
+
 // Register signal handlers
 export function registerSignalHandlers(): void {
   process.on('SIGINT', handleInterrupt)
@@ -13,8 +22,112 @@ export function registerSignalHandlers(): void {
 }
 
 function handleInterrupt(): void {
+  const repeated = interrupted
   interrupted = true
-  process.exit(130)
+  interruptController.abort()
+
+  // A cancellable operation rejects with CancelledError and exits through
+  // the normal error path; a second Ctrl+C exits right away regardless
+  if (inFlight > 0 && !repeated) {
+    return
+  }
+  runCleanup()
+  process.exit(ExitCode.Interrupted)
+}
+
+/**
+ * Run an operation that can be cancelled by SIGINT/SIGTERM
+ * `fn` gets a signal that fires on interrupt; while it runs, an interrupt
+ * aborts the signal instead of exiting, so the operation can unwind
+ *
+ * @param fn - Operation to run, e.g. an AI request
+ */
+export async function cancellable<T>(
+  fn: (signal: AbortSignal) => Promise<T>
+): Promise<T> {
+  inFlight++
+  try {
+    return await fn(interruptController.signal)
+  } finally {
+    inFlight--
+  }
+}
+
+/**
+ * Register a cleanup callback to run if the process is interrupted
+ * Returns a function that unregisters the callback once it's no longer needed
+ */
+export function registerCleanup(fn: () => void): () => void {
+  cleanupHandlers.add(fn)
+  return () => {
+    cleanupHandlers.delete(fn)
+  }
+}
+
+/**
+ * Run (and clear) all registered cleanup callbacks
+ * A failing callback must not prevent the others or the exit from running
+ */
+export function runCleanup(): void {
+  const handlers = [...cleanupHandlers]
+  cleanupHandlers.clear()
+  for (const fn of handlers) {
+    try {
+      fn()
+    } catch {
+      // Best-effort: keep going so the terminal still gets restored
+    }
+  }
+}
+
+/**
+ * Restore the terminal to a sane state
+ * Disables raw mode on stdin and re-shows the cursor (Ink hides it)
+ */
+export function restoreTerminal(): void {
+  if (process.stdin.isTTY && process.stdin.isRaw) {
+    process.stdin.setRawMode(false)
+  }
+  if (process.stderr.isTTY) {
+    process.stderr.write('\x1b[?25h')
+  }
+}
+
+/**
+ * Minimal stream surface needed to toggle raw mode (process.stdin in practice)
+ */
+export interface RawModeStream {
+  isTTY?: boolean
+  isRaw?: boolean
+  setRawMode(mode: boolean): unknown
+}
+
+/**
+ * Guard that guarantees raw mode gets turned off again
+ * Create it before handing stdin to a raw-mode consumer (Ink) and call
+ * release() on every exit path. It is also registered as interrupt cleanup,
+ * so SIGINT/SIGTERM mid-prompt can't leave the terminal raw.
+ * release() is idempotent.
+ */
+export class RawModeGuard {
+  private released = false
+  private unregister: () => void
+
+  constructor(private stream: RawModeStream = process.stdin) {
+    this.unregister = registerCleanup(() => this.release())
+  }
+
+  release(): void {
+    if (this.released) {
+      return
+    }
+    this.released = true
+    this.unregister()
+
+    if (this.stream.isTTY && this.stream.isRaw) {
+      this.stream.setRawMode(false)
+    }
+  }
 }
 
 // Check if interrupted and throw if so
@@ -8,31 +8,56 @@ import { useTimeout } from './hooks/useTimeout.js'
 import { CommandDisplay } from './components/CommandDisplay.js'
 import { DangerousWarning } from './components/DangerousWarning.js'
 import { ActionPrompt } from './components/ActionPrompt.js'
+import { useTerminalSize } from './hooks/useTerminalSize.js'
+import { renderOptionsTable } from './output.js'
+import { joinCommands } from './join.js'
+import { enterAction, parseConfirmKey } from './keys.js'
 
 export function App({
   commands,
   isDangerous,
+  dangerousCommands = [],
+  indirectionWarnings = [],
+  dangerExplanation,
+  allowRegenerate = false,
   config,
   onComplete,
 }: AppProps): React.ReactElement {
   const { exit } = useApp()
+  const { width } = useTerminalSize()
+
+  // Run, or print with ui.interactive-default-action = "output"
+  const primaryAction = enterAction(config.ui.interactiveDefaultAction)
 
   const [selectedIndex, setSelectedIndex] = useState(0)
-  const [selectedAction, setSelectedAction] = useState(UserAction.Execute)
+  const [selectedAction, setSelectedAction] = useState(primaryAction)
+
+  const [marked, setMarked] = useState<number[]>([])
 
   const currentCommand = commands[selectedIndex] ?? ''
+  const indirection = isDangerous ? null : indirectionWarnings[selectedIndex]
   const hasMultiple = commands.length > 1
 
+  // Marked options run together, in list order, joined by ui.command-join
+  const joinedCommand =
+    marked.length > 0
+      ? joinCommands(
+          [...marked].sort((a, b) => a - b).map((i) => commands[i] ?? ''),
+          config.ui.commandJoin
+        )
+      : null
+  const finalCommand = joinedCommand ?? currentCommand
+
   // Note: Avoid console.error inside Ink components - it interferes with rendering
   // Debug output is handled in renderUI before Ink mounts
 
   // Handle completion
   const handleComplete = useCallback(
     (action: UserAction) => {
-      onComplete(action, currentCommand)
+      onComplete(action, finalCommand)
       exit()
     },
-    [currentCommand, onComplete, exit]
+    [finalCommand, onComplete, exit]
   )
 
   // Auto-abort timeout
@@ -56,10 +81,20 @@ export function App({
       return
     }
 
+    // Space: mark/unmark the current option to run several together
+    if (hasMultiple && input === ' ') {
+      setMarked((m) =>
+        m.includes(selectedIndex)
+          ? m.filter((i) => i !== selectedIndex)
+          : [...m, selectedIndex]
+      )
+      return
+    }
+
     // Up/Down: toggle action
     if (key.upArrow || key.downArrow) {
       setSelectedAction((a) =>
-        a === UserAction.Execute ? UserAction.Abort : UserAction.Execute
+        a === primaryAction ? UserAction.Abort : primaryAction
       )
       return
     }
@@ -70,6 +105,17 @@ export function App({
       return
     }
 
+    // Action shortcuts (safety.confirm-keys)
+    const action = parseConfirmKey(
+      input,
+      config.safety.confirmKeys,
+      allowRegenerate
+    )
+    if (action) {
+      handleComplete(action)
+      return
+    }
+
     // Number keys for direct selection
     const num = parseInt(input, 10)
     if (hasMultiple && num >= 1 && num <= commands.length) {
@@ -87,20 +133,50 @@ export function App({
         isDangerous={isDangerous}
       />
 
+      {/* All options, with the selected one highlighted */}
+      {hasMultiple && (
+        <Box marginTop={1}>
+          <Text>
+            {renderOptionsTable(commands, selectedIndex, {
+              color: config.ui.color !== 'never',
+              dangerous: dangerousCommands,
+              width,
+            }).trimEnd()}
+          </Text>
+        </Box>
+      )}
+
+      {/* Marked options, as they will run */}
+      {joinedCommand && (
+        <Box marginTop={1}>
+          <Text dimColor>Run together: {joinedCommand}</Text>
+        </Box>
+      )}
+
       {/* Tab hint for multiple commands */}
       {hasMultiple && (
         <Box marginTop={1}>
-          <Text dimColor>Tab: next command</Text>
+          <Text dimColor>Tab: next command  Space: mark to run together</Text>
         </Box>
       )}
 
       {/* Warning for dangerous commands */}
-      {isDangerous && <DangerousWarning />}
+      {isDangerous && <DangerousWarning message={dangerExplanation} />}
+
+      {/* Softer warning: the literal text may not show what actually runs */}
+      {indirection && (
+        <DangerousWarning
+          message={`This command ${indirection}; check what it will run`}
+        />
+      )}
 
       {/* Actions */}
       <ActionPrompt
         selectedAction={selectedAction}
         isDangerous={isDangerous}
+        allowRegenerate={allowRegenerate}
+        confirmKeys={config.safety.confirmKeys}
+        defaultAction={config.ui.interactiveDefaultAction}
       />
     </Box>
   )
@@ -4,43 +4,59 @@
 import React from 'react'
 import { Box, Text } from 'ink'
 import { UserAction } from '../types.js'
+import {
+  DEFAULT_CONFIRM_KEYS,
+  DEFAULT_INTERACTIVE_ACTION,
+  formatKeyHints,
+  type ConfirmKeys,
+  type InteractiveDefaultAction,
+} from '../keys.js'
 
 export interface ActionPromptProps {
   selectedAction: UserAction
   isDangerous: boolean
+  allowRegenerate?: boolean
+  confirmKeys?: ConfirmKeys
+  /** What the first button (and Enter) does: run or print */
+  defaultAction?: InteractiveDefaultAction
 }
 
 export function ActionPrompt({
   selectedAction,
   isDangerous,
+  allowRegenerate = false,
+  confirmKeys = DEFAULT_CONFIRM_KEYS,
+  defaultAction = DEFAULT_INTERACTIVE_ACTION,
 }: ActionPromptProps): React.ReactElement {
-  const isExecute = selectedAction === UserAction.Execute
-  const executeColor = isDangerous ? 'red' : 'green'
+  const isPrimary = selectedAction !== UserAction.Abort
+  const isPrint = defaultAction === 'output'
+  const primaryColor = isPrint ? 'cyan' : isDangerous ? 'red' : 'green'
+  const label = isPrint ? 'Print' : 'Run'
 
   return (
     <Box flexDirection="column" marginTop={1}>
       {/* Action buttons */}
       <Box gap={2}>
         <Text
-          color={isExecute ? 'black' : executeColor}
-          backgroundColor={isExecute ? executeColor : undefined}
-          bold={isExecute}
+          color={isPrimary ? 'black' : primaryColor}
+          backgroundColor={isPrimary ? primaryColor : undefined}
+          bold={isPrimary}
         >
-          {isExecute ? ' ▶ Run ' : '   Run '}
+          {isPrimary ? ` ▶ ${label} ` : `   ${label} `}
         </Text>
         <Text
-          color={!isExecute ? 'black' : 'yellow'}
-          backgroundColor={!isExecute ? 'yellow' : undefined}
-          bold={!isExecute}
+          color={!isPrimary ? 'black' : 'yellow'}
+          backgroundColor={!isPrimary ? 'yellow' : undefined}
+          bold={!isPrimary}
         >
-          {!isExecute ? ' ▶ Cancel ' : '   Cancel '}
+          {!isPrimary ? ' ▶ Cancel ' : '   Cancel '}
         </Text>
       </Box>
 
       {/* Simple hints */}
       <Box marginTop={1}>
         <Text dimColor>
-          ↑↓ select  Enter confirm  Esc cancel
+          {formatKeyHints(confirmKeys, allowRegenerate, defaultAction)}
         </Text>
       </Box>
     </Box>
@@ -4,6 +4,11 @@
 import React from 'react'
 import { render } from 'ink'
 import { App } from './App.js'
+import {
+  registerCleanup,
+  restoreTerminal,
+  RawModeGuard,
+} from '../signals/index.js'
 import {
   UserAction,
   type RenderOptions,
@@ -35,15 +40,35 @@ export {
   createSeparator,
 } from './utils/formatCommand.js'
 
+// Re-export regenerate helpers
+export {
+  MAX_REGENERATIONS,
+  canRegenerate,
+  buildRegenerateInstruction,
+} from './regenerate.js'
+
+// Re-export command joining
+export {
+  joinCommands,
+  COMMAND_JOINS,
+  DEFAULT_COMMAND_JOIN,
+} from './join.js'
+export type { CommandJoin } from './join.js'
+
 // Re-export spinner and output
-export { createSpinner, withSpinner } from './spinner.js'
+export { createSpinner, withSpinner, isSpinnerEnabled } from './spinner.js'
 export {
   printCommand,
   printWarning,
   printError,
   printSuccess,
   printInfo,
+  confirm,
+  parseConfirmAnswer,
+  renderOptionsTable,
+  formatRunStats,
 } from './output.js'
+export type { OptionsTableOptions, RunStats } from './output.js'
 
 // Re-export components
 export { Spinner } from './components/Spinner.js'
@@ -62,7 +87,15 @@ export { App } from './App.js'
  * @returns Promise resolving to user action and selected command
  */
 export function renderUI(options: RenderOptions): Promise<RenderResult> {
-  const { commands, config, isDangerous } = options
+  const {
+    commands,
+    config,
+    isDangerous,
+    dangerousCommands,
+    indirectionWarnings,
+    dangerExplanation,
+    allowRegenerate,
+  } = options
 
   // Debug logging
   if (config.debug) {
@@ -93,16 +126,30 @@ export function renderUI(options: RenderOptions): Promise<RenderResult> {
   }
 
   return new Promise((resolve) => {
+    // If we're interrupted mid-prompt, don't leave the terminal in raw mode
+    const unregisterCleanup = registerCleanup(() => {
+      unmount()
+      restoreTerminal()
+    })
+    // Ink enables raw mode for useInput; make sure every exit path disables it
+    const rawMode = new RawModeGuard()
+
     const { unmount, waitUntilExit } = render(
       <App
         commands={commands}
         isDangerous={isDangerous}
+        dangerousCommands={dangerousCommands}
+        indirectionWarnings={indirectionWarnings}
+        dangerExplanation={dangerExplanation}
+        allowRegenerate={allowRegenerate}
         config={config}
         onComplete={(action, command) => {
           if (config.debug) {
             console.error(`[UI] onComplete: ${action}, ${command}`)
           }
+          unregisterCleanup()
           unmount()
+          rawMode.release()
           resolve({ action, command })
         }}
       />,
@@ -116,6 +163,9 @@ export function renderUI(options: RenderOptions): Promise<RenderResult> {
 
     // Handle any errors during rendering
     waitUntilExit().catch((err) => {
+      unregisterCleanup()
+      rawMode.release()
+      restoreTerminal()
       if (config.debug) {
         console.error('[UI] Render error:', err)
       }
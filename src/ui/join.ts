@@ -0,0 +1,37 @@
+// src/ui/join.ts
+// Joining several selected options into one command line
+
+/**
+ * Separator used to join selected commands (ui.command-join)
+ * - &&: stop at the first failure (default)
+ * - ;: run every command regardless of failures
+ * - |: pipe each command into the next
+ */
+export type CommandJoin = '&&' | ';' | '|'
+
+export const COMMAND_JOINS: readonly CommandJoin[] = ['&&', ';', '|']
+
+/** Default separator: stopping on failure is the safe choice */
+export const DEFAULT_COMMAND_JOIN: CommandJoin = '&&'
+
+/**
+ * Join commands into a single command line
+ * Multi-line commands are wrapped in `{ ... }` so the separator applies
+ * to the whole command rather than its last line
+ *
+ * @param commands - Commands in execution order
+ * @param separator - Separator to place between them
+ * @returns Joined command line
+ */
+export function joinCommands(
+  commands: string[],
+  separator: CommandJoin = DEFAULT_COMMAND_JOIN
+): string {
+  const parts = commands.map((cmd) => {
+    const trimmed = cmd.trim()
+    return commands.length > 1 && trimmed.includes('\n')
+      ? `{ ${trimmed}\n}`
+      : trimmed
+  })
+  return parts.join(separator === ';' ? '; ' : ` ${separator} `)
+}
@@ -0,0 +1,128 @@
+// src/ui/keys.ts
+// Single-key shortcuts for the confirmation prompt (safety.confirm-keys)
+
+import { UserAction } from './types.js'
+
+/**
+ * Key for each prompt action
+ * Keys are single characters and matched case-insensitively
+ */
+export interface ConfirmKeys {
+  execute: string
+  abort: string
+  regenerate: string
+}
+
+/** What Enter does on the prompt (ui.interactive-default-action) */
+export type InteractiveDefaultAction = 'execute' | 'output'
+
+/** Enter runs the command unless configured otherwise */
+export const DEFAULT_INTERACTIVE_ACTION: InteractiveDefaultAction = 'execute'
+
+/** Default keys: e(xecute), a(bort), r(egenerate) */
+export const DEFAULT_CONFIRM_KEYS: ConfirmKeys = {
+  execute: 'e',
+  abort: 'a',
+  regenerate: 'r',
+}
+
+/**
+ * Fill in unset actions with the defaults
+ *
+ * @param keys - Keys from config (any subset of actions)
+ */
+export function resolveConfirmKeys(keys?: Partial<ConfirmKeys>): ConfirmKeys {
+  return { ...DEFAULT_CONFIRM_KEYS, ...keys }
+}
+
+/**
+ * Check that every key is a single character and no two actions share one
+ *
+ * @param keys - Resolved key mapping
+ * @returns Problem description, or null if the mapping is usable
+ */
+export function validateConfirmKeys(keys: ConfirmKeys): string | null {
+  const seen = new Map<string, string>()
+
+  for (const [action, key] of Object.entries(keys)) {
+    if ([...key].length !== 1 || key.trim() === '') {
+      return `key for ${action} must be a single visible character (got "${key}")`
+    }
+    // Digits already select options
+    if (/[0-9]/.test(key)) {
+      return `key for ${action} must not be a digit (got "${key}")`
+    }
+    const normalized = key.toLowerCase()
+    const other = seen.get(normalized)
+    if (other) {
+      return `${other} and ${action} both use "${key}"`
+    }
+    seen.set(normalized, action)
+  }
+
+  return null
+}
+
+/**
+ * Action of the highlighted button when the prompt opens, i.e. what
+ * Enter does; with "output" the execute key is the only way to run
+ *
+ * @param defaultAction - ui.interactive-default-action
+ */
+export function enterAction(
+  defaultAction: InteractiveDefaultAction = DEFAULT_INTERACTIVE_ACTION
+): UserAction {
+  return defaultAction === 'output' ? UserAction.Output : UserAction.Execute
+}
+
+/**
+ * Map a keypress to a prompt action
+ *
+ * @param input - Character typed by the user
+ * @param keys - Resolved key mapping
+ * @param allowRegenerate - Whether regenerate is currently offered
+ * @returns The action, or null if the key isn't bound
+ */
+export function parseConfirmKey(
+  input: string,
+  keys: ConfirmKeys,
+  allowRegenerate: boolean = true
+): UserAction | null {
+  const pressed = input.toLowerCase()
+
+  if (pressed === keys.execute.toLowerCase()) {
+    return UserAction.Execute
+  }
+  if (pressed === keys.abort.toLowerCase()) {
+    return UserAction.Abort
+  }
+  if (allowRegenerate && pressed === keys.regenerate.toLowerCase()) {
+    return UserAction.Regenerate
+  }
+  return null
+}
+
+/**
+ * Hint line shown under the action buttons
+ *
+ * @param keys - Resolved key mapping
+ * @param allowRegenerate - Whether regenerate is currently offered
+ * @param defaultAction - What Enter does (ui.interactive-default-action)
+ */
+export function formatKeyHints(
+  keys: ConfirmKeys,
+  allowRegenerate: boolean = true,
+  defaultAction: InteractiveDefaultAction = DEFAULT_INTERACTIVE_ACTION
+): string {
+  const hints = [
+    '↑↓ select',
+    defaultAction === 'output' ? 'Enter print' : 'Enter confirm',
+    `${keys.execute} run`,
+    `${keys.abort} cancel`,
+  ]
+  if (allowRegenerate) {
+    hints.push(`${keys.regenerate} regenerate`)
+  }
+  hints.push('Esc cancel')
+  return hints.join('  ')
+}
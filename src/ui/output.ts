@@ -1,6 +1,10 @@
 // src/ui/output.ts
 // Pretty output formatting for non-interactive mode
 
+import { createInterface } from 'readline'
+import { writeCommands } from '../output/format.js'
+import { formatCommand } from './utils/formatCommand.js'
+
 const isTTY = process.stdout.isTTY
 
 // ANSI color codes
@@ -23,16 +27,7 @@ function color(text: string, ...codes: string[]): string {
  * Print a command to stdout with nice formatting
  */
 export function printCommand(command: string, isDangerous = false): void {
-  if (isTTY) {
-    const promptColor = isDangerous ? colors.red : colors.green
-    const cmdColor = isDangerous ? colors.red : colors.cyan
-    process.stdout.write(
-      `${promptColor}${colors.bold}$${colors.reset} ${cmdColor}${command}${colors.reset}\n`
-    )
-  } else {
-    // Clean output for piping
-    process.stdout.write(command)
-  }
+  writeCommands([command], 'plain', { piped: !isTTY, dangerous: isDangerous })
 }
 
 /**
@@ -62,3 +57,124 @@ export function printSuccess(message: string): void {
 export function printInfo(message: string): void {
   process.stderr.write(color(message, colors.dim) + '\n')
 }
+
+/**
+ * What one generation cost (--stats)
+ */
+export interface RunStats {
+  provider: string
+  model?: string
+  /** Total tokens, when the provider reported usage */
+  totalTokens?: number
+  /** Wall-clock time of the AI call */
+  durationMs: number
+}
+
+/**
+ * One-line run summary, e.g. "openrouter/qwen3-coder · 342 tokens · 1.2s"
+ */
+export function formatRunStats(stats: RunStats): string {
+  const parts = [
+    stats.model ? `${stats.provider}/${stats.model}` : stats.provider,
+  ]
+  if (stats.totalTokens !== undefined) {
+    parts.push(`${stats.totalTokens} tokens`)
+  }
+  parts.push(`${(stats.durationMs / 1000).toFixed(1)}s`)
+  return parts.join(' · ')
+}
+
+/**
+ * Options for renderOptionsTable
+ */
+export interface OptionsTableOptions {
+  /** Emit ANSI colors (selected row, danger markers) */
+  color?: boolean
+  /** Per-command danger flags, by index */
+  dangerous?: boolean[]
+  /** Terminal width; longer commands are truncated with an ellipsis */
+  width?: number
+}
+
+/**
+ * Render command options as an aligned table, one row per option:
+ * selection pointer, 1-based index, danger marker ("!"), command
+ * Multi-line commands are shown on one row joined with "; "
+ *
+ * @param commands - Options to list
+ * @param selectedIndex - Index of the highlighted option (0-based)
+ * @param options - Color, danger flags and width
+ * @returns Table text, newline-terminated ('' for no commands)
+ */
+export function renderOptionsTable(
+  commands: string[],
+  selectedIndex: number,
+  options: OptionsTableOptions = {}
+): string {
+  const { color: useColor = false, dangerous = [], width } = options
+  const indexWidth = String(commands.length).length
+  const paint = (text: string, ...codes: string[]) =>
+    useColor ? codes.join('') + text + colors.reset : text
+
+  // "> NN. ! " before the command
+  const prefixWidth = indexWidth + 6
+
+  return commands
+    .map((command, i) => {
+      const selected = i === selectedIndex
+      const highlight = (text: string) =>
+        selected ? paint(text, colors.bold, colors.cyan) : text
+
+      const pointer = selected ? '>' : ' '
+      const index = `${String(i + 1).padStart(indexWidth)}.`
+      const marker = dangerous[i] ? paint('!', colors.bold, colors.red) : ' '
+      const oneLine = command.replace(/\r?\n/g, '; ')
+      const text =
+        width === undefined
+          ? oneLine
+          : formatCommand(oneLine, width - prefixWidth)
+
+      return `${highlight(`${pointer} ${index}`)} ${marker} ${highlight(text)}\n`
+    })
+    .join('')
+}
+
+// Accepted affirmative answers (compared after ASCII-only lowercasing)
+const YES_ANSWERS = ['y', 'yes']
+
+/**
+ * Parse a confirmation answer
+ * Only ASCII y/yes (any case, surrounding whitespace ignored) confirm;
+ * anything else, including non-ASCII input, is treated as "no"
+ *
+ * @param answer - Raw line typed by the user
+ * @returns true if the answer confirms
+ */
+export function parseConfirmAnswer(answer: string): boolean {
+  // Lowercase ASCII letters only so locale-specific case mappings
+  // (e.g. Turkish dotted I) can't turn other input into "y"
+  const normalized = answer
+    .trim()
+    .replace(/[A-Z]/g, (letter) =>
+      String.fromCharCode(letter.charCodeAt(0) + 32)
+    )
+  return YES_ANSWERS.includes(normalized)
+}
+
+/**
+ * Ask a yes/no question on stderr
+ * Resolves false without prompting when stdin is not a TTY
+ */
+export function confirm(question: string): Promise<boolean> {
+  if (!process.stdin.isTTY) {
+    return Promise.resolve(false)
+  }
+
+  const rl = createInterface({ input: process.stdin, output: process.stderr })
+  return new Promise((resolve) => {
+    rl.question(`${question} [y/N] `, (answer) => {
+      rl.close()
+      resolve(parseConfirmAnswer(answer))
+    })
+  })
+}
@@ -0,0 +1,39 @@
+// src/ui/regenerate.ts
+// Bookkeeping for the interactive "regenerate" action
+
+/** Maximum regenerations per run, so a picky session can't loop forever */
+export const MAX_REGENERATIONS = 3
+
+/**
+ * Whether another regeneration is allowed
+ *
+ * @param count - Regenerations already performed
+ * @param max - Regeneration limit
+ */
+export function canRegenerate(
+  count: number,
+  max: number = MAX_REGENERATIONS
+): boolean {
+  return count < max
+}
+
+/**
+ * Extend the instruction so the model avoids the rejected commands
+ *
+ * @param instruction - Original user instruction
+ * @param rejected - Commands the user asked to regenerate
+ * @returns Instruction with a "give different options" hint
+ */
+export function buildRegenerateInstruction(
+  instruction: string,
+  rejected: string[]
+): string {
+  if (rejected.length === 0) {
+    return instruction
+  }
+  const list = rejected.map((cmd) => `- ${cmd}`).join('\n')
+  return (
+    `${instruction}\n\n` +
+    `These suggestions were rejected; give different commands:\n${list}`
+  )
+}
@@ -1,6 +1,8 @@
 // src/ui/spinner.ts
 // Simple terminal spinner for loading states (non-Ink)
 
+import { parseEnvBool } from '../config/index.js'
+
 const FRAMES = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏']
 const INTERVAL = 80
 
@@ -9,13 +11,36 @@ export interface SpinnerInstance {
   update: (message: string) => void
 }
 
+/**
+ * Whether the spinner should be shown
+ * Disabled by --no-spinner, a truthy CLAI_NO_SPINNER, or --quiet;
+ * other stderr output is unaffected
+ *
+ * @param options - noSpinner (--no-spinner) and quiet (-q) flags
+ * @param env - Environment to read CLAI_NO_SPINNER from
+ */
+export function isSpinnerEnabled(
+  options: { noSpinner: boolean; quiet: boolean },
+  env: NodeJS.ProcessEnv = process.env
+): boolean {
+  if (options.noSpinner || options.quiet) {
+    return false
+  }
+  // Any value other than an explicit false (0/false/no/off) disables it
+  const envValue = env.CLAI_NO_SPINNER
+  return !envValue || parseEnvBool(envValue) === false
+}
+
 /**
  * Create a simple terminal spinner
  * Renders to stderr to keep stdout clean
  */
-export function createSpinner(message: string): SpinnerInstance {
-  // Only show spinner in TTY mode
-  if (!process.stderr.isTTY) {
+export function createSpinner(
+  message: string,
+  enabled: boolean = true
+): SpinnerInstance {
+  // Only show spinner in TTY mode (and when not disabled)
+  if (!enabled || !process.stderr.isTTY) {
     return {
       stop: () => {},
       update: () => {},
@@ -54,15 +79,18 @@ export function createSpinner(message: string): SpinnerInstance {
 
 /**
  * Run an async function with a spinner
+ * The spinner is passed to `fn` so it can update the message as it goes
+ * With `enabled` false, `fn` still runs but nothing is drawn
  */
 export async function withSpinner<T>(
   message: string,
-  fn: () => Promise<T>,
-  successMessage?: string
+  fn: (spinner: SpinnerInstance) => Promise<T>,
+  successMessage?: string,
+  enabled: boolean = true
 ): Promise<T> {
-  const spinner = createSpinner(message)
+  const spinner = createSpinner(message, enabled)
   try {
-    const result = await fn()
+    const result = await fn(spinner)
     spinner.stop(successMessage)
     return result
   } catch (error) {
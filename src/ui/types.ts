@@ -8,7 +8,10 @@ import type { Config } from '../config/types.js'
  */
 export enum UserAction {
   Execute = 'execute',
+  /** Print the command to stdout instead of running it */
+  Output = 'output',
   Abort = 'abort',
+  Regenerate = 'regenerate',
 }
 
 /**
@@ -38,6 +41,13 @@ export interface UIState {
 export interface AppProps {
   commands: string[]
   isDangerous: boolean
+  /** Per-command danger flags for the options table, by index */
+  dangerousCommands?: boolean[]
+  /** Per-command indirection warnings (eval, $(...), ...), by index */
+  indirectionWarnings?: (string | null)[]
+  dangerExplanation?: string
+  /** Whether the `r` key may request new options */
+  allowRegenerate?: boolean
   config: Config
   onComplete: (action: UserAction, command: string) => void
 }
@@ -49,6 +59,14 @@ export interface RenderOptions {
   commands: string[]
   config: Config
   isDangerous: boolean
+  /** Per-command danger flags for the options table, by index */
+  dangerousCommands?: boolean[]
+  /** Per-command indirection warnings (eval, $(...), ...), by index */
+  indirectionWarnings?: (string | null)[]
+  /** AI-provided risk explanation (--explain-danger) */
+  dangerExplanation?: string
+  /** Offer the regenerate action (false once the limit is reached) */
+  allowRegenerate?: boolean
 }
 
 /**
@@ -0,0 +1,23 @@
+// Single source for the clai version (shown by --version and sent as the User-Agent)
+export const VERSION = '0.1.0'
+
+/**
+ * Machine-readable version info (--version-json)
+ */
+export interface VersionInfo {
+  name: 'clai'
+  version: string
+  /** Node.js runtime version, e.g. v20.11.0 */
+  node: string
+  /** Platform and architecture, e.g. linux-x64 */
+  target: string
+}
+
+export function getVersionInfo(): VersionInfo {
+  return {
+    name: 'clai',
+    version: VERSION,
+    node: process.version,
+    target: `${process.platform}-${process.arch}`,
+  }
+}
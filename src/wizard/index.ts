@@ -0,0 +1,105 @@
+// First-run setup: ask for a provider and API key when none is configured
+// Only offered on a terminal; non-interactive runs fail as before
+
+import { createInterface } from 'readline'
+import { parseConfirmAnswer } from '../ui/output.js'
+
+/** Providers the wizard can set up (those getProvider can construct) */
+export const SETUP_PROVIDERS = ['openrouter']
+
+/**
+ * Prompt I/O, injected so the flow can be tested without a terminal
+ */
+export interface WizardIO {
+  /** Ask for a line of text */
+  ask(question: string): Promise<string>
+  /** Ask a yes/no question */
+  confirm(question: string): Promise<boolean>
+  /** Show a message (stderr) */
+  say(message: string): void
+}
+
+/**
+ * Outcome of a completed wizard
+ * `savedTo` is set when the user chose to write the settings to a file
+ */
+export interface WizardResult {
+  provider: string
+  apiKey: string
+  savedTo?: string
+}
+
+/**
+ * Walk the user through choosing a provider and entering an API key
+ *
+ * Empty answers skip: an empty provider picks the default, an empty key
+ * aborts the wizard (null), so clai reports the missing key as usual
+ *
+ * @param io - Prompt I/O
+ * @param defaultProvider - Provider offered when the answer is empty
+ * @param save - Writes `section.key=value` assignments, returns the file
+ * @param configPath - File `save` writes to (shown in the question)
+ * @returns Chosen provider and key, or null if the user skipped setup
+ */
+export async function runSetupWizard(
+  io: WizardIO,
+  defaultProvider: string,
+  save: (assignments: string[]) => string,
+  configPath: string
+): Promise<WizardResult | null> {
+  io.say("No API key is configured. Let's set one up (leave empty to skip).")
+
+  const answer = (
+    await io.ask(
+      `Provider [${SETUP_PROVIDERS.join(', ')}] (${defaultProvider}): `
+    )
+  )
+    .trim()
+    .toLowerCase()
+  const provider = answer || defaultProvider
+  if (!SETUP_PROVIDERS.includes(provider)) {
+    io.say(`Unknown provider: ${provider}`)
+    return null
+  }
+
+  const apiKey = (await io.ask(`${provider} API key: `)).trim()
+  if (!apiKey) {
+    return null
+  }
+
+  if (!(await io.confirm(`Save to ${configPath} (mode 0600)?`))) {
+    io.say('Not saved; the key is used for this run only.')
+    return { provider, apiKey }
+  }
+
+  const savedTo = save([
+    `provider.default=${provider}`,
+    `${provider}.api-key=${JSON.stringify(apiKey)}`,
+  ])
+  io.say(`Saved to ${savedTo}`)
+  return { provider, apiKey, savedTo }
+}
+
+/**
+ * Wizard I/O on the real terminal: reads stdin, prompts on stderr
+ */
+export function createTerminalIO(): WizardIO {
+  const ask = (question: string) =>
+    new Promise<string>((resolve) => {
+      const rl = createInterface({
+        input: process.stdin,
+        output: process.stderr,
+      })
+      rl.question(question, (answer) => {
+        rl.close()
+        resolve(answer)
+      })
+    })
+
+  return {
+    ask,
+    confirm: async (question) =>
+      parseConfirmAnswer(await ask(`${question} [y/N] `)),
+    say: (message) => process.stderr.write(message + '\n'),
+  }
+}
@@ -10,15 +10,43 @@ import {
 } from 'vitest'
 import {
   generateCommands,
+  generateCommandsWithRaw,
   AIError,
+  AITimeoutError,
+  AIConnectionError,
+  RequestDumpedError,
+  checkProviders,
+  formatModelInfo,
   buildPrompt,
+  resolveSectionOrder,
   parseResponse,
+  stripReasoningTags,
+  StreamingExtractor,
   formatPromptForDebug,
   OpenRouterProvider,
   MockProvider,
+  buildExplainDangerPrompt,
+  buildExplainCommandPrompt,
+  explainDanger,
+  explainCommand,
+  generateCommandsWithProvider,
+  getCapabilities,
+  getPromptOptions,
+  DEFAULT_CAPABILITIES,
+  ProviderChain,
+  RequestThrottle,
+  CancelledError,
+  type AIProvider,
+  type ChatRequest,
+  type ChatResponse,
+  type ProviderCapabilities,
 } from '../src/ai/index.js'
 import { ContextData, SystemInfo } from '../src/context/types.js'
 import { Config } from '../src/config/types.js'
+import { ConfigError } from '../src/config/index.js'
+import { VERSION } from '../src/version.js'
+import { toJsonError, formatJsonError } from '../src/error/index.js'
+import { retryDelay } from '../src/ai/providers/openrouter.js'
 
 describe('AI Module', () => {
   // Mock context for testing
@@ -40,12 +68,18 @@ describe('AI Module', () => {
   }
 
   const mockConfig: Config = {
-    provider: { default: 'openrouter', fallback: [] },
+    provider: {
+      default: 'openrouter',
+      fallback: [],
+      stopOnAuthError: false,
+      jsonRetries: 0,
+    },
     context: {
       maxFiles: 10,
       maxHistory: 3,
       redactPaths: false,
       redactUsername: false,
+      mode: 'full',
     },
     safety: {
       confirmDangerous: true,
@@ -56,6 +90,10 @@ describe('AI Module', () => {
       interactive: false,
       numOptions: 1,
     },
+    prompt: {
+      instructionPrefix: '',
+      instructionSuffix: '',
+    },
     providers: {},
     quiet: false,
     verbose: 0,
@@ -106,6 +144,22 @@ describe('AI Module', () => {
       expect(messages[1].content).toContain('some input data')
     })
 
+    it('should label stdin with the --stdin-as-file name', () => {
+      const messages = buildPrompt(
+        {
+          ...mockContext,
+          stdin: 'FROM node:20\nRUN npm ci',
+          stdinName: 'Dockerfile',
+        },
+        'optimize this',
+        1
+      )
+      const content = messages[1].content
+
+      expect(content).toContain('Contents of Dockerfile:\nFROM node:20\nRUN npm ci')
+      expect(content).not.toContain('Stdin input:')
+    })
+
     it('should not include history section when empty', () => {
       const contextNoHistory: ContextData = {
         ...mockContext,
@@ -118,6 +172,186 @@ describe('AI Module', () => {
       expect(messages[1].content).not.toContain('Recent Shell History:')
     })
 
+    it('should only send allowlisted system fields', () => {
+      const messages = buildPrompt(
+        {
+          ...mockContext,
+          systemFields: ['os-name', 'os-version', 'architecture', 'shell'],
+        },
+        'list files',
+        1
+      )
+      const content = messages[1].content
+
+      expect(content).toContain('System Context:')
+      expect(content).toContain('Shell: bash')
+      expect(content).not.toContain('User:')
+      expect(content).not.toContain('Memory:')
+    })
+
+    it('should list detected package managers in the system context', () => {
+      const messages = buildPrompt(
+        { ...mockContext, packageManagers: ['apt', 'snap'] },
+        'install ripgrep',
+        1
+      )
+
+      expect(messages[1].content).toContain('Package Managers: apt, snap')
+    })
+
+    it('should include the previous exit status when provided', () => {
+      const failed = buildPrompt({ ...mockContext, lastExit: 127 }, 'fix it', 1)
+      expect(failed[1].content).toContain(
+        'Last command failed: exited with code 127'
+      )
+
+      const succeeded = buildPrompt({ ...mockContext, lastExit: 0 }, 'next', 1)
+      expect(succeeded[1].content).toContain(
+        'Last command succeeded: exited with code 0'
+      )
+
+      const unknown = buildPrompt(mockContext, 'fix it', 1)
+      expect(unknown[1].content).not.toContain('Last command')
+    })
+
+    it('should list active environments in the system context', () => {
+      const messages = buildPrompt(
+        {
+          ...mockContext,
+          environments: ['python venv /srv/app/.venv (active)'],
+        },
+        'install requests',
+        1
+      )
+
+      expect(messages[1].content).toContain(
+        'Environments: python venv /srv/app/.venv (active)'
+      )
+    })
+
+    it('should include the current time in the system context', () => {
+      const messages = buildPrompt(
+        {
+          ...mockContext,
+          time: {
+            local: '2026-03-14T09:26:53+01:00',
+            timezone: 'Europe/Berlin',
+          },
+        },
+        'files modified today',
+        1
+      )
+
+      expect(messages[1].content).toContain(
+        'Current Time: 2026-03-14T09:26:53+01:00 (Europe/Berlin)'
+      )
+    })
+
+    it('should only include history when limited to the history section', () => {
+      const historyOnly: ContextData = {
+        ...mockContext,
+        stdin: 'piped data',
+        sections: ['history'],
+      }
+
+      const messages = buildPrompt(historyOnly, 'undo that', 1)
+      const content = messages[1].content
+
+      expect(content).toContain('Recent Shell History:')
+      expect(content).toContain('git status')
+      expect(content).not.toContain('System Context:')
+      expect(content).not.toContain('Directory Context:')
+      expect(content).not.toContain('Stdin input:')
+      expect(content).toContain('User Instruction: undo that')
+    })
+
+    it('should bracket the instruction with prefix and suffix', () => {
+      const config: Config = {
+        ...mockConfig,
+        prompt: {
+          instructionPrefix: 'Using our standard tooling, ',
+          instructionSuffix: ' (prefer POSIX tools)',
+        },
+      }
+
+      const messages = buildPrompt(
+        mockContext,
+        'list files',
+        1,
+        getPromptOptions(config)
+      )
+
+      expect(messages[1].content).toContain(
+        'User Instruction: Using our standard tooling, list files (prefer POSIX tools)'
+      )
+    })
+
+    it('should leave the instruction unchanged with empty affixes', () => {
+      const messages = buildPrompt(
+        mockContext,
+        'list files',
+        1,
+        getPromptOptions(mockConfig)
+      )
+
+      expect(messages[1].content).toContain('User Instruction: list files\n')
+    })
+
+    it('should include inline context next to the instruction', () => {
+      const context: ContextData = {
+        ...mockContext,
+        inlineContext: 'we use podman',
+      }
+
+      const content = buildPrompt(context, 'list containers', 1)[1].content
+
+      expect(content).toContain(
+        'Additional Context: we use podman\nUser Instruction: list containers'
+      )
+    })
+
+    it('should follow a custom section order', () => {
+      const config: Config = {
+        ...mockConfig,
+        prompt: {
+          instructionPrefix: '',
+          instructionSuffix: '',
+          sectionOrder: ['instruction', 'system', 'directory'],
+        },
+      }
+
+      const content = buildPrompt(
+        mockContext,
+        'list files',
+        1,
+        getPromptOptions(config)
+      )[1].content
+
+      expect(content.startsWith('User Instruction: list files\n')).toBe(true)
+      expect(content.indexOf('User Instruction:')).toBeLessThan(
+        content.indexOf('System Context:')
+      )
+      expect(content.indexOf('System Context:')).toBeLessThan(
+        content.indexOf('Directory Context:')
+      )
+    })
+
+    it('should keep the default order without a section order', () => {
+      expect(resolveSectionOrder()).toEqual([
+        'system',
+        'directory',
+        'history',
+        'stdin',
+        'instruction',
+      ])
+    })
+
+    it('should skip unknown sections and always keep the instruction', () => {
+      expect(
+        resolveSectionOrder(['stdin', 'bogus', 'stdin', 'system'])
+      ).toEqual(['stdin', 'system', 'instruction'])
+    })
+
     it('should format prompt for debug output', () => {
       const messages = buildPrompt(mockContext, 'test', 1)
       const formatted = formatPromptForDebug(messages)
@@ -129,6 +363,108 @@ describe('AI Module', () => {
   })
 
   describe('Response Parser', () => {
+    describe('strict JSON mode', () => {
+      const prose = 'Sure! You could run ls -la or find . -type f'
+      const embedded = 'Here you go: {"commands": ["ls", "ls -la"]} enjoy'
+
+      it('should error on prose in strict mode', () => {
+        expect(() => parseResponse(prose, true, true)).toThrow(AIError)
+        expect(() => parseResponse(prose, true, true)).toThrow(
+          '--strict-json'
+        )
+      })
+
+      it('should error on JSON embedded in text in strict mode', () => {
+        expect(() => parseResponse(embedded, true, true)).toThrow(AIError)
+      })
+
+      it('should still extract leniently by default', () => {
+        expect(parseResponse(prose, true)).toEqual([prose])
+        expect(parseResponse(embedded, true)).toEqual(['ls', 'ls -la'])
+      })
+
+      it('should accept a clean commands object, fenced or not', () => {
+        const json = '{"commands": ["ls", "pwd"]}'
+        const fenced = '```json\n' + json + '\n```'
+        expect(parseResponse(json, true, true)).toEqual(['ls', 'pwd'])
+        expect(parseResponse(fenced, true, true)).toEqual(['ls', 'pwd'])
+      })
+
+      it('should reject a bare array or non-string entries', () => {
+        expect(() => parseResponse('["ls"]', true, true)).toThrow(AIError)
+        expect(() => parseResponse('{"commands": [1]}', true, true)).toThrow(
+          AIError
+        )
+      })
+
+      it('should not affect single-command mode', () => {
+        expect(parseResponse('ls -la', false, true)).toEqual(['ls -la'])
+      })
+    })
+
+    describe('prose around a single command', () => {
+      it('should take the command after a lead-in and colon', () => {
+        expect(parseResponse("Here's the command: ls -la", false)).toEqual([
+          'ls -la',
+        ])
+        expect(parseResponse('Sure! Run this:\ngit status', false)).toEqual([
+          'git status',
+        ])
+      })
+
+      it('should strip backticks around the command', () => {
+        expect(
+          parseResponse("Here's a one-liner: `du -sh *`.", false)
+        ).toEqual(['du -sh *'])
+      })
+
+      it('should take a single fenced block out of prose', () => {
+        const content =
+          'To list everything, use:\n```bash\nls -la\n```\nThis shows dotfiles.'
+        expect(parseResponse(content, false)).toEqual(['ls -la'])
+      })
+
+      it('should leave anything it does not recognize alone', () => {
+        const note = 'echo "Note: run this: ls"'
+        const trailing = "Here's the command:\nls\nThat lists files."
+        expect(parseResponse(note, false)).toEqual([note])
+        expect(parseResponse(trailing, false)).toEqual([trailing])
+      })
+    })
+
+    describe('reasoning tags', () => {
+      const thinking =
+        '<think>\nThe user wants large files, so find with -size.\n</think>\n'
+
+      it('should drop a <think> block before a fenced command', () => {
+        const content = thinking + '```bash\nfind . -size +100M\n```'
+        expect(parseResponse(content, false)).toEqual(['find . -size +100M'])
+      })
+
+      it('should drop reasoning before a JSON command list', () => {
+        const content =
+          '<reasoning>Two options.</reasoning>{"commands": ["ls", "pwd"]}'
+        expect(parseResponse(content, true, true)).toEqual(['ls', 'pwd'])
+      })
+
+      it('should drop an unopened closing tag and special tokens', () => {
+        expect(
+          parseResponse('thinking...</think>\nls -la<|im_end|>', false)
+        ).toEqual(['ls -la'])
+      })
+
+      it('should keep reasoning when stripping is disabled', () => {
+        const content = thinking + 'ls'
+        expect(parseResponse(content, false, false, false)).toEqual([
+          content.trim(),
+        ])
+      })
+
+      it('should strip reasoning tags on their own', () => {
+        expect(stripReasoningTags('<THINKING>hmm</THINKING> pwd')).toBe('pwd')
+      })
+    })
+
     it('should parse clean single command', () => {
       const result = parseResponse('ls -la', false)
 
@@ -220,6 +556,57 @@ describe('AI Module', () => {
 
       expect(result).toEqual(['cmd1', 'cmd2', 'cmd3'])
     })
+
+    describe('StreamingExtractor', () => {
+      const responses = [
+        '```bash\nls -la\n```',
+        "```\nfind . -name '*.ts' | xargs grep -n TODO\n```\n",
+        '  ```sh\n\nfor f in *; do\n  echo "$f"\ndone\n\n```  ',
+        '```bash\ncat <<EOF\n```\nEOF\n```',
+        '```ls -la```',
+        'git status\n',
+      ]
+
+      function stream(chunks: string[]): string {
+        const extractor = new StreamingExtractor()
+        const output = chunks.map((chunk) => extractor.push(chunk)).join('')
+        return output + extractor.finish()
+      }
+
+      it('should match the one-shot parse for every two and three chunk split', () => {
+        for (const response of responses) {
+          const [expected] = parseResponse(response, false)
+          for (let i = 0; i <= response.length; i++) {
+            for (let j = i; j <= response.length; j++) {
+              const chunks = [
+                response.slice(0, i),
+                response.slice(i, j),
+                response.slice(j),
+              ]
+              expect(stream(chunks)).toBe(expected)
+            }
+          }
+        }
+      })
+
+      it('should match the one-shot parse when fed one character at a time', () => {
+        for (const response of responses) {
+          const [expected] = parseResponse(response, false)
+          expect(stream([...response])).toBe(expected)
+        }
+      })
+
+      it('should hold back the opening fence until its line is complete', () => {
+        const extractor = new StreamingExtractor()
+
+        expect(extractor.push('``')).toBe('')
+        expect(extractor.push('`ba')).toBe('')
+        expect(extractor.push('sh\nls')).toBe('ls')
+        expect(extractor.push(' -la\n`')).toBe(' -la')
+        expect(extractor.push('``')).toBe('')
+        expect(extractor.finish()).toBe('')
+      })
+    })
   })
 
   describe('OpenRouter Provider', () => {
@@ -276,6 +663,172 @@ describe('AI Module', () => {
       })
     })
 
+    it('should send default attribution headers', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+
+      await provider.complete({
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      })
+
+      const headers = (fetch as any).mock.calls[0][1].headers
+      expect(headers['HTTP-Referer']).toBe('https://github.com/clai')
+      expect(headers['X-Title']).toBe('clai')
+    })
+
+    it('should send a clai User-Agent by default', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+
+      await provider.complete({
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      })
+
+      const headers = (fetch as any).mock.calls[0][1].headers
+      expect(headers['User-Agent']).toBe(`clai/${VERSION}`)
+    })
+
+    it('should send the configured User-Agent', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+
+      const custom = new OpenRouterProvider('test-api-key', {
+        userAgent: 'my-wrapper/2.0',
+      })
+      await custom.complete({
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      })
+
+      const headers = (fetch as any).mock.calls[0][1].headers
+      expect(headers['User-Agent']).toBe('my-wrapper/2.0')
+    })
+
+    it('should send configured attribution headers', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+
+      const custom = new OpenRouterProvider('test-api-key', {
+        referer: 'https://example.com/my-app',
+        title: 'my-app',
+      })
+      await custom.complete({
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      })
+
+      const headers = (fetch as any).mock.calls[0][1].headers
+      expect(headers['HTTP-Referer']).toBe('https://example.com/my-app')
+      expect(headers['X-Title']).toBe('my-app')
+    })
+
+    it('should merge extra params into the request body', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+
+      const custom = new OpenRouterProvider('test-api-key', {
+        extraParams: { top_p: 0.9, model: 'sneaky', stop: ['\n\n'] },
+      })
+      await custom.complete({
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+        temperature: 0.1,
+      })
+
+      const body = JSON.parse((fetch as any).mock.calls[0][1].body)
+      expect(body.top_p).toBe(0.9)
+      expect(body.stop).toEqual(['\n\n'])
+      expect(body.temperature).toBe(0.1)
+      expect(body.model).toBe('test')
+      expect(body.messages).toEqual([{ role: 'user', content: 'test' }])
+    })
+
+    it('should send reasoning effort only when set', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+      })
+      const messages = [{ role: 'user' as const, content: 'test' }]
+
+      await provider.complete({
+        model: 'test',
+        messages,
+        reasoningEffort: 'high',
+      })
+      await provider.complete({ model: 'test', messages })
+
+      const withEffort = JSON.parse((fetch as any).mock.calls[0][1].body)
+      const without = JSON.parse((fetch as any).mock.calls[1][1].body)
+      expect(withEffort.reasoning).toEqual({ effort: 'high' })
+      expect(without).not.toHaveProperty('reasoning')
+    })
+
+    it('should dump the request instead of sending it', async () => {
+      ;(global as any).fetch = vi.fn()
+      const dumping = new OpenRouterProvider('sk-or-secret', {
+        dumpRequest: true,
+      })
+
+      const error = await dumping
+        .complete({
+          model: 'qwen/qwen3-coder',
+          messages: [{ role: 'user' as const, content: 'list files' }],
+          temperature: 0.1,
+        })
+        .catch((err: unknown) => err)
+
+      expect(fetch).not.toHaveBeenCalled()
+      expect(error).toBeInstanceOf(RequestDumpedError)
+      const dump = (error as RequestDumpedError).dump
+      expect(dump).toContain(
+        'POST https://openrouter.ai/api/v1/chat/completions'
+      )
+      expect(dump).toContain('Authorization: Bearer ********')
+      expect(dump).not.toContain('sk-or-secret')
+
+      const body = JSON.parse(dump.slice(dump.indexOf('\n{') + 1))
+      expect(body.model).toBe('qwen/qwen3-coder')
+      expect(body.messages).toEqual([{ role: 'user', content: 'list files' }])
+    })
+
+    it('should not fall back to another provider after a dump', async () => {
+      const backup: AIProvider = {
+        name: 'backup',
+        isAvailable: () => true,
+        complete: vi.fn(),
+      }
+      const chain = new ProviderChain([
+        {
+          provider: new OpenRouterProvider('key', { dumpRequest: true }),
+          model: 'a',
+        },
+        { provider: backup, model: 'b' },
+      ])
+
+      await expect(
+        chain.complete({ model: 'a', messages: [] })
+      ).rejects.toThrow(RequestDumpedError)
+      expect(backup.complete).not.toHaveBeenCalled()
+    })
+
     it('should retry on 429 with exponential backoff', async () => {
       const mockSuccess = {
         choices: [{ message: { content: 'success' } }],
@@ -370,6 +923,113 @@ describe('AI Module', () => {
       await expect(provider.complete(request)).rejects.toThrow('Timeout error')
     })
 
+    it('should classify a 408 as a timeout in the JSON error output', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: false,
+        status: 408,
+        text: () => Promise.resolve('Timeout'),
+      })
+
+      const error = await provider
+        .complete({
+          model: 'test',
+          messages: [{ role: 'user' as const, content: 'test' }],
+        })
+        .catch((err: unknown) => err)
+
+      expect(error).toBeInstanceOf(AITimeoutError)
+      expect(JSON.parse(formatJsonError(error))).toEqual({
+        error: 'Timeout error (408): Timeout',
+        category: 'api',
+        reason: 'timeout',
+        exit_code: 4,
+        status_code: 408,
+      })
+    })
+
+    it('should report a request with no response as a timeout', async () => {
+      vi.useFakeTimers()
+      try {
+        ;(global as any).fetch = vi.fn(
+          (_url: string, init: { signal: AbortSignal }) =>
+            new Promise((_resolve, reject) => {
+              init.signal.addEventListener('abort', () =>
+                reject(new DOMException('aborted', 'AbortError'))
+              )
+            })
+        )
+
+        const pending = provider
+          .complete({
+            model: 'test',
+            messages: [{ role: 'user' as const, content: 'test' }],
+          })
+          .catch((err: unknown) => err)
+        await vi.advanceTimersByTimeAsync(60000)
+        const error = await pending
+
+        expect(error).toBeInstanceOf(AITimeoutError)
+        expect(toJsonError(error).reason).toBe('timeout')
+        // A timeout is not retried
+        expect(fetch).toHaveBeenCalledTimes(1)
+      } finally {
+        vi.useRealTimers()
+      }
+    })
+
+    it('should retry 5xx responses and then succeed', async () => {
+      const failure = {
+        ok: false,
+        status: 503,
+        text: () => Promise.resolve('Service unavailable'),
+      }
+      const fetchMock = vi
+        .fn()
+        .mockResolvedValueOnce(failure)
+        .mockResolvedValueOnce(failure)
+        .mockResolvedValueOnce({
+          ok: true,
+          json: () =>
+            Promise.resolve({ choices: [{ message: { content: 'ls' } }] }),
+        })
+      ;(global as any).fetch = fetchMock
+
+      const request = {
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      }
+
+      const response = await provider.complete(request)
+      expect(response.content).toBe('ls')
+      expect(fetchMock).toHaveBeenCalledTimes(3)
+    })
+
+    it('should not retry 4xx responses other than 429', async () => {
+      const fetchMock = vi.fn().mockResolvedValue({
+        ok: false,
+        status: 400,
+        text: () => Promise.resolve('Bad request'),
+      })
+      ;(global as any).fetch = fetchMock
+
+      const request = {
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      }
+
+      await expect(provider.complete(request)).rejects.toThrow(
+        'API error (400)'
+      )
+      expect(fetchMock).toHaveBeenCalledTimes(1)
+    })
+
+    it('should back off less for server errors than for rate limits', () => {
+      expect(retryDelay(503, 0)).toBeLessThan(retryDelay(429, 0)!)
+      expect(retryDelay(500, 1)).toBe(2 * retryDelay(500, 0)!)
+      expect(retryDelay(400, 0)).toBeNull()
+      expect(retryDelay(404, 0)).toBeNull()
+    })
+
     it('should throw AIError after 3 failed 429 retries', async () => {
       const fetchMock = vi.fn().mockResolvedValue({
         ok: false,
@@ -419,13 +1079,114 @@ describe('AI Module', () => {
         'Invalid response'
       )
     })
-  })
 
-  describe('Mock Provider', () => {
-    let provider: MockProvider
+    it('should explain a null content response with tool calls', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({
+            choices: [
+              {
+                message: {
+                  role: 'assistant',
+                  content: null,
+                  tool_calls: [
+                    {
+                      id: 'call_1',
+                      type: 'function',
+                      function: { name: 'run_shell', arguments: '{}' },
+                    },
+                  ],
+                },
+                finish_reason: 'tool_calls',
+              },
+            ],
+          }),
+      })
 
-    beforeEach(() => {
-      provider = new MockProvider()
+      const error = await provider
+        .complete({
+          model: 'test',
+          messages: [{ role: 'user' as const, content: 'test' }],
+        })
+        .catch((err: unknown) => err)
+
+      expect(error).toBeInstanceOf(AIError)
+      expect((error as Error).message).toBe(
+        'Model returned no text content; it may have attempted a tool call'
+      )
+    })
+
+    it('should report a null content response without tool calls', async () => {
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: null } }] }),
+      })
+
+      await expect(
+        provider.complete({
+          model: 'test',
+          messages: [{ role: 'user' as const, content: 'test' }],
+        })
+      ).rejects.toThrow('Model returned no text content')
+    })
+
+    it('should reject promptly with CancelledError when cancelled mid-request', async () => {
+      // A request that only ends when its fetch signal is aborted
+      const fetchMock = vi.fn(
+        (_url: string, init: { signal: AbortSignal }) =>
+          new Promise((_, reject) => {
+            init.signal.addEventListener('abort', () =>
+              reject(new Error('The operation was aborted'))
+            )
+          })
+      )
+      ;(global as any).fetch = fetchMock
+
+      const request = {
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      }
+      const controller = new AbortController()
+      const started = Date.now()
+      const pending = provider.complete(request, controller.signal)
+      setTimeout(() => controller.abort(), 10)
+
+      await expect(pending).rejects.toThrow(CancelledError)
+      expect(Date.now() - started).toBeLessThan(1000)
+      expect(fetchMock.mock.calls[0]![1].signal.aborted).toBe(true)
+    })
+
+    it('should not send a request when already cancelled', async () => {
+      const fetchMock = vi.fn()
+      ;(global as any).fetch = fetchMock
+      const controller = new AbortController()
+      controller.abort()
+
+      const request = {
+        model: 'test',
+        messages: [{ role: 'user' as const, content: 'test' }],
+      }
+
+      await expect(
+        provider.complete(request, controller.signal)
+      ).rejects.toThrow(CancelledError)
+      expect(fetchMock).not.toHaveBeenCalled()
+    })
+
+    it('should report cancellation with the interrupt exit code', () => {
+      const error = new CancelledError()
+      expect(error.code).toBe(130)
+      expect(error.category).toBe('interrupt')
+    })
+  })
+
+  describe('Mock Provider', () => {
+    let provider: MockProvider
+
+    beforeEach(() => {
+      provider = new MockProvider()
     })
 
     it('should always be available', () => {
@@ -497,9 +1258,11 @@ describe('AI Module', () => {
       expect(commands).toEqual(['echo "mock command"'])
     })
 
-    it('should throw AIError when API key is missing', async () => {
+    it('should throw ConfigError when API key is missing', async () => {
       // Ensure MOCK_AI is not set
       delete process.env.MOCK_AI
+      const originalKey = process.env.OPENROUTER_API_KEY
+      delete process.env.OPENROUTER_API_KEY
 
       // Config with no API key configured
       const noKeyConfig: Config = {
@@ -507,9 +1270,94 @@ describe('AI Module', () => {
         providers: {},
       }
 
-      await expect(
-        generateCommands(mockContext, 'list files', noKeyConfig)
-      ).rejects.toThrow(AIError)
+      try {
+        await expect(
+          generateCommands(mockContext, 'list files', noKeyConfig)
+        ).rejects.toThrow(ConfigError)
+      } finally {
+        if (originalKey !== undefined) {
+          process.env.OPENROUTER_API_KEY = originalKey
+        }
+      }
+    })
+
+    it('should list every configured provider when the primary is unusable', async () => {
+      delete process.env.MOCK_AI
+      const originalKey = process.env.OPENROUTER_API_KEY
+      delete process.env.OPENROUTER_API_KEY
+
+      const config: Config = {
+        ...mockConfig,
+        provider: { ...mockConfig.provider, fallback: ['acme'] },
+        providers: {},
+      }
+
+      try {
+        expect(checkProviders(config)).toEqual([
+          {
+            name: 'openrouter',
+            problem:
+              'no API key (set OPENROUTER_API_KEY or openrouter.api-key in .clai.toml)',
+          },
+          {
+            name: 'acme',
+            problem: 'unknown provider (supported: openrouter)',
+          },
+        ])
+
+        const error = await generateCommands(
+          mockContext,
+          'list files',
+          config
+        ).catch((err: unknown) => err)
+        expect(error).toBeInstanceOf(ConfigError)
+        expect((error as Error).message).toBe(
+          "AI provider 'openrouter' is not usable. Configured providers:\n" +
+            '  openrouter: no API key (set OPENROUTER_API_KEY or openrouter.api-key in .clai.toml)\n' +
+            '  acme: unknown provider (supported: openrouter)'
+        )
+      } finally {
+        if (originalKey !== undefined) {
+          process.env.OPENROUTER_API_KEY = originalKey
+        }
+      }
+    })
+
+    it('should report a usable fallback as ok', () => {
+      delete process.env.MOCK_AI
+
+      const config: Config = {
+        ...mockConfig,
+        provider: {
+          ...mockConfig.provider,
+          default: 'acme',
+          fallback: ['openrouter'],
+        },
+        providers: { openrouter: { apiKey: 'sk-test' } },
+      }
+
+      expect(checkProviders(config)).toEqual([
+        { name: 'acme', problem: 'unknown provider (supported: openrouter)' },
+        { name: 'openrouter' },
+      ])
+    })
+
+    it('should describe the provider and model a hinted --model resolves to', () => {
+      const config: Config = {
+        ...mockConfig,
+        model: 'local/llama3',
+        modelFallback: [],
+        provider: {
+          ...mockConfig.provider,
+          fallback: ['openrouter'],
+          modelProviderHints: [{ prefix: 'local/', provider: 'ollama' }],
+        },
+        providers: { openrouter: { model: 'qwen/qwen3-coder' } },
+      }
+
+      expect(formatModelInfo(config)).toBe(
+        'Provider: ollama\nModel: local/llama3\nFallback: openrouter local/llama3\n'
+      )
     })
 
     it('should use provided API key', async () => {
@@ -568,6 +1416,787 @@ describe('AI Module', () => {
       expect(commands[1]).toContain('mock command 2')
       expect(commands[2]).toContain('mock command 3')
     })
+
+    it('should return the raw response alongside the commands', async () => {
+      process.env.MOCK_AI = '1'
+
+      const multiConfig: Config = {
+        ...mockConfig,
+        ui: { ...mockConfig.ui, numOptions: 2 },
+      }
+
+      const { commands, raw } = await generateCommandsWithRaw(
+        mockContext,
+        'list files',
+        multiConfig
+      )
+
+      expect(commands).toHaveLength(2)
+      expect(JSON.parse(raw)).toEqual({ commands })
+    })
+
+    it('should report provider, model and token usage', async () => {
+      process.env.MOCK_AI = '1'
+
+      const result = await generateCommandsWithRaw(
+        mockContext,
+        'list files',
+        mockConfig
+      )
+
+      expect(result.provider).toBe('mock')
+      expect(result.model).toBe('mock')
+      expect(result.totalTokens).toBe(60)
+    })
+  })
+
+  describe('Provider Capabilities', () => {
+    // Records requests and answers according to the request shape
+    class CapabilityProvider implements AIProvider {
+      name = 'capability-mock'
+      requests: ChatRequest[] = []
+
+      constructor(private caps?: ProviderCapabilities) {}
+
+      isAvailable(): boolean {
+        return true
+      }
+
+      capabilities(): ProviderCapabilities {
+        return this.caps ?? DEFAULT_CAPABILITIES
+      }
+
+      async complete(request: ChatRequest): Promise<ChatResponse> {
+        this.requests.push(request)
+        if (request.n) {
+          const choices = Array.from(
+            { length: request.n },
+            (_, i) => `echo native ${i + 1}`
+          )
+          return { content: choices[0]!, choices }
+        }
+        return {
+          content: JSON.stringify({ commands: ['echo a', 'echo b', 'echo c'] }),
+        }
+      }
+    }
+
+    const multiConfig: Config = {
+      ...mockConfig,
+      ui: { ...mockConfig.ui, numOptions: 3 },
+    }
+
+    it('should report conservative defaults when not implemented', () => {
+      const provider = new MockProvider()
+      expect(getCapabilities(provider)).toEqual(DEFAULT_CAPABILITIES)
+    })
+
+    it('should report OpenRouter capabilities', () => {
+      const caps = getCapabilities(new OpenRouterProvider('key'))
+      expect(caps.supportsN).toBe(false)
+      expect(caps.supportsStreaming).toBe(false)
+    })
+
+    it('should use native n when the provider supports it', async () => {
+      const provider = new CapabilityProvider({
+        ...DEFAULT_CAPABILITIES,
+        supportsN: true,
+      })
+
+      const commands = await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        multiConfig
+      )
+
+      expect(provider.requests[0]?.n).toBe(3)
+      expect(provider.requests[0]?.messages[0]?.content).not.toContain('JSON')
+      expect(commands).toEqual([
+        'echo native 1',
+        'echo native 2',
+        'echo native 3',
+      ])
+    })
+
+    it('should fall back to the JSON prompt without n support', async () => {
+      const provider = new CapabilityProvider()
+
+      const commands = await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        multiConfig
+      )
+
+      expect(provider.requests[0]?.n).toBeUndefined()
+      expect(provider.requests[0]?.jsonMode).toBeUndefined()
+      expect(provider.requests[0]?.messages[0]?.content).toContain('JSON')
+      expect(commands).toEqual(['echo a', 'echo b', 'echo c'])
+    })
+
+    it('should request JSON mode when supported', async () => {
+      const provider = new CapabilityProvider({
+        ...DEFAULT_CAPABILITIES,
+        supportsJsonMode: true,
+      })
+
+      await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        multiConfig
+      )
+
+      expect(provider.requests[0]?.jsonMode).toBe(true)
+    })
+
+    it('should pass reasoning effort only to providers that support it', async () => {
+      const config: Config = {
+        ...mockConfig,
+        provider: { ...mockConfig.provider, reasoningEffort: 'low' },
+      }
+      const supported = new CapabilityProvider({
+        ...DEFAULT_CAPABILITIES,
+        supportsReasoningEffort: true,
+      })
+      const unsupported = new CapabilityProvider()
+
+      await generateCommandsWithProvider(
+        supported,
+        mockContext,
+        'list files',
+        config
+      )
+      await generateCommandsWithProvider(
+        unsupported,
+        mockContext,
+        'list files',
+        config
+      )
+      await generateCommandsWithProvider(
+        supported,
+        mockContext,
+        'list files',
+        mockConfig
+      )
+
+      expect(supported.requests[0]?.reasoningEffort).toBe('low')
+      expect(unsupported.requests[0]?.reasoningEffort).toBeUndefined()
+      expect(supported.requests[1]?.reasoningEffort).toBeUndefined()
+    })
+
+    it('should not use n for a single option', async () => {
+      const provider = new CapabilityProvider({
+        ...DEFAULT_CAPABILITIES,
+        supportsN: true,
+      })
+
+      await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        mockConfig
+      )
+
+      expect(provider.requests[0]?.n).toBeUndefined()
+    })
+  })
+
+  describe('JSON Retry', () => {
+    // Answers with prose first, then with the JSON list
+    class ProseThenJsonProvider implements AIProvider {
+      name = 'prose-then-json'
+      requests: ChatRequest[] = []
+
+      isAvailable(): boolean {
+        return true
+      }
+
+      async complete(request: ChatRequest): Promise<ChatResponse> {
+        this.requests.push({ ...request, messages: [...request.messages] })
+        if (this.requests.length === 1) {
+          return { content: 'Sure! You could use ls or find to list files.' }
+        }
+        return { content: '{"commands": ["ls", "ls -la", "find ."]}' }
+      }
+    }
+
+    const retryConfig: Config = {
+      ...mockConfig,
+      provider: { ...mockConfig.provider, jsonRetries: 1 },
+      ui: { ...mockConfig.ui, numOptions: 3 },
+    }
+
+    it('should retry once with a stricter prompt on malformed JSON', async () => {
+      const provider = new ProseThenJsonProvider()
+
+      const commands = await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        retryConfig
+      )
+
+      expect(commands).toEqual(['ls', 'ls -la', 'find .'])
+      expect(provider.requests).toHaveLength(2)
+      const retryMessages = provider.requests[1]!.messages
+      expect(retryMessages.at(-2)?.role).toBe('assistant')
+      expect(retryMessages.at(-1)?.content).toContain('ONLY with JSON')
+    })
+
+    it('should not retry when retries are disabled', async () => {
+      const provider = new ProseThenJsonProvider()
+
+      const commands = await generateCommandsWithProvider(
+        provider,
+        mockContext,
+        'list files',
+        { ...retryConfig, provider: { ...retryConfig.provider, jsonRetries: 0 } }
+      )
+
+      expect(provider.requests).toHaveLength(1)
+      expect(commands).toHaveLength(1)
+    })
+
+    it('should not retry single-command requests', async () => {
+      const provider = new ProseThenJsonProvider()
+
+      await generateCommandsWithProvider(provider, mockContext, 'list files', {
+        ...retryConfig,
+        ui: { ...retryConfig.ui, numOptions: 1 },
+      })
+
+      expect(provider.requests).toHaveLength(1)
+    })
+  })
+
+  describe('Provider Chain', () => {
+    // Answers with a fixed command, or fails when constructed without one
+    class StubProvider implements AIProvider {
+      requests: ChatRequest[] = []
+
+      constructor(
+        public name: string,
+        private command?: string
+      ) {}
+
+      isAvailable(): boolean {
+        return true
+      }
+
+      async complete(request: ChatRequest): Promise<ChatResponse> {
+        this.requests.push(request)
+        if (!this.command) {
+          throw new AIError(`${this.name} unavailable`, 503)
+        }
+        return { content: this.command }
+      }
+    }
+
+    const request: ChatRequest = {
+      model: 'ignored',
+      messages: [{ role: 'user', content: 'test' }],
+    }
+
+    it('should report the provider that answered', async () => {
+      const chain = new ProviderChain([
+        { provider: new StubProvider('primary'), model: 'model-a' },
+        { provider: new StubProvider('backup', 'ls'), model: 'model-b' },
+      ])
+
+      const response = await chain.complete(request)
+
+      expect(response.content).toBe('ls')
+      expect(response.provider).toBe('backup')
+    })
+
+    it('should space out rapid requests by min-interval-ms', async () => {
+      const starts: number[] = []
+      const provider = new StubProvider('primary', 'ls')
+      provider.complete = async () => {
+        starts.push(Date.now())
+        return { content: 'ls' }
+      }
+      const throttle = new RequestThrottle()
+
+      // A fresh chain per call, as in --batch
+      for (let i = 0; i < 4; i++) {
+        const chain = new ProviderChain([{ provider, model: 'a' }], {
+          minIntervalMs: 50,
+          throttle,
+        })
+        await chain.complete(request)
+      }
+
+      expect(starts).toHaveLength(4)
+      for (let i = 1; i < starts.length; i++) {
+        // Timers may fire a millisecond or two early
+        expect(starts[i]! - starts[i - 1]!).toBeGreaterThanOrEqual(48)
+      }
+    })
+
+    it('should not wait without min-interval-ms', async () => {
+      const throttle = new RequestThrottle()
+      const chain = new ProviderChain(
+        [{ provider: new StubProvider('primary', 'ls'), model: 'a' }],
+        { throttle }
+      )
+
+      const started = Date.now()
+      for (let i = 0; i < 5; i++) {
+        await chain.complete(request)
+      }
+      expect(Date.now() - started).toBeLessThan(50)
+    })
+
+    it('should stop waiting for the throttle when cancelled', async () => {
+      const throttle = new RequestThrottle()
+      const provider = new StubProvider('primary', 'ls')
+      const chain = new ProviderChain([{ provider, model: 'a' }], {
+        minIntervalMs: 10000,
+        throttle,
+      })
+      await chain.complete(request)
+
+      const controller = new AbortController()
+      const pending = chain.complete(request, controller.signal)
+      controller.abort()
+
+      await expect(pending).rejects.toBeInstanceOf(CancelledError)
+      expect(provider.requests).toHaveLength(1)
+    })
+
+    it('should send each provider its own model', async () => {
+      const primary = new StubProvider('primary')
+      const backup = new StubProvider('backup', 'ls')
+      const chain = new ProviderChain([
+        { provider: primary, model: 'model-a' },
+        { provider: backup, model: 'model-b' },
+      ])
+
+      await chain.complete(request)
+
+      expect(primary.requests[0]?.model).toBe('model-a')
+      expect(backup.requests[0]?.model).toBe('model-b')
+    })
+
+    it('should rethrow the last error when every provider fails', async () => {
+      const chain = new ProviderChain([
+        { provider: new StubProvider('primary'), model: 'a' },
+        { provider: new StubProvider('backup'), model: 'b' },
+      ])
+
+      await expect(chain.complete(request)).rejects.toThrow(
+        'backup unavailable'
+      )
+    })
+
+    it('should list each provider when none could be reached', async () => {
+      class UnreachableProvider extends StubProvider {
+        async complete(): Promise<ChatResponse> {
+          throw new AIConnectionError(`Network error: ${this.name} refused`)
+        }
+      }
+      const chain = new ProviderChain([
+        { provider: new UnreachableProvider('primary'), model: 'a' },
+        { provider: new UnreachableProvider('backup'), model: 'b' },
+      ])
+
+      const error = await chain.complete(request).catch((err: unknown) => err)
+
+      expect(error).toBeInstanceOf(AIConnectionError)
+      expect((error as AIError).reason).toBe('unreachable')
+      expect((error as Error).message).toBe(
+        'No AI provider could be reached:\n' +
+          '  primary: Network error: primary refused\n' +
+          '  backup: Network error: backup refused'
+      )
+    })
+
+    it('should report progress once per attempted provider in order', async () => {
+      const onAttempt = vi.fn()
+      const chain = new ProviderChain(
+        [
+          { provider: new StubProvider('primary'), model: 'a' },
+          { provider: new StubProvider('backup', 'ls'), model: 'b' },
+          { provider: new StubProvider('unused', 'pwd'), model: 'c' },
+        ],
+        { onAttempt }
+      )
+
+      await chain.complete(request)
+
+      expect(onAttempt.mock.calls).toEqual([
+        ['primary', 1, 3],
+        ['backup', 2, 3],
+      ])
+    })
+
+    it('should let a per-call progress callback override the default', async () => {
+      const fromOptions = vi.fn()
+      const perCall = vi.fn()
+      const chain = new ProviderChain(
+        [{ provider: new StubProvider('primary', 'ls'), model: 'a' }],
+        { onAttempt: fromOptions }
+      )
+
+      await chain.complete(request, undefined, perCall)
+
+      expect(perCall).toHaveBeenCalledWith('primary', 1, 1)
+      expect(fromOptions).not.toHaveBeenCalled()
+    })
+
+    it('should try the fallback after an auth error by default', async () => {
+      const primary = new StubProvider('primary')
+      primary.complete = async () => {
+        throw new AIError('Authentication error (401): bad key', 401)
+      }
+      const chain = new ProviderChain([
+        { provider: primary, model: 'a' },
+        { provider: new StubProvider('backup', 'ls'), model: 'b' },
+      ])
+
+      const response = await chain.complete(request)
+      expect(response.provider).toBe('backup')
+    })
+
+    it('should stop on an auth error when stopOnAuthError is set', async () => {
+      const primary = new StubProvider('primary')
+      primary.complete = async () => {
+        throw new AIError('Authentication error (401): bad key', 401)
+      }
+      const backup = new StubProvider('backup', 'ls')
+      const chain = new ProviderChain(
+        [
+          { provider: primary, model: 'a' },
+          { provider: backup, model: 'b' },
+        ],
+        { stopOnAuthError: true }
+      )
+
+      await expect(chain.complete(request)).rejects.toThrow(
+        'Authentication error (401)'
+      )
+      expect(backup.requests).toHaveLength(0)
+    })
+
+    it('should still fall back on non-auth errors when stopOnAuthError is set', async () => {
+      const chain = new ProviderChain(
+        [
+          { provider: new StubProvider('primary'), model: 'a' },
+          { provider: new StubProvider('backup', 'ls'), model: 'b' },
+        ],
+        { stopOnAuthError: true }
+      )
+
+      const response = await chain.complete(request)
+      expect(response.provider).toBe('backup')
+    })
+
+    it('should not fall back after a cancellation', async () => {
+      const primary = new StubProvider('primary')
+      primary.complete = async () => {
+        throw new CancelledError()
+      }
+      const backup = new StubProvider('backup', 'ls')
+      const chain = new ProviderChain([
+        { provider: primary, model: 'a' },
+        { provider: backup, model: 'b' },
+      ])
+
+      await expect(chain.complete(request)).rejects.toThrow(CancelledError)
+      expect(backup.requests).toHaveLength(0)
+    })
+
+    it('should pass the cancellation signal to each provider', async () => {
+      const controller = new AbortController()
+      const seen: (AbortSignal | undefined)[] = []
+      const primary = new StubProvider('primary', 'ls')
+      primary.complete = async (_request, signal) => {
+        seen.push(signal)
+        return { content: 'ls' }
+      }
+      const chain = new ProviderChain([{ provider: primary, model: 'a' }])
+
+      await chain.complete(request, controller.signal)
+
+      expect(seen).toEqual([controller.signal])
+    })
+
+    it('should log the provider/model used and the winning provider', async () => {
+      const logger = { info: vi.fn() }
+      const chain = new ProviderChain(
+        [
+          { provider: new StubProvider('primary'), model: 'model-a' },
+          { provider: new StubProvider('backup', 'ls'), model: 'model-b' },
+        ],
+        { logger }
+      )
+
+      await generateCommandsWithProvider(
+        chain,
+        mockContext,
+        'list files',
+        mockConfig,
+        logger
+      )
+
+      const messages = logger.info.mock.calls.map((call) => call[0])
+      expect(messages).toContain(
+        "Using provider 'primary' model 'qwen/qwen3-coder'"
+      )
+      expect(messages).toContain("Falling back to provider 'backup' model 'model-b'")
+      expect(messages).toContain("Response from provider 'backup'")
+    })
+  })
+
+  describe('Model Fallback', () => {
+    // Rejects every model except the one it serves
+    class SingleModelProvider implements AIProvider {
+      name = 'single'
+      models: string[] = []
+
+      constructor(
+        private served: string,
+        private status = 404
+      ) {}
+
+      isAvailable(): boolean {
+        return true
+      }
+
+      async complete(request: ChatRequest): Promise<ChatResponse> {
+        this.models.push(request.model)
+        if (request.model !== this.served) {
+          throw new AIError(
+            `API error (${this.status}): no such model ${request.model}`,
+            this.status
+          )
+        }
+        return { content: 'ls -la' }
+      }
+    }
+
+    it('should retry the same provider with the next model', async () => {
+      const provider = new SingleModelProvider('smart-model')
+      const chain = new ProviderChain([
+        {
+          provider,
+          model: 'cheap-model',
+          fallbackModels: ['smart-model'],
+        },
+      ])
+
+      const commands = await generateCommandsWithProvider(
+        chain,
+        mockContext,
+        'list files',
+        mockConfig
+      )
+
+      expect(commands).toEqual(['ls -la'])
+      expect(provider.models).toEqual(['cheap-model', 'smart-model'])
+    })
+
+    it('should not try other models after a non-model error', async () => {
+      const provider = new SingleModelProvider('smart-model', 500)
+      const chain = new ProviderChain([
+        {
+          provider,
+          model: 'cheap-model',
+          fallbackModels: ['smart-model'],
+        },
+      ])
+
+      await expect(chain.complete(request())).rejects.toThrow('API error (500)')
+      expect(provider.models).toEqual(['cheap-model'])
+    })
+
+    function request(): ChatRequest {
+      return { model: 'ignored', messages: [{ role: 'user', content: 'x' }] }
+    }
+  })
+
+  describe('Explain Danger', () => {
+    let originalMockAI: string | undefined
+
+    beforeEach(() => {
+      originalMockAI = process.env.MOCK_AI
+    })
+
+    afterEach(() => {
+      if (originalMockAI !== undefined) {
+        process.env.MOCK_AI = originalMockAI
+      } else {
+        delete process.env.MOCK_AI
+      }
+      vi.restoreAllMocks()
+    })
+
+    it('should build explain prompt containing the command', () => {
+      const messages = buildExplainDangerPrompt('rm -rf /tmp/build')
+
+      expect(messages).toHaveLength(2)
+      expect(messages[0].role).toBe('system')
+      expect(messages[0].content).toContain('ONE short sentence')
+      expect(messages[1].content).toContain('rm -rf /tmp/build')
+    })
+
+    it('should request a low max_tokens explanation', async () => {
+      delete process.env.MOCK_AI
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({
+            choices: [{ message: { content: 'Deletes the build dir.' } }],
+          }),
+      })
+
+      const config: Config = {
+        ...mockConfig,
+        providers: { openrouter: { apiKey: 'sk-test' } },
+      }
+
+      const explanation = await explainDanger('rm -rf build', config)
+
+      expect(explanation).toBe('Deletes the build dir.')
+      const body = JSON.parse((fetch as any).mock.calls[0][1].body)
+      expect(body.max_tokens).toBeLessThanOrEqual(100)
+      expect(body.messages[1].content).toContain('rm -rf build')
+    })
+
+    it('should fall back to the next model like command generation', async () => {
+      delete process.env.MOCK_AI
+      ;(global as any).fetch = vi
+        .fn()
+        .mockResolvedValueOnce({
+          ok: false,
+          status: 404,
+          text: () => Promise.resolve('model not found'),
+        })
+        .mockResolvedValueOnce({
+          ok: true,
+          json: () =>
+            Promise.resolve({
+              choices: [{ message: { content: 'Deletes the build dir.' } }],
+            }),
+        })
+
+      const config: Config = {
+        ...mockConfig,
+        modelFallback: ['backup-model'],
+        providers: { openrouter: { apiKey: 'sk-test' } },
+      }
+
+      const explanation = await explainDanger('rm -rf build', config)
+
+      expect(explanation).toBe('Deletes the build dir.')
+      const body = JSON.parse((fetch as any).mock.calls[1][1].body)
+      expect(body.model).toBe('backup-model')
+    })
+
+    it('should fail open when the explanation call errors', async () => {
+      delete process.env.MOCK_AI
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: false,
+        status: 500,
+        text: () => Promise.resolve('boom'),
+      })
+
+      const config: Config = {
+        ...mockConfig,
+        providers: { openrouter: { apiKey: 'sk-test' } },
+      }
+
+      await expect(explainDanger('rm -rf /', config)).resolves.toBeNull()
+    })
+
+    it('should fail open when no provider is configured', async () => {
+      delete process.env.MOCK_AI
+      const originalKey = process.env.OPENROUTER_API_KEY
+      delete process.env.OPENROUTER_API_KEY
+
+      try {
+        await expect(
+          explainDanger('rm -rf /', { ...mockConfig, providers: {} })
+        ).resolves.toBeNull()
+      } finally {
+        if (originalKey !== undefined) {
+          process.env.OPENROUTER_API_KEY = originalKey
+        }
+      }
+    })
+  })
+
+  describe('Explain Command', () => {
+    let originalMockAI: string | undefined
+
+    beforeEach(() => {
+      originalMockAI = process.env.MOCK_AI
+    })
+
+    afterEach(() => {
+      if (originalMockAI !== undefined) {
+        process.env.MOCK_AI = originalMockAI
+      } else {
+        delete process.env.MOCK_AI
+      }
+      vi.restoreAllMocks()
+    })
+
+    it('should build the explain prompt from the given command', () => {
+      const messages = buildExplainCommandPrompt('tar xzvf file.tar.gz')
+
+      expect(messages).toHaveLength(2)
+      expect(messages[0].role).toBe('system')
+      expect(messages[0].content).toContain('Explain what the given command')
+      expect(messages[1].content).toBe(
+        'Explain this command: tar xzvf file.tar.gz'
+      )
+    })
+
+    it('should return the trimmed explanation without reasoning tags', async () => {
+      delete process.env.MOCK_AI
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({
+            choices: [
+              {
+                message: {
+                  content: '<think>tar flags</think>\nExtracts file.tar.gz.\n',
+                },
+              },
+            ],
+          }),
+      })
+
+      const explanation = await explainCommand('tar xzvf file.tar.gz', {
+        ...mockConfig,
+        providers: { openrouter: { apiKey: 'sk-test' } },
+      })
+
+      expect(explanation).toBe('Extracts file.tar.gz.')
+      const body = JSON.parse((fetch as any).mock.calls[0][1].body)
+      expect(body.messages[1].content).toContain('tar xzvf file.tar.gz')
+    })
+
+    it('should throw on an empty explanation', async () => {
+      delete process.env.MOCK_AI
+      ;(global as any).fetch = vi.fn().mockResolvedValue({
+        ok: true,
+        json: () =>
+          Promise.resolve({ choices: [{ message: { content: '  ' } }] }),
+      })
+
+      await expect(
+        explainCommand('ls', {
+          ...mockConfig,
+          providers: { openrouter: { apiKey: 'sk-test' } },
+        })
+      ).rejects.toThrow(AIError)
+    })
   })
 
   describe('AIError', () => {
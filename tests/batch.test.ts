@@ -0,0 +1,230 @@
+import { describe, it, expect } from 'vitest'
+import {
+  runBatch,
+  parseBatchInput,
+  formatBatchLine,
+  finishBatchCommand,
+} from '../src/batch/index.js'
+import {
+  generateCommandsWithProvider,
+  ProviderChain,
+  RequestThrottle,
+  type AIProvider,
+  type ChatRequest,
+  type ChatResponse,
+} from '../src/ai/index.js'
+import type { ContextData } from '../src/context/types.js'
+import type { Config } from '../src/config/types.js'
+
+describe('Batch mode', () => {
+  const context: ContextData = {
+    system: {
+      osName: 'Linux',
+      osVersion: '6.1.0',
+      architecture: 'x64',
+      shell: 'bash',
+      user: 'testuser',
+      totalMemoryMb: 8192,
+    },
+    cwd: '/tmp',
+    files: [],
+    history: [],
+  }
+
+  const config = {
+    provider: { default: 'openrouter', fallback: [], jsonRetries: 0 },
+    ui: { numOptions: 1 },
+    prompt: { instructionPrefix: '', instructionSuffix: '' },
+    providers: {},
+  } as unknown as Config
+
+  // Echoes the instruction back as a command; slower for earlier lines
+  class EchoProvider implements AIProvider {
+    name = 'echo'
+
+    isAvailable(): boolean {
+      return true
+    }
+
+    async complete(request: ChatRequest): Promise<ChatResponse> {
+      const content = request.messages[1]?.content ?? ''
+      const instruction = content.match(/User Instruction: (.*)/)?.[1] ?? ''
+      if (instruction === 'fail') {
+        throw new Error('provider exploded')
+      }
+      const delay = instruction === 'first' ? 20 : 0
+      await new Promise((resolve) => setTimeout(resolve, delay))
+      return { content: `echo ${instruction}` }
+    }
+  }
+
+  async function generate(instruction: string): Promise<string> {
+    const [command] = await generateCommandsWithProvider(
+      new EchoProvider(),
+      context,
+      instruction,
+      config
+    )
+    return command ?? ''
+  }
+
+  it('should split input into non-empty trimmed lines', () => {
+    expect(parseBatchInput('first\n\n  second  \r\nthird\n')).toEqual([
+      'first',
+      'second',
+      'third',
+    ])
+  })
+
+  it('should produce one output line per instruction in order', async () => {
+    const instructions = parseBatchInput('first\nsecond\nthird\n')
+
+    const results = await runBatch(instructions, generate, 3)
+    const lines = results.map(formatBatchLine)
+
+    expect(lines).toEqual(['echo first', 'echo second', 'echo third'])
+  })
+
+  it('should emit an error marker without aborting the batch', async () => {
+    const results = await runBatch(['first', 'fail', 'third'], generate)
+    const lines = results.map(formatBatchLine)
+
+    expect(lines).toEqual([
+      'echo first',
+      '# error: provider exploded',
+      'echo third',
+    ])
+  })
+
+  it('should block dangerous lines with --only-if-safe', async () => {
+    const provider: AIProvider = {
+      name: 'wiper',
+      isAvailable: () => true,
+      complete: async () => ({ content: 'rm -rf /' }),
+    }
+    const safeConfig = {
+      ...config,
+      onlyIfSafe: true,
+      safety: {
+        dangerousPatterns: [],
+        disablePatterns: [],
+        extraPatterns: [],
+      },
+    } as unknown as Config
+
+    const results = await runBatch(['wipe everything'], async (instr) => {
+      const [command] = await generateCommandsWithProvider(
+        provider,
+        context,
+        instr,
+        safeConfig
+      )
+      return finishBatchCommand(command ?? '', safeConfig)
+    })
+    const lines = results.map(formatBatchLine)
+
+    expect(results[0]).toMatchObject({ blocked: true })
+    expect(lines).toHaveLength(1)
+    // Only a comment is printed, never the command itself
+    expect(lines[0]).toMatch(/^# error: .*--only-if-safe is set$/)
+  })
+
+  it('should wrap each line in the command template', async () => {
+    const templated = {
+      ...config,
+      postprocess: { commandTemplate: 'time {command}' },
+    } as unknown as Config
+
+    const results = await runBatch(['first', 'second'], async (instr) =>
+      finishBatchCommand(await generate(instr), templated)
+    )
+
+    expect(results.map(formatBatchLine)).toEqual([
+      'time echo first',
+      'time echo second',
+    ])
+  })
+
+  it('should never run more than the concurrency limit at once', async () => {
+    // Counts requests between entering and leaving complete()
+    class CountingProvider implements AIProvider {
+      name = 'counting'
+      inFlight = 0
+      maxInFlight = 0
+
+      isAvailable(): boolean {
+        return true
+      }
+
+      async complete(): Promise<ChatResponse> {
+        this.inFlight++
+        this.maxInFlight = Math.max(this.maxInFlight, this.inFlight)
+        await new Promise((resolve) => setTimeout(resolve, 5))
+        this.inFlight--
+        return { content: 'ls' }
+      }
+    }
+    const provider = new CountingProvider()
+    let active = 0
+    let maxActive = 0
+
+    const results = await runBatch(
+      ['a', 'b', 'c', 'd', 'e'],
+      async (instruction) => {
+        active++
+        maxActive = Math.max(maxActive, active)
+        const [command] = await generateCommandsWithProvider(
+          provider,
+          context,
+          instruction,
+          config
+        )
+        active--
+        return command ?? ''
+      },
+      2
+    )
+
+    expect(results).toHaveLength(5)
+    expect(maxActive).toBe(2)
+    expect(provider.maxInFlight).toBe(2)
+    expect(provider.inFlight).toBe(0)
+  })
+
+  it('should still space out request starts with min-interval-ms', async () => {
+    const starts: number[] = []
+    const provider: AIProvider = {
+      name: 'timed',
+      isAvailable: () => true,
+      complete: async () => {
+        starts.push(Date.now())
+        return { content: 'ls' }
+      },
+    }
+    const throttle = new RequestThrottle()
+
+    await runBatch(
+      ['a', 'b', 'c', 'd'],
+      async () => {
+        const chain = new ProviderChain([{ provider, model: 'm' }], {
+          minIntervalMs: 30,
+          throttle,
+        })
+        return (await chain.complete({ model: 'm', messages: [] })).content
+      },
+      4
+    )
+
+    expect(starts).toHaveLength(4)
+    for (let i = 1; i < starts.length; i++) {
+      // Timers may fire a millisecond or two early
+      expect(starts[i]! - starts[i - 1]!).toBeGreaterThanOrEqual(28)
+    }
+  })
+
+  it('should keep multi-line commands on one output line', () => {
+    expect(
+      formatBatchLine({ instruction: 'x', command: 'cd /tmp\nls' })
+    ).toBe('cd /tmp; ls')
+  })
+})
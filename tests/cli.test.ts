@@ -25,6 +25,13 @@ describe('CLI Parser', () => {
     it('parses positional instruction', () => {
       const cli = parse(['find all rust files'])
       expect(cli.instruction).toBe('find all rust files')
+      expect(cli.inlineContext).toBeUndefined()
+    })
+
+    it('joins positionals after the instruction into inline context', () => {
+      const cli = parse(['list containers', 'we use', 'podman'])
+      expect(cli.instruction).toBe('list containers')
+      expect(cli.inlineContext).toBe('we use podman')
     })
 
     it('throws UsageError with code 2 when instruction is missing', () => {
@@ -43,7 +50,130 @@ describe('CLI Parser', () => {
     })
   })
 
+  describe('standalone actions', () => {
+    it('does not require an instruction with --fix-permissions', () => {
+      const cli = parse(['--fix-permissions'])
+      expect(cli.fixPermissions).toBe(true)
+      expect(cli.instruction).toBe('')
+    })
+
+    it('does not require an instruction with --validate-config', () => {
+      const cli = parse(['--validate-config'])
+      expect(cli.validateConfig).toBe(true)
+    })
+
+    it('does not require an instruction with --model-info', () => {
+      const cli = parse(['--model-info', '--model', 'local/llama3'])
+      expect(cli.modelInfo).toBe(true)
+      expect(cli.model).toBe('local/llama3')
+      expect(cli.instruction).toBe('')
+    })
+
+    it('does not require an instruction with --print-config', () => {
+      const cli = parse(['--print-config'])
+      expect(cli.printConfig).toBe(true)
+    })
+
+    it('does not require an instruction with --set', () => {
+      const cli = parse([
+        '--set',
+        'provider.default=openrouter',
+        '--set',
+        'ui.num-options=3',
+      ])
+      expect(cli.set).toEqual(['provider.default=openrouter', 'ui.num-options=3'])
+      expect(cli.instruction).toBe('')
+    })
+
+    it('does not require an instruction with --version-json', () => {
+      const cli = parse(['--version-json'])
+      expect(cli.versionJson).toBe(true)
+      expect(cli.instruction).toBe('')
+    })
+
+    it('uses the positional as the command with --test-pattern', () => {
+      const cli = parse(['--test-pattern', 'rm -rf /tmp/x'])
+      expect(cli.testPattern).toBe(true)
+      expect(cli.instruction).toBe('rm -rf /tmp/x')
+    })
+
+    it('does not require an instruction with --batch', () => {
+      const cli = parse(['--batch'])
+      expect(cli.batch).toBe(true)
+    })
+
+    it('parses --concurrency for --batch', () => {
+      expect(parse(['--batch']).concurrency).toBe(4)
+      expect(parse(['--batch', '--concurrency', '2']).concurrency).toBe(2)
+    })
+
+    it('rejects a --concurrency below 1', () => {
+      expect(() => parse(['--batch', '--concurrency', '0'])).toThrow()
+      expect(() => parse(['--batch', '--concurrency', 'many'])).toThrow()
+    })
+
+    it('defaults fixPermissions to false', () => {
+      const cli = parse(['test'])
+      expect(cli.fixPermissions).toBe(false)
+    })
+  })
+
+  describe('config subcommand', () => {
+    it('parses config actions without an instruction', () => {
+      for (const action of ['list', 'validate', 'init', 'path']) {
+        const cli = parse(['config', action])
+        expect(cli.configCommand).toEqual({ action, args: [] })
+        expect(cli.instruction).toBe('')
+      }
+    })
+
+    it('passes the key to config get', () => {
+      const cli = parse(['config', 'get', 'ui.color'])
+      expect(cli.configCommand).toEqual({ action: 'get', args: ['ui.color'] })
+    })
+
+    it('passes every assignment to config set', () => {
+      const cli = parse([
+        'config',
+        'set',
+        'provider.default=openrouter',
+        'ui.num-options=3',
+      ])
+      expect(cli.configCommand).toEqual({
+        action: 'set',
+        args: ['provider.default=openrouter', 'ui.num-options=3'],
+      })
+    })
+
+    it('keeps global options given before the subcommand', () => {
+      const cli = parse(['--format', 'json', 'config', 'list'])
+      expect(cli.configCommand?.action).toBe('list')
+      expect(cli.format).toBe('json')
+    })
+
+    it('still treats a plain positional as the instruction', () => {
+      const cli = parse(['do thing'])
+      expect(cli.configCommand).toBeUndefined()
+      expect(cli.instruction).toBe('do thing')
+    })
+
+    it('does not reserve config inside an instruction', () => {
+      const cli = parse(['config nginx as a reverse proxy'])
+      expect(cli.configCommand).toBeUndefined()
+      expect(cli.instruction).toBe('config nginx as a reverse proxy')
+    })
+  })
+
   describe('model and provider options', () => {
+    it('parses --model-fallback as a comma-separated list', () => {
+      const cli = parse(['--model-fallback', 'a/cheap, b/smart', 'test'])
+      expect(cli.modelFallback).toEqual(['a/cheap', 'b/smart'])
+    })
+
+    it('defaults modelFallback to an empty list', () => {
+      expect(parse(['test']).modelFallback).toEqual([])
+    })
+
     it('parses --model with -m shorthand', () => {
       const cli = parse(['-m', 'gpt-4', 'test instruction'])
       expect(cli.model).toBe('gpt-4')
@@ -54,10 +184,27 @@ describe('CLI Parser', () => {
       expect(cli.provider).toBe('openai')
     })
 
+    it('parses --dump-request', () => {
+      expect(parse(['--dump-request', 'test']).dumpRequest).toBe(true)
+      expect(parse(['test']).dumpRequest).toBe(false)
+    })
+
+    it('parses --reasoning effort levels', () => {
+      expect(parse(['--reasoning', 'high', 'test']).reasoning).toBe('high')
+      expect(parse(['test']).reasoning).toBeUndefined()
+      expect(() => parse(['--reasoning', 'max', 'test'])).toThrow()
+    })
+
+    it('parses --api-key-env', () => {
+      const cli = parse(['--api-key-env', 'CLAI_WORK_KEY', 'test instruction'])
+      expect(cli.apiKeyEnv).toBe('CLAI_WORK_KEY')
+    })
+
     it('defaults to undefined when not provided', () => {
       const cli = parse(['test instruction'])
       expect(cli.model).toBeUndefined()
       expect(cli.provider).toBeUndefined()
+      expect(cli.apiKeyEnv).toBeUndefined()
     })
   })
 
@@ -84,6 +231,22 @@ describe('CLI Parser', () => {
     })
   })
 
+  describe('log level option', () => {
+    it('defaults logLevel to undefined', () => {
+      const cli = parse(['test'])
+      expect(cli.logLevel).toBeUndefined()
+    })
+
+    it('parses --log-level', () => {
+      const cli = parse(['--log-level', 'warn', 'test'])
+      expect(cli.logLevel).toBe('warn')
+    })
+
+    it('rejects unknown levels', () => {
+      expect(() => parse(['--log-level', 'loud', 'test'])).toThrow()
+    })
+  })
+
   describe('color options', () => {
     it('defaults color to auto', () => {
       const cli = parse(['test'])
@@ -132,6 +295,25 @@ describe('CLI Parser', () => {
       expect(cli.dryRun).toBe(true)
     })
 
+    it('sets configFile with --config', () => {
+      expect(parse(['test']).configFile).toBeUndefined()
+      expect(parse(['--config', './team.toml', 'test']).configFile).toBe(
+        './team.toml'
+      )
+    })
+
+    it('sets noWarnMissingConfig with --no-warn-missing-config', () => {
+      expect(parse(['test']).noWarnMissingConfig).toBe(false)
+      expect(
+        parse(['--no-warn-missing-config', 'test']).noWarnMissingConfig
+      ).toBe(true)
+    })
+
+    it('sets previewScript with --preview-script', () => {
+      expect(parse(['test']).previewScript).toBe(false)
+      expect(parse(['--preview-script', 'test']).previewScript).toBe(true)
+    })
+
     it('defaults offline to false', () => {
       const cli = parse(['test'])
       expect(cli.offline).toBe(false)
@@ -154,6 +336,16 @@ describe('CLI Parser', () => {
       expect(cli.numOptions).toBe(5)
     })
 
+    it('marks numOptions as implicit when -o is not given', () => {
+      const cli = parse(['test'])
+      expect(cli.numOptionsExplicit).toBe(false)
+    })
+
+    it('marks numOptions as explicit when -o is given', () => {
+      const cli = parse(['-o', '1', 'test'])
+      expect(cli.numOptionsExplicit).toBe(true)
+    })
+
     it('clamps numOptions to minimum 1', () => {
       const cli = parse(['-o', '0', 'test'])
       expect(cli.numOptions).toBe(1)
@@ -199,6 +391,160 @@ describe('CLI Parser', () => {
     })
   })
 
+  describe('shell-integration option', () => {
+    it('does not require an instruction', () => {
+      expect(parse(['--shell-integration', 'zsh']).shellIntegration).toBe(
+        'zsh'
+      )
+    })
+
+    it('rejects unsupported shells', () => {
+      expect(() => parse(['--shell-integration', 'tcsh'])).toThrow()
+    })
+  })
+
+  describe('last-exit option', () => {
+    it('parses --last-exit as a number', () => {
+      expect(parse(['--last-exit', '127', 'fix that']).lastExit).toBe(127)
+      expect(parse(['--last-exit', '0', 'next']).lastExit).toBe(0)
+      expect(parse(['test']).lastExit).toBeUndefined()
+    })
+
+    it('rejects values that are not exit statuses', () => {
+      expect(() => parse(['--last-exit', 'abc', 'test'])).toThrow()
+      expect(() => parse(['--last-exit', '256', 'test'])).toThrow()
+      expect(() => parse(['--last-exit', '-1', 'test'])).toThrow()
+    })
+  })
+
+  describe('explain-only option', () => {
+    it('defaults explainOnly to false', () => {
+      expect(parse(['test']).explainOnly).toBe(false)
+    })
+
+    it('sets explainOnly with --explain-only and keeps the command', () => {
+      const cli = parse(['--explain-only', 'tar xzvf file.tar.gz'])
+      expect(cli.explainOnly).toBe(true)
+      expect(cli.instruction).toBe('tar xzvf file.tar.gz')
+    })
+  })
+
+  describe('explain-danger option', () => {
+    it('defaults explainDanger to false', () => {
+      const cli = parse(['test'])
+      expect(cli.explainDanger).toBe(false)
+    })
+
+    it('sets explainDanger with --explain-danger', () => {
+      const cli = parse(['--explain-danger', 'test'])
+      expect(cli.explainDanger).toBe(true)
+    })
+
+    it('sets summarizeContext with --summarize-context', () => {
+      expect(parse(['--summarize-context', 'test']).summarizeContext).toBe(
+        true
+      )
+      expect(parse(['test']).summarizeContext).toBe(false)
+    })
+
+    it('sets stats with --stats', () => {
+      expect(parse(['--stats', 'test']).stats).toBe(true)
+      expect(parse(['test']).stats).toBe(false)
+    })
+
+    it('sets refreshContext with --refresh-context', () => {
+      expect(parse(['--refresh-context', 'test']).refreshContext).toBe(true)
+      expect(parse(['test']).refreshContext).toBe(false)
+    })
+
+    it('sets showRaw and rawFile with --show-raw / --raw-file', () => {
+      expect(parse(['--show-raw', 'test']).showRaw).toBe(true)
+      expect(parse(['test']).showRaw).toBe(false)
+      expect(parse(['--raw-file', 'raw.txt', 'test']).rawFile).toBe('raw.txt')
+      expect(parse(['test']).rawFile).toBeUndefined()
+    })
+
+    it('sets strictJson with --strict-json', () => {
+      expect(parse(['--strict-json', 'test']).strictJson).toBe(true)
+      expect(parse(['test']).strictJson).toBe(false)
+    })
+
+    it('sets onlyIfSafe with --only-if-safe', () => {
+      expect(parse(['--only-if-safe', 'test']).onlyIfSafe).toBe(true)
+      expect(parse(['test']).onlyIfSafe).toBe(false)
+    })
+  })
+
+  describe('stdin option', () => {
+    it('defaults noStdin to false', () => {
+      const cli = parse(['test'])
+      expect(cli.noStdin).toBe(false)
+    })
+
+    it('sets noStdin with --no-stdin', () => {
+      const cli = parse(['--no-stdin', 'test'])
+      expect(cli.noStdin).toBe(true)
+    })
+
+    it('sets noCacheSystemInfo with --no-cache-system-info', () => {
+      expect(
+        parse(['--no-cache-system-info', 'test']).noCacheSystemInfo
+      ).toBe(true)
+      expect(parse(['test']).noCacheSystemInfo).toBe(false)
+    })
+
+    it('sets noSpinner with --no-spinner', () => {
+      expect(parse(['--no-spinner', 'test']).noSpinner).toBe(true)
+      expect(parse(['test']).noSpinner).toBe(false)
+    })
+
+    it('parses --stdin-as-file', () => {
+      const cli = parse(['--stdin-as-file', 'Dockerfile', 'optimize this'])
+      expect(cli.stdinAsFile).toBe('Dockerfile')
+      expect(cli.instruction).toBe('optimize this')
+    })
+  })
+
+  describe('format option', () => {
+    it('defaults format to plain', () => {
+      const cli = parse(['test'])
+      expect(cli.format).toBe('plain')
+    })
+
+    it('parses --format jsonl', () => {
+      const cli = parse(['--format', 'jsonl', 'test'])
+      expect(cli.format).toBe('jsonl')
+    })
+
+    it('rejects unknown formats', () => {
+      expect(() => parse(['--format', 'yaml', 'test'])).toThrow()
+    })
+  })
+
+  describe('fallback-on-auth option', () => {
+    it('defaults noFallbackOnAuth to false', () => {
+      const cli = parse(['test'])
+      expect(cli.noFallbackOnAuth).toBe(false)
+    })
+
+    it('sets noFallbackOnAuth with --no-fallback-on-auth', () => {
+      const cli = parse(['--no-fallback-on-auth', 'test'])
+      expect(cli.noFallbackOnAuth).toBe(true)
+    })
+  })
+
+  describe('history-only option', () => {
+    it('defaults historyOnly to false', () => {
+      const cli = parse(['test'])
+      expect(cli.historyOnly).toBe(false)
+    })
+
+    it('sets historyOnly with --history-only', () => {
+      const cli = parse(['--history-only', 'undo that'])
+      expect(cli.historyOnly).toBe(true)
+    })
+  })
+
   describe('combined flags', () => {
     it('parses multiple flags together', () => {
       const cli = parse([
@@ -6,10 +6,39 @@ import {
   ConfigError,
   getProviderApiKey,
   getProviderModel,
+  resolveProviderName,
+  mergeFallback,
+  mergeFileConfigs,
+  findInsecureConfigFiles,
+  fixConfigPermissions,
+  validateFileConfig,
+  getConfigWarnings,
+  resolveNumOptions,
+  parseEnvBool,
+  formatFileConfig,
+  setConfigValues,
+  maskConfigAssignment,
+  parseConfigAssignment,
+  parseConfigKey,
+  getConfigValue,
+  initUserConfig,
+  findUnknownSections,
+  missingConfigNotice,
+  MISSING_CONFIG_NOTICE,
+  setConfigFile,
+  getConfigPaths,
+  getUserConfigPath,
 } from '../src/config/index.js'
 import { FileConfig, Config } from '../src/config/types.js'
 import { Cli } from '../src/cli/index.js'
-import { mkdirSync, writeFileSync, rmSync, chmodSync } from 'fs'
+import {
+  mkdirSync,
+  writeFileSync,
+  readFileSync,
+  rmSync,
+  chmodSync,
+  statSync,
+} from 'fs'
 import { join } from 'path'
 
 describe('Config Module', () => {
@@ -37,6 +66,70 @@ describe('Config Module', () => {
     }
   })
 
+  describe('Pinned Config File', () => {
+    afterEach(() => {
+      setConfigFile(undefined)
+      delete process.env.CLAI_CONFIG
+    })
+
+    it('should load only the --config file and skip discovery', () => {
+      // A discoverable project config that must be ignored
+      writeFileSync(join(testDir, '.clai.toml'), '[ui]\ncolor = "always"\n')
+      const pinned = join(testDir, 'team.toml')
+      writeFileSync(pinned, '[context]\nmax-files = 7\n')
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+
+      try {
+        setConfigFile('team.toml')
+
+        expect(getConfigPaths()).toEqual([pinned])
+        expect(getUserConfigPath()).toBe(pinned)
+
+        const config = loadFileConfig()
+        expect(config.context.maxFiles).toBe(7)
+        expect(config.ui.color).toBe('auto')
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should read CLAI_CONFIG when --config is not given', () => {
+      const pinned = join(testDir, 'env.toml')
+      writeFileSync(pinned, '[context]\nmax-history = 9\n')
+      process.env.CLAI_CONFIG = pinned
+
+      expect(getConfigPaths()).toEqual([pinned])
+      expect(loadFileConfig().context.maxHistory).toBe(9)
+    })
+
+    it('should let --config win over CLAI_CONFIG', () => {
+      process.env.CLAI_CONFIG = join(testDir, 'env.toml')
+      setConfigFile(join(testDir, 'flag.toml'))
+
+      expect(getConfigPaths()).toEqual([join(testDir, 'flag.toml')])
+    })
+
+    it('should fail with exit code 3 when the file is missing', () => {
+      setConfigFile(join(testDir, 'missing.toml'))
+
+      expect(() => loadFileConfig()).toThrow(ConfigError)
+      expect(() => loadFileConfig()).toThrow(
+        `Config file not found: ${join(testDir, 'missing.toml')}`
+      )
+    })
+
+    it('should still enforce file permissions', () => {
+      const pinned = join(testDir, 'secret.toml')
+      writeFileSync(pinned, '[providers.openrouter]\napi-key = "sk-test"\n')
+      chmodSync(pinned, 0o644)
+      setConfigFile(pinned)
+
+      expect(() => loadFileConfig()).toThrow(ConfigError)
+    })
+  })
+
   describe('FileConfig Loading', () => {
     it('should return defaults when no config files exist', () => {
       // Change to test directory to avoid loading project root's .clai.toml
@@ -156,7 +249,7 @@ max-files = "not a number"
       }
     })
 
-    it('should check Unix permissions (0600) on config files', () => {
+    it('should check Unix permissions (0600) on config files with secrets', () => {
       // Skip on Windows
       if (process.platform === 'win32') {
         return
@@ -165,6 +258,9 @@ max-files = "not a number"
       const configContent = `
 [provider]
 default = "test"
+
+[providers.openrouter]
+api-key = "sk-secret"
 `
       const configPath = join(testDir, '.clai.toml')
       writeFileSync(configPath, configContent)
@@ -183,6 +279,218 @@ default = "test"
       }
     })
 
+    it('should load a config file without secrets regardless of permissions', () => {
+      if (process.platform === 'win32') {
+        return
+      }
+
+      const configPath = join(testDir, '.clai.toml')
+      writeFileSync(
+        configPath,
+        '[provider]\ndefault = "test"\n\n[providers.openrouter]\napi-key-env = "MY_KEY"\n'
+      )
+      chmodSync(configPath, 0o644)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        expect(loadFileConfig().provider?.default).toBe('test')
+        expect(findInsecureConfigFiles()).not.toContain(
+          join(process.cwd(), '.clai.toml')
+        )
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should detect quoted and dotted API keys', () => {
+      if (process.platform === 'win32') {
+        return
+      }
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+
+      try {
+        for (const content of [
+          '[providers.openrouter]\n"api-key" = "sk-x"\n',
+          'providers.openrouter.api-key = "sk-x"\n',
+          'openrouter-api-key = "sk-x"\n',
+        ]) {
+          const configPath = join(testDir, '.clai.toml')
+          writeFileSync(configPath, content)
+          chmodSync(configPath, 0o644)
+          clearConfigCache()
+
+          expect(() => loadFileConfig()).toThrow(/insecure permissions/)
+          expect(findInsecureConfigFiles()).toContain(
+            join(process.cwd(), '.clai.toml')
+          )
+        }
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should suggest the chmod command for insecure permissions', () => {
+      if (process.platform === 'win32') {
+        return
+      }
+
+      const configPath = join(testDir, '.clai.toml')
+      writeFileSync(configPath, '[providers.openrouter]\napi_key = "sk-x"\n')
+      chmodSync(configPath, 0o644)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        expect(() => loadFileConfig()).toThrow(
+          `chmod 600 ${join(process.cwd(), '.clai.toml')}`
+        )
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should fix insecure permissions to 0600', () => {
+      if (process.platform === 'win32') {
+        return
+      }
+
+      const configPath = join(testDir, '.clai.toml')
+      writeFileSync(configPath, '[providers.openrouter]\napi_key = "sk-x"\n')
+      chmodSync(configPath, 0o644)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        const insecure = findInsecureConfigFiles()
+        expect(insecure).toContain(join(process.cwd(), '.clai.toml'))
+
+        fixConfigPermissions(configPath)
+
+        expect(statSync(configPath).mode & 0o777).toBe(0o600)
+        expect(findInsecureConfigFiles()).not.toContain(
+          join(process.cwd(), '.clai.toml')
+        )
+        expect(() => loadFileConfig()).not.toThrow()
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should merge included config files', () => {
+      writeFileSync(
+        join(testDir, 'base.toml'),
+        `
+[context]
+max-history = 7
+redact-paths = true
+`
+      )
+      chmodSync(join(testDir, 'base.toml'), 0o600)
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        `include = "base.toml"
+
+[provider]
+default = "included-test"
+`
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.provider?.default).toBe('included-test')
+        expect(config.context?.maxHistory).toBe(7)
+        expect(config.context?.redactPaths).toBe(true)
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should keep included values in a section both files set', () => {
+      writeFileSync(join(testDir, 'base.toml'), '[context]\nmax-files = 50\n')
+      chmodSync(join(testDir, 'base.toml'), 0o600)
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        'include = "base.toml"\n\n[context]\nredact-paths = true\n'
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.context?.maxFiles).toBe(50)
+        expect(config.context?.redactPaths).toBe(true)
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should let the including file override included values', () => {
+      writeFileSync(
+        join(testDir, 'base.toml'),
+        `
+[provider]
+default = "from-base"
+`
+      )
+      chmodSync(join(testDir, 'base.toml'), 0o600)
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        `include = ["base.toml"]
+
+[provider]
+default = "from-main"
+`
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        expect(loadFileConfig().provider?.default).toBe('from-main')
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should detect a self-include cycle', () => {
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        `include = ".clai.toml"
+`
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        expect(() => loadFileConfig()).toThrow(ConfigError)
+        expect(() => loadFileConfig()).toThrow(/include cycle/)
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
     it('should cache config after first load', () => {
       const configContent = `
 [provider]
@@ -242,6 +550,31 @@ default = "cached"
       }
     })
 
+    it('should only override the keys that have an env var', () => {
+      const originalEnv = { ...process.env }
+      const file = join(testDir, 'fields.toml')
+      writeFileSync(
+        file,
+        '[context]\nsystem-fields = ["os-name"]\nredaction-placeholder = "~"\n' +
+          '[safety]\nmax-command-lines = 5\n'
+      )
+      setConfigFile(file)
+      process.env.CLAI_CONTEXT_MAX_FILES = '5'
+      process.env.CLAI_SAFETY_CONFIRM_DANGEROUS = 'false'
+
+      try {
+        const config = loadFileConfig()
+        expect(config.context.maxFiles).toBe(5)
+        expect(config.context.systemFields).toEqual(['os-name'])
+        expect(config.context.redactionPlaceholder).toBe('~')
+        expect(config.safety.confirmDangerous).toBe(false)
+        expect(config.safety.maxCommandLines).toBe(5)
+      } finally {
+        setConfigFile(undefined)
+        process.env = originalEnv
+      }
+    })
+
     it('should handle CLAI_PROVIDER_FALLBACK as comma-separated list', () => {
       const originalEnv = { ...process.env }
 
@@ -279,6 +612,337 @@ default = "cached"
     })
   })
 
+  describe('Print Config', () => {
+    const config: FileConfig = {
+      provider: { default: 'openrouter', fallback: ['backup'] },
+      context: {
+        maxFiles: 20,
+        maxHistory: 3,
+        redactPaths: true,
+        redactUsername: false,
+        mode: 'full',
+      },
+      safety: {
+        confirmDangerous: false,
+        dangerousPatterns: [],
+        maxCommandLength: 4096,
+        maxCommandLines: 100,
+      },
+      ui: { color: 'auto', interactive: false, promptTimeout: 30000 },
+      providers: {
+        openrouter: { apiKey: 'sk-or-secret', model: 'qwen/qwen3-coder' },
+      },
+    }
+
+    it('should print TOML that loads back as a config', () => {
+      const output = formatFileConfig(config)
+      expect(output).toContain('max-files = 20')
+
+      const originalCwd = process.cwd()
+      writeFileSync(join(testDir, '.clai.toml'), output)
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+      process.chdir(testDir)
+
+      try {
+        const loaded = loadFileConfig()
+        expect(loaded.provider.fallback).toEqual(['backup'])
+        expect(loaded.context.maxFiles).toBe(20)
+        expect(loaded.context.redactPaths).toBe(true)
+        expect(loaded.safety.confirmDangerous).toBe(false)
+        expect(loaded.providers.openrouter?.model).toBe('qwen/qwen3-coder')
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should mask API keys', () => {
+      const toml = formatFileConfig(config)
+      expect(toml).not.toContain('sk-or-secret')
+      expect(toml).toContain('api-key = "********"')
+
+      const json = JSON.parse(formatFileConfig(config, 'json'))
+      expect(json.providers.openrouter['api-key']).toBe('********')
+      expect(json.providers.openrouter.model).toBe('qwen/qwen3-coder')
+    })
+  })
+
+  describe('Unknown Sections', () => {
+    it('should warn about a misspelled [contex] section', () => {
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        '[contex]\nmax-files = 5\n\n[provider]\ndefault = "openrouter"\n'
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.context?.maxFiles).toBe(10)
+        expect(getConfigWarnings()).toContainEqual(
+          expect.stringContaining(
+            'Unknown section [contex] in ' +
+              join(process.cwd(), '.clai.toml') +
+              ' is ignored; did you mean [context]?'
+          )
+        )
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should suggest the closest section only for near misses', () => {
+      expect(
+        findUnknownSections({ saftey: {}, uii: {}, xy: {} }, 'a.toml')
+      ).toEqual([
+        'Unknown section [saftey] in a.toml is ignored; did you mean [safety]?',
+        'Unknown section [uii] in a.toml is ignored; did you mean [ui]?',
+        'Unknown section [xy] in a.toml is ignored',
+      ])
+    })
+
+    it('should accept known sections and include', () => {
+      expect(
+        findUnknownSections(
+          { include: 'base.toml', provider: {}, providers: {}, ui: {} },
+          'a.toml'
+        )
+      ).toEqual([])
+    })
+  })
+
+  describe('Set Config', () => {
+    it('should write keys to a new file with 0600 and read them back', () => {
+      const configPath = join(testDir, 'clai', 'config.toml')
+
+      setConfigValues(
+        ['provider.default=openrouter', 'openrouter.model=qwen/qwen3-coder'],
+        configPath
+      )
+
+      if (process.platform !== 'win32') {
+        expect(statSync(configPath).mode & 0o777).toBe(0o600)
+      }
+
+      const originalXdg = process.env.XDG_CONFIG_HOME
+      process.env.XDG_CONFIG_HOME = testDir
+      clearConfigCache()
+
+      try {
+        const loaded = loadFileConfig()
+        expect(loaded.provider.default).toBe('openrouter')
+        expect(loaded.providers.openrouter?.model).toBe('qwen/qwen3-coder')
+      } finally {
+        if (originalXdg === undefined) {
+          delete process.env.XDG_CONFIG_HOME
+        } else {
+          process.env.XDG_CONFIG_HOME = originalXdg
+        }
+      }
+    })
+
+    it('should keep other settings and the file permissions', () => {
+      const configPath = join(testDir, 'config.toml')
+      writeFileSync(
+        configPath,
+        '[context]\nmax-files = 20\n\n[providers.openrouter]\napi-key = "sk-x"\n'
+      )
+      chmodSync(configPath, 0o600)
+
+      setConfigValues(
+        ['context.redact-paths=true', 'ui.num-options=5'],
+        configPath
+      )
+
+      const content = readFileSync(configPath, 'utf-8')
+      expect(content).toContain('max-files = 20')
+      expect(content).toContain('redact-paths = true')
+      expect(content).toContain('num-options = 5')
+      expect(content).toContain('api-key = "sk-x"')
+      if (process.platform !== 'win32') {
+        expect(statSync(configPath).mode & 0o777).toBe(0o600)
+      }
+    })
+
+    it('should restrict an existing file to 0600 once it holds a key', () => {
+      if (process.platform === 'win32') {
+        return
+      }
+
+      const configPath = join(testDir, 'config.toml')
+      writeFileSync(configPath, '[ui]\ncolor = "never"\n')
+      chmodSync(configPath, 0o644)
+
+      setConfigValues(['ui.num-options=5'], configPath)
+      expect(statSync(configPath).mode & 0o777).toBe(0o644)
+
+      setConfigValues(['openrouter.api-key=sk-secret'], configPath)
+      expect(statSync(configPath).mode & 0o777).toBe(0o600)
+    })
+
+    it('should mask API keys when echoing an assignment', () => {
+      expect(maskConfigAssignment('openrouter.api-key=sk-secret')).toBe(
+        'openrouter.api-key=********'
+      )
+      expect(maskConfigAssignment('ui.color=never')).toBe('ui.color=never')
+      expect(maskConfigAssignment('openrouter.api-key-env=MY_KEY')).toBe(
+        'openrouter.api-key-env=MY_KEY'
+      )
+    })
+
+    it('should parse values as TOML, falling back to a string', () => {
+      expect(parseConfigAssignment('ui.num-options=3')).toEqual({
+        path: ['ui', 'num-options'],
+        value: 3,
+      })
+      expect(parseConfigAssignment('openrouter.model=qwen/qwen3-coder')).toEqual({
+        path: ['providers', 'openrouter', 'model'],
+        value: 'qwen/qwen3-coder',
+      })
+    })
+
+    it('should reject unknown keys and invalid values', () => {
+      const configPath = join(testDir, 'config.toml')
+      expect(() => setConfigValues(['ui.colour=never'], configPath)).toThrow(
+        'Unknown config key: ui.colour'
+      )
+      expect(() => setConfigValues(['ui.color=purple'], configPath)).toThrow(
+        ConfigError
+      )
+      expect(() => setConfigValues(['provider'], configPath)).toThrow(
+        ConfigError
+      )
+    })
+  })
+
+  describe('Get Config', () => {
+    const config: FileConfig = {
+      provider: { default: 'openrouter', fallback: ['backup'] },
+      context: {
+        maxFiles: 20,
+        maxHistory: 3,
+        redactPaths: true,
+        redactUsername: false,
+        mode: 'full',
+      },
+      safety: { confirmDangerous: false, dangerousPatterns: [] },
+      ui: { color: 'never', interactive: false },
+      providers: {
+        openrouter: { apiKey: 'sk-or-secret', model: 'qwen/qwen3-coder' },
+      },
+    }
+
+    it('should print scalars, lists and sections', () => {
+      expect(getConfigValue(config, 'ui.color')).toBe('never\n')
+      expect(getConfigValue(config, 'context.max-files')).toBe('20\n')
+      expect(getConfigValue(config, 'provider.fallback')).toBe(
+        '[ "backup" ]\n'
+      )
+      expect(getConfigValue(config, 'openrouter.model')).toBe(
+        'qwen/qwen3-coder\n'
+      )
+      expect(getConfigValue(config, 'provider')).toContain(
+        'default = "openrouter"'
+      )
+    })
+
+    it('should mask API keys', () => {
+      expect(getConfigValue(config, 'openrouter.api-key')).toBe('********\n')
+    })
+
+    it('should reject unknown and unset keys', () => {
+      expect(() => getConfigValue(config, 'ui.colour')).toThrow(
+        'Unknown config key: ui.colour'
+      )
+      expect(() => getConfigValue(config, 'ui.num-options')).toThrow(
+        'ui.num-options is not set'
+      )
+    })
+
+    it('should resolve provider shorthand like --set', () => {
+      expect(parseConfigKey('openrouter.model')).toEqual([
+        'providers',
+        'openrouter',
+        'model',
+      ])
+      expect(parseConfigKey('context')).toEqual(['context'])
+    })
+  })
+
+  describe('Init Config', () => {
+    it('should write the defaults with 0600 and refuse to overwrite', () => {
+      const configPath = join(testDir, 'clai', 'config.toml')
+
+      expect(initUserConfig(configPath)).toBe(configPath)
+      const content = readFileSync(configPath, 'utf-8')
+      expect(content).toContain('default = "openrouter"')
+      if (process.platform !== 'win32') {
+        expect(statSync(configPath).mode & 0o777).toBe(0o600)
+      }
+
+      expect(() => initUserConfig(configPath)).toThrow('already exists')
+    })
+  })
+
+  describe('Boolean Environment Variables', () => {
+    it.each(['1', 'true', 'TRUE', 'yes', 'Yes', 'on', 'ON'])(
+      'should parse %s as true',
+      (value) => {
+        expect(parseEnvBool(value)).toBe(true)
+      }
+    )
+
+    it.each(['0', 'false', 'False', 'no', 'NO', 'off', 'Off'])(
+      'should parse %s as false',
+      (value) => {
+        expect(parseEnvBool(value)).toBe(false)
+      }
+    )
+
+    it('should return undefined for unrecognized values', () => {
+      expect(parseEnvBool('maybe')).toBeUndefined()
+      expect(parseEnvBool('')).toBeUndefined()
+    })
+
+    it('should accept any spelling in boolean env vars', () => {
+      const originalEnv = { ...process.env }
+
+      process.env.CLAI_CONTEXT_REDACT_PATHS = 'yes'
+      process.env.CLAI_CONTEXT_REDACT_USERNAME = '1'
+      process.env.CLAI_SAFETY_CONFIRM_DANGEROUS = 'off'
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.context.redactPaths).toBe(true)
+        expect(config.context.redactUsername).toBe(true)
+        expect(config.safety.confirmDangerous).toBe(false)
+        expect(getConfigWarnings()).toEqual([])
+      } finally {
+        process.env = originalEnv
+      }
+    })
+
+    it('should warn and keep the default for an invalid value', () => {
+      const originalEnv = { ...process.env }
+
+      process.env.CLAI_SAFETY_CONFIRM_DANGEROUS = 'nope'
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.safety.confirmDangerous).toBe(true)
+        const warnings = getConfigWarnings()
+        expect(warnings).toHaveLength(1)
+        expect(warnings[0]).toContain("CLAI_SAFETY_CONFIRM_DANGEROUS='nope'")
+      } finally {
+        process.env = originalEnv
+      }
+    })
+  })
+
   describe('Config Building', () => {
     it('should build runtime config from file config and CLI', () => {
       const fileConfig: FileConfig = {
@@ -504,6 +1168,29 @@ default = "cached"
       }
     })
 
+    it('should use --api-key-env for the selected provider', () => {
+      process.env.CLAI_WORK_KEY = 'work-key'
+
+      const config = {
+        provider: { default: 'openrouter', fallback: [] },
+        providers: {
+          openrouter: { apiKey: 'inline-key' },
+          backup: { apiKeyEnv: 'BACKUP_KEY_VAR' },
+        },
+        apiKeyEnv: 'CLAI_WORK_KEY',
+      } as unknown as Config
+
+      try {
+        expect(getProviderApiKey('openrouter', config)).toBe('work-key')
+        // Other providers (fallbacks) keep their own keys
+        expect(getProviderApiKey('backup', config)).toBe(
+          process.env.BACKUP_KEY_VAR
+        )
+      } finally {
+        delete process.env.CLAI_WORK_KEY
+      }
+    })
+
     it('should fall back to OPENROUTER_API_KEY env var', () => {
       const originalEnv = process.env.OPENROUTER_API_KEY
       process.env.OPENROUTER_API_KEY = 'fallback-key'
@@ -567,4 +1254,459 @@ default = "cached"
       expect(model).toBe('gpt-4o-mini')
     })
   })
+
+  describe('Provider Name Resolution', () => {
+    const hints = [
+      { prefix: 'gpt-', provider: 'openai' },
+      { prefix: 'claude-', provider: 'anthropic' },
+    ]
+
+    function configWith(overrides: Partial<Config>): Config {
+      return {
+        provider: {
+          default: 'openrouter',
+          fallback: [],
+          modelProviderHints: hints,
+        },
+        providers: {},
+        ...overrides,
+      } as unknown as Config
+    }
+
+    it('should route a model to the provider whose prefix matches', () => {
+      expect(resolveProviderName(configWith({ model: 'gpt-4o' }))).toBe(
+        'openai'
+      )
+      expect(
+        resolveProviderName(configWith({ model: 'claude-3.5-sonnet' }))
+      ).toBe('anthropic')
+    })
+
+    it('should fall back to provider.default when no hint matches', () => {
+      expect(
+        resolveProviderName(configWith({ model: 'qwen/qwen3-coder' }))
+      ).toBe('openrouter')
+      expect(resolveProviderName(configWith({}))).toBe('openrouter')
+    })
+
+    it('should let an explicit --provider win over hints', () => {
+      expect(
+        resolveProviderName(
+          configWith({ model: 'gpt-4o', providerName: 'openrouter' })
+        )
+      ).toBe('openrouter')
+    })
+
+    it('should load hints from TOML without mangling prefixes', () => {
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        `
+[[provider.model-provider-hints]]
+prefix = "meta-llama/"
+provider = "together"
+`
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.provider.modelProviderHints).toEqual([
+          { prefix: 'meta-llama/', provider: 'together' },
+        ])
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+  })
+
+  describe('Num Options Resolution', () => {
+    const fileConfig: FileConfig = {
+      provider: { default: 'openrouter', fallback: [] },
+      ui: { color: 'auto', interactive: false, promptTimeout: 30000 },
+      providers: {},
+    }
+
+    function cliWith(overrides: Partial<Cli>): Cli {
+      return {
+        instruction: 'test',
+        quiet: false,
+        verbose: 0,
+        noColor: false,
+        color: 'auto',
+        interactive: false,
+        force: false,
+        dryRun: false,
+        offline: false,
+        numOptions: 1,
+        numOptionsExplicit: false,
+        debug: false,
+        ...overrides,
+      } as Cli
+    }
+
+    it('should generate a single option when not interactive', () => {
+      const config = buildConfig(fileConfig, cliWith({}))
+      expect(resolveNumOptions(config, false)).toBe(1)
+    })
+
+    it('should default to 3 options when interactive', () => {
+      const config = buildConfig(fileConfig, cliWith({}))
+      expect(resolveNumOptions(config, true)).toBe(3)
+    })
+
+    it('should use ui.num-options from config when interactive', () => {
+      const config = buildConfig(
+        { ...fileConfig, ui: { ...fileConfig.ui!, numOptions: 5 } },
+        cliWith({})
+      )
+      expect(resolveNumOptions(config, true)).toBe(5)
+      expect(resolveNumOptions(config, false)).toBe(1)
+    })
+
+    it('should let an explicit -o win in either mode', () => {
+      const config = buildConfig(
+        fileConfig,
+        cliWith({ numOptions: 4, numOptionsExplicit: true })
+      )
+      expect(resolveNumOptions(config, true)).toBe(4)
+      expect(resolveNumOptions(config, false)).toBe(4)
+    })
+
+    it('should default ui.command-join to && and honor the config', () => {
+      expect(buildConfig(fileConfig, cliWith({})).ui.commandJoin).toBe('&&')
+
+      const config = buildConfig(
+        { ...fileConfig, ui: { ...fileConfig.ui!, commandJoin: ';' } },
+        cliWith({})
+      )
+      expect(config.ui.commandJoin).toBe(';')
+    })
+
+    it('should default ui.interactive-default-action to execute', () => {
+      expect(
+        buildConfig(fileConfig, cliWith({})).ui.interactiveDefaultAction
+      ).toBe('execute')
+
+      const config = buildConfig(
+        {
+          ...fileConfig,
+          ui: { ...fileConfig.ui!, interactiveDefaultAction: 'output' },
+        },
+        cliWith({})
+      )
+      expect(config.ui.interactiveDefaultAction).toBe('output')
+    })
+
+    it('should keep an explicit -o 1 when interactive', () => {
+      const config = buildConfig(
+        fileConfig,
+        cliWith({ numOptions: 1, numOptionsExplicit: true })
+      )
+      expect(resolveNumOptions(config, true)).toBe(1)
+    })
+  })
+
+  describe('Missing Config Notice', () => {
+    const fileConfig: FileConfig = {
+      provider: { default: 'openrouter', fallback: [] },
+      ui: { color: 'auto', interactive: false, promptTimeout: 30000 },
+      providers: {},
+    }
+
+    function configWith(overrides: Partial<Cli>): Config {
+      return buildConfig(fileConfig, {
+        instruction: 'test',
+        quiet: false,
+        verbose: 1,
+        force: false,
+        dryRun: false,
+        offline: false,
+        numOptions: 1,
+        debug: false,
+        noWarnMissingConfig: false,
+        ...overrides,
+      } as Cli)
+    }
+
+    it('should note a missing config file', () => {
+      expect(missingConfigNotice(configWith({}), true, {})).toBe(
+        MISSING_CONFIG_NOTICE
+      )
+    })
+
+    it('should stay quiet when a config file exists', () => {
+      expect(missingConfigNotice(configWith({}), false, {})).toBeNull()
+    })
+
+    it('should be suppressed by --no-warn-missing-config', () => {
+      const config = configWith({ noWarnMissingConfig: true })
+      expect(config.noWarnMissingConfig).toBe(true)
+      expect(missingConfigNotice(config, true, {})).toBeNull()
+    })
+
+    it('should be suppressed by a truthy CLAI_NO_WARN_MISSING_CONFIG', () => {
+      const config = configWith({})
+      expect(
+        missingConfigNotice(config, true, { CLAI_NO_WARN_MISSING_CONFIG: '1' })
+      ).toBeNull()
+      expect(
+        missingConfigNotice(config, true, { CLAI_NO_WARN_MISSING_CONFIG: '0' })
+      ).toBe(MISSING_CONFIG_NOTICE)
+    })
+  })
+
+  describe('Config Validation', () => {
+    const base: FileConfig = {
+      provider: { default: 'openrouter', fallback: [] },
+      context: {
+        maxFiles: 10,
+        maxHistory: 3,
+        redactPaths: false,
+        redactUsername: false,
+        mode: 'full',
+      },
+      ui: { color: 'auto', interactive: false, promptTimeout: 30000 },
+      providers: {},
+    }
+
+    const defaultSafety = {
+      confirmDangerous: true,
+      dangerousPatterns: [],
+      confirmPipeToInterpreter: false,
+      maxCommandLength: 4096,
+      maxCommandLines: 100,
+    }
+
+    function withContext(overrides: Partial<NonNullable<FileConfig['context']>>) {
+      return { ...base, context: { ...base.context!, ...overrides } }
+    }
+
+    it('should accept defaults without warnings', () => {
+      const { config, warnings } = validateFileConfig(base)
+      expect(warnings).toEqual([])
+      expect(config).toEqual(base)
+    })
+
+    it('should accept max-files at its bounds', () => {
+      expect(validateFileConfig(withContext({ maxFiles: 1 })).warnings).toEqual([])
+      expect(validateFileConfig(withContext({ maxFiles: 100 })).warnings).toEqual([])
+    })
+
+    it('should clamp max-files above 100 with a warning', () => {
+      const { config, warnings } = validateFileConfig(
+        withContext({ maxFiles: 100000 })
+      )
+      expect(config.context?.maxFiles).toBe(100)
+      expect(warnings).toHaveLength(1)
+      expect(warnings[0]).toContain('context.max-files = 100000')
+    })
+
+    it('should clamp max-files below 1 with a warning', () => {
+      const { config, warnings } = validateFileConfig(withContext({ maxFiles: 0 }))
+      expect(config.context?.maxFiles).toBe(1)
+      expect(warnings).toHaveLength(1)
+    })
+
+    it('should accept max-history at its bounds', () => {
+      expect(validateFileConfig(withContext({ maxHistory: 0 })).warnings).toEqual([])
+      expect(validateFileConfig(withContext({ maxHistory: 50 })).warnings).toEqual([])
+    })
+
+    it('should clamp max-history out of range with a warning', () => {
+      const high = validateFileConfig(withContext({ maxHistory: 51 }))
+      expect(high.config.context?.maxHistory).toBe(50)
+      expect(high.warnings[0]).toContain('context.max-history = 51')
+
+      const low = validateFileConfig(withContext({ maxHistory: -1 }))
+      expect(low.config.context?.maxHistory).toBe(0)
+      expect(low.warnings).toHaveLength(1)
+    })
+
+    it('should clamp prompt-timeout above 300000 with a warning', () => {
+      const atMax = validateFileConfig({
+        ...base,
+        ui: { ...base.ui!, promptTimeout: 300000 },
+      })
+      expect(atMax.warnings).toEqual([])
+
+      const { config, warnings } = validateFileConfig({
+        ...base,
+        ui: { ...base.ui!, promptTimeout: 300001 },
+      })
+      expect(config.ui?.promptTimeout).toBe(300000)
+      expect(warnings[0]).toContain('ui.prompt-timeout')
+    })
+
+    it('should reject confirm-keys that share a key', () => {
+      expect(() =>
+        validateFileConfig({
+          ...base,
+          safety: {
+            ...defaultSafety,
+            confirmKeys: { execute: 'y', abort: 'Y' },
+          },
+        })
+      ).toThrow('safety.confirm-keys: execute and abort both use "Y"')
+
+      // Only execute/abort changed: still clashes with the default regenerate key
+      expect(() =>
+        validateFileConfig({
+          ...base,
+          safety: { ...defaultSafety, confirmKeys: { abort: 'r' } },
+        })
+      ).toThrow(ConfigError)
+
+      expect(
+        validateFileConfig({
+          ...base,
+          safety: {
+            ...defaultSafety,
+            confirmKeys: { execute: 'y', abort: 'n', regenerate: 'c' },
+          },
+        }).warnings
+      ).toEqual([])
+    })
+
+    it('should reject an empty default provider', () => {
+      expect(() =>
+        validateFileConfig({
+          ...base,
+          provider: { default: '  ', fallback: [] },
+        })
+      ).toThrow(ConfigError)
+    })
+
+    it('should reject extra-params that override model or messages', () => {
+      expect(() =>
+        validateFileConfig({
+          ...base,
+          providers: { openrouter: { extraParams: { model: 'other' } } },
+        })
+      ).toThrow('providers.openrouter.extra-params must not set model')
+
+      expect(
+        validateFileConfig({
+          ...base,
+          providers: { openrouter: { extraParams: { top_p: 0.9 } } },
+        }).warnings
+      ).toEqual([])
+    })
+
+    it('should surface warnings for out-of-range env values', () => {
+      const originalEnv = process.env.CLAI_CONTEXT_MAX_FILES
+      process.env.CLAI_CONTEXT_MAX_FILES = '100000'
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.context?.maxFiles).toBe(100)
+        expect(getConfigWarnings()).toEqual([
+          expect.stringContaining('context.max-files'),
+        ])
+      } finally {
+        if (originalEnv === undefined) {
+          delete process.env.CLAI_CONTEXT_MAX_FILES
+        } else {
+          process.env.CLAI_CONTEXT_MAX_FILES = originalEnv
+        }
+      }
+    })
+  })
+
+  describe('Fallback Merging', () => {
+    const base = ['openrouter', 'ollama']
+    const override = ['anthropic']
+
+    it('should replace the base list by default', () => {
+      expect(mergeFallback(base, override)).toEqual(['anthropic'])
+    })
+
+    it('should keep the base list when replace override is empty', () => {
+      expect(mergeFallback(base, [], 'replace')).toEqual(base)
+    })
+
+    it('should append override after base', () => {
+      expect(mergeFallback(base, override, 'append')).toEqual([
+        'openrouter',
+        'ollama',
+        'anthropic',
+      ])
+    })
+
+    it('should prepend override before base', () => {
+      expect(mergeFallback(base, override, 'prepend')).toEqual([
+        'anthropic',
+        'openrouter',
+        'ollama',
+      ])
+    })
+
+    it('should drop duplicate providers', () => {
+      expect(mergeFallback(base, ['ollama', 'groq'], 'append')).toEqual([
+        'openrouter',
+        'ollama',
+        'groq',
+      ])
+    })
+
+    it('should use the merge mode from the overriding file', () => {
+      const baseConfig = {
+        provider: { default: 'openrouter', fallback: base },
+      } as FileConfig
+      const overrideConfig = {
+        provider: {
+          default: 'openrouter',
+          fallback: override,
+          fallbackMerge: 'append' as const,
+        },
+      }
+
+      const merged = mergeFileConfigs(baseConfig, overrideConfig)
+      expect(merged.provider?.fallback).toEqual([
+        'openrouter',
+        'ollama',
+        'anthropic',
+      ])
+    })
+
+    it('should merge fallback lists across config files', () => {
+      const userDir = join(testDir, 'xdg')
+      mkdirSync(join(userDir, 'clai'), { recursive: true })
+      writeFileSync(
+        join(userDir, 'clai', 'config.toml'),
+        '[provider]\nfallback = ["ollama"]\n'
+      )
+      chmodSync(join(userDir, 'clai', 'config.toml'), 0o600)
+      writeFileSync(
+        join(testDir, '.clai.toml'),
+        '[provider]\nfallback = ["groq"]\nfallback-merge = "prepend"\n'
+      )
+      chmodSync(join(testDir, '.clai.toml'), 0o600)
+
+      const originalCwd = process.cwd()
+      const originalXdg = process.env.XDG_CONFIG_HOME
+      process.chdir(testDir)
+      process.env.XDG_CONFIG_HOME = userDir
+      clearConfigCache()
+
+      try {
+        const config = loadFileConfig()
+        expect(config.provider.fallback).toEqual([
+          'groq',
+          'ollama',
+        ])
+      } finally {
+        process.chdir(originalCwd)
+        if (originalXdg !== undefined) {
+          process.env.XDG_CONFIG_HOME = originalXdg
+        } else {
+          delete process.env.XDG_CONFIG_HOME
+        }
+      }
+    })
+  })
 })
@@ -0,0 +1,28 @@
+import { describe, it, expect } from 'vitest'
+import { parseConfirmAnswer } from '../src/ui/output.js'
+
+describe('parseConfirmAnswer', () => {
+  it.each(['y', 'Y', 'yes', 'YES', 'Yes'])('should accept %s', (answer) => {
+    expect(parseConfirmAnswer(answer)).toBe(true)
+  })
+
+  it('should ignore surrounding whitespace', () => {
+    expect(parseConfirmAnswer(' y')).toBe(true)
+    expect(parseConfirmAnswer('\tyes \n')).toBe(true)
+  })
+
+  it.each(['', 'n', 'no', 'yep', 'yess', 'y y'])(
+    'should reject %j',
+    (answer) => {
+      expect(parseConfirmAnswer(answer)).toBe(false)
+    }
+  )
+
+  it('should reject non-ASCII input without case-mapping it', () => {
+    expect(parseConfirmAnswer('é')).toBe(false)
+    expect(parseConfirmAnswer('É')).toBe(false)
+    expect(parseConfirmAnswer('ÿ')).toBe(false)
+    expect(parseConfirmAnswer('ｙ')).toBe(false) // fullwidth y
+    expect(parseConfirmAnswer('ß')).toBe(false)
+  })
+})
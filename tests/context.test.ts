@@ -1,26 +1,55 @@
 // Context module tests
 
-import { describe, it, expect, beforeEach, afterEach } from 'vitest'
+import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest'
 import {
   gatherContext,
   getSystemInfo,
   clearSystemCache,
+  refreshSystemInfo,
+  collectSystemInfo,
   getCwd,
   scanDirectory,
   getRedactedCwd,
   getShellHistory,
+  readHistoryTail,
+  readHistoryTailAsync,
+  extractShellHistory,
+  getHistoryCandidates,
+  getShellHistoryAsync,
   getDetectedShell,
   readStdin,
   hasPipedStdin,
+  looksBinary,
+  decodeStdin,
+  BINARY_STDIN_MARKER,
   redactPath,
   redactUsername,
   redactEnvVars,
+  DEFAULT_REDACTION_PLACEHOLDER,
   ContextError,
+  getContextSections,
+  formatSystemInfo,
+  getHomeDir,
+  selectTopN,
+  detectPackageManagers,
+  detectTimezone,
+  formatLocalTimestamp,
+  summarizeContext,
+  detectEnvironments,
+  readProjectDescription,
+  MAX_PROJECT_DESCRIPTION,
 } from '../src/context/index.js'
+import { buildPrompt } from '../src/ai/index.js'
 import { Config } from '../src/config/types.js'
-import { mkdirSync, rmSync, writeFileSync } from 'fs'
-import { join } from 'path'
-import { homedir } from 'os'
+import {
+  mkdirSync,
+  rmSync,
+  writeFileSync,
+  utimesSync,
+  chmodSync,
+} from 'fs'
+import { join, delimiter } from 'path'
+import { homedir, userInfo } from 'os'
 
 describe('Context Module', () => {
   describe('System Info', () => {
@@ -69,6 +98,59 @@ describe('Context Module', () => {
       }
     })
 
+    it('should re-collect system info on refresh', () => {
+      const originalShell = process.env.SHELL
+      process.env.SHELL = '/bin/bash'
+
+      try {
+        expect(getSystemInfo(false).shell).toBe('bash')
+
+        // The user switched shells: the cache still has the old one
+        process.env.SHELL = '/usr/bin/fish'
+        expect(getSystemInfo(false).shell).toBe('bash')
+
+        expect(refreshSystemInfo(false).shell).toBe('fish')
+        expect(getSystemInfo(false).shell).toBe('fish')
+      } finally {
+        process.env.SHELL = originalShell
+        clearSystemCache()
+      }
+    })
+
+    it('should reflect an environment change after clearing the cache', () => {
+      const originalShell = process.env.SHELL
+      process.env.SHELL = '/bin/bash'
+      clearSystemCache()
+
+      try {
+        expect(getSystemInfo(false).shell).toBe('bash')
+        process.env.SHELL = '/bin/zsh'
+
+        clearSystemCache()
+        expect(getSystemInfo(false).shell).toBe('zsh')
+      } finally {
+        process.env.SHELL = originalShell
+        clearSystemCache()
+      }
+    })
+
+    it('should collect without reading or filling the cache', () => {
+      const originalShell = process.env.SHELL
+      process.env.SHELL = '/bin/bash'
+      clearSystemCache()
+
+      try {
+        const cached = getSystemInfo(false)
+        process.env.SHELL = '/usr/bin/fish'
+
+        expect(collectSystemInfo(false).shell).toBe('fish')
+        expect(getSystemInfo(false)).toBe(cached)
+      } finally {
+        process.env.SHELL = originalShell
+        clearSystemCache()
+      }
+    })
+
     it('should handle missing SHELL env var', () => {
       const originalShell = process.env.SHELL
       delete process.env.SHELL
@@ -84,6 +166,45 @@ describe('Context Module', () => {
     })
   })
 
+  describe('formatSystemInfo', () => {
+    const info = {
+      osName: 'Linux',
+      osVersion: '6.1.0',
+      architecture: 'x64',
+      shell: 'bash',
+      user: 'alice',
+      totalMemoryMb: 16384,
+    }
+
+    it('should include every field by default', () => {
+      expect(formatSystemInfo(info)).toEqual({
+        OS: 'Linux 6.1.0',
+        Architecture: 'x64',
+        Shell: 'bash',
+        User: 'alice',
+        Memory: '16384 MB',
+      })
+    })
+
+    it('should omit fields missing from the allowlist', () => {
+      const formatted = formatSystemInfo(info, [
+        'os-name',
+        'os-version',
+        'architecture',
+        'shell',
+      ])
+
+      expect(formatted).not.toHaveProperty('User')
+      expect(formatted).not.toHaveProperty('Memory')
+      expect(formatted.OS).toBe('Linux 6.1.0')
+      expect(formatted.Shell).toBe('bash')
+    })
+
+    it('should keep the OS line when only the name is allowed', () => {
+      expect(formatSystemInfo(info, ['os-name'])).toEqual({ OS: 'Linux' })
+    })
+  })
+
   describe('Redaction', () => {
     const home = homedir()
 
@@ -146,6 +267,50 @@ describe('Context Module', () => {
       expect(redactUsername('john')).toBe('[REDACTED]')
     })
 
+    it('should use [REDACTED] as the default placeholder', () => {
+      expect(DEFAULT_REDACTION_PLACEHOLDER).toBe('[REDACTED]')
+      if (!home) return
+
+      expect(redactPath(`${home}/src`)).toBe(
+        redactPath(`${home}/src`, '[REDACTED]')
+      )
+    })
+
+    it('should use a custom placeholder', () => {
+      expect(redactUsername('john', '<USER>')).toBe('<USER>')
+      expect(redactEnvVars('$HOME/bin', ['HOME'], '<HOME>')).toBe('<HOME>/bin')
+      if (!home || process.platform === 'win32') return
+
+      expect(redactPath(`cd ${home}/src`, '<HOME>')).toBe('cd <HOME>/src')
+      // `$` in the placeholder is literal, not a replacement pattern
+      expect(redactPath(`${home}/src`, '$&$HOME')).toBe('$&$HOME/src')
+    })
+
+    it('should keep paths runnable with a ~ placeholder', () => {
+      if (!home || process.platform === 'win32') return
+
+      expect(redactPath(`${home}/projects/test`, '~')).toBe('~/projects/test')
+      expect(redactPath(home, '~')).toBe('~')
+      expect(redactPath('~/projects', '~')).toBe('~/projects')
+      expect(redactPath('~', '~')).toBe('~')
+    })
+
+    it('should not redact paths that only share a prefix with home', () => {
+      if (!home || process.platform === 'win32') return
+
+      expect(redactPath(`${home}extra/file`, '~')).toBe(`${home}extra/file`)
+    })
+
+    it('should redact the username with the configured placeholder', () => {
+      clearSystemCache()
+      try {
+        expect(getSystemInfo(true, '<USER>').user).toBe('<USER>')
+        expect(getSystemInfo(true).user).toBe('[REDACTED]')
+      } finally {
+        clearSystemCache()
+      }
+    })
+
     it('should redact environment variables', () => {
       const text = 'Path: ${HOME}/test and $HOME/bin'
       const redacted = redactEnvVars(text, ['HOME'])
@@ -213,6 +378,58 @@ describe('Context Module', () => {
       }
     })
 
+    it('should pick the newest files with mtime sort', () => {
+      // Staggered mtimes (seconds): b newest, then c, then a, subdir oldest
+      utimesSync(join(testDir, 'a-file.txt'), 1000, 1000)
+      utimesSync(join(testDir, 'b-file.txt'), 3000, 3000)
+      utimesSync(join(testDir, 'c-file.txt'), 2000, 2000)
+      utimesSync(join(testDir, 'subdir'), 500, 500)
+
+      const originalCwd = process.cwd()
+      process.chdir(testDir)
+
+      try {
+        const byMtime = scanDirectory(2, false, 'mtime')
+        expect(byMtime.map((f) => f.split('/').pop())).toEqual([
+          'b-file.txt',
+          'c-file.txt',
+        ])
+
+        const byName = scanDirectory(2, false)
+        expect(byName.map((f) => f.split('/').pop())).toEqual([
+          'a-file.txt',
+          'b-file.txt',
+        ])
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
+    it('should keep exactly the first N names in a large directory', () => {
+      const largeDir = join(testDir, 'large')
+      mkdirSync(largeDir, { recursive: true })
+      const names = Array.from(
+        { length: 2000 },
+        (_, i) => `file-${String((i * 7919) % 2000).padStart(4, '0')}.txt`
+      )
+      for (const name of names) {
+        writeFileSync(join(largeDir, name), '')
+      }
+
+      const originalCwd = process.cwd()
+      process.chdir(largeDir)
+
+      try {
+        const files = scanDirectory(25, false)
+        const expected = [...names]
+          .sort((a, b) => a.localeCompare(b))
+          .slice(0, 25)
+        expect(files.map((f) => f.split('/').pop())).toEqual(expected)
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+
     it('should return empty array on directory read error', () => {
       // We can't easily test this without mocking fs
       // The function should return [] on error
@@ -241,6 +458,48 @@ describe('Context Module', () => {
     })
   })
 
+  describe('Home Directory', () => {
+    let originalHome: string | undefined
+
+    beforeEach(() => {
+      originalHome = process.env.HOME
+    })
+
+    afterEach(() => {
+      if (originalHome === undefined) {
+        delete process.env.HOME
+      } else {
+        process.env.HOME = originalHome
+      }
+    })
+
+    it('should prefer $HOME when set', () => {
+      process.env.HOME = '/tmp/clai-home'
+      expect(getHomeDir()).toBe('/tmp/clai-home')
+    })
+
+    it('should fall back to the account database when $HOME is unset', () => {
+      delete process.env.HOME
+      expect(getHomeDir()).toBe(userInfo().homedir)
+    })
+
+    it('should fall back when $HOME is empty', () => {
+      process.env.HOME = ''
+      expect(getHomeDir()).toBe(userInfo().homedir)
+    })
+
+    it('should still redact the home path when $HOME is unset', () => {
+      delete process.env.HOME
+      const home = userInfo().homedir
+      expect(redactPath(`${home}/projects/app`)).toBe('[REDACTED]/projects/app')
+    })
+
+    it('should read history without throwing when $HOME is unset', () => {
+      delete process.env.HOME
+      expect(Array.isArray(getShellHistory(3))).toBe(true)
+    })
+  })
+
   describe('Shell History', () => {
     it('should detect shell from SHELL env var', () => {
       const originalShell = process.env.SHELL
@@ -284,6 +543,129 @@ describe('Context Module', () => {
       // Should not throw, might be empty or have content
       expect(Array.isArray(history)).toBe(true)
     })
+
+    it('should read the same lines asynchronously as synchronously', async () => {
+      const dir = join(process.cwd(), 'test-history')
+      const file = join(dir, '.bash_history')
+      mkdirSync(dir, { recursive: true })
+      writeFileSync(file, 'ls\n\ncd src\ngit status\n  \nmake test\n')
+
+      try {
+        const sync = readHistoryTail(file, 3)
+        expect(sync).toEqual(['cd src', 'git status', 'make test'])
+        expect(await readHistoryTailAsync(file, 3)).toEqual(sync)
+        expect(await readHistoryTailAsync(join(dir, 'missing'), 3)).toEqual(
+          readHistoryTail(join(dir, 'missing'), 3)
+        )
+      } finally {
+        rmSync(dir, { recursive: true, force: true })
+      }
+    })
+
+    it('should return no lines when asked for none', async () => {
+      const dir = join(process.cwd(), 'test-history-zero')
+      const file = join(dir, '.bash_history')
+      mkdirSync(dir, { recursive: true })
+      writeFileSync(file, 'ls\ngit status\n')
+
+      try {
+        expect(readHistoryTail(file, 0)).toEqual([])
+        expect(await readHistoryTailAsync(file, 0)).toEqual([])
+      } finally {
+        rmSync(dir, { recursive: true, force: true })
+      }
+    })
+
+    it('should return a very long last entry complete, never partial', async () => {
+      const dir = join(process.cwd(), 'test-history-long')
+      const file = join(dir, '.bash_history')
+      const longCommand = `echo ${'x'.repeat(10000)} | wc -c`
+      mkdirSync(dir, { recursive: true })
+      writeFileSync(file, `ls\ngit status\n${longCommand}\n`)
+
+      try {
+        expect(readHistoryTail(file, 2)).toEqual(['git status', longCommand])
+        expect(await readHistoryTailAsync(file, 1)).toEqual([longCommand])
+      } finally {
+        rmSync(dir, { recursive: true, force: true })
+      }
+    })
+
+    it('should try the default file before framework locations', () => {
+      expect(getHistoryCandidates('zsh', '/home/u', {})).toEqual([
+        '/home/u/.zsh_history',
+        '/home/u/.zhistory',
+        '/home/u/.histfile',
+        '/home/u/.local/state/zsh/history',
+      ])
+      expect(getHistoryCandidates('bash', '/home/u', {})).toEqual([
+        '/home/u/.bash_history',
+        '/home/u/.bash_eternal_history',
+        '/home/u/.local/state/bash/history',
+      ])
+    })
+
+    it('should put HISTFILE and ZDOTDIR ahead of the home directory', () => {
+      const candidates = getHistoryCandidates('zsh', '/home/u', {
+        HISTFILE: '/tmp/session-history',
+        ZDOTDIR: '/home/u/.config/zsh',
+      })
+      expect(candidates.slice(0, 4)).toEqual([
+        '/tmp/session-history',
+        '/home/u/.config/zsh/.zsh_history',
+        '/home/u/.config/zsh/.zhistory',
+        '/home/u/.zsh_history',
+      ])
+    })
+
+    it('should not offer candidates for an unsupported shell', () => {
+      expect(getHistoryCandidates('tcsh', '/home/u', {})).toEqual([])
+      expect(getHistoryCandidates('bash', null, {})).toEqual([])
+    })
+
+    it('should keep only the history between the markers from a shell', () => {
+      const stdout = [
+        '[instant prompt] welcome back',
+        '__clai_history__',
+        '  git status',
+        'make test',
+        '__clai_history__',
+        'logout banner',
+      ].join('\n')
+      expect(extractShellHistory(stdout)).toEqual(['git status', 'make test'])
+      expect(extractShellHistory('banner only\n')).toEqual([])
+    })
+
+    it('should fall back to the next file when the default is missing or empty', async () => {
+      const home = join(process.cwd(), 'test-history-home')
+      const originalHome = process.env.HOME
+      const originalShell = process.env.SHELL
+      const originalHistfile = process.env.HISTFILE
+      const originalZdotdir = process.env.ZDOTDIR
+      mkdirSync(home, { recursive: true })
+      writeFileSync(join(home, '.zsh_history'), '\n')
+      writeFileSync(join(home, '.zhistory'), 'git pull\nmake\n')
+      writeFileSync(join(home, '.histfile'), 'ignored\n')
+      process.env.HOME = home
+      process.env.SHELL = '/bin/zsh'
+      delete process.env.HISTFILE
+      delete process.env.ZDOTDIR
+
+      try {
+        expect(getShellHistory(3)).toEqual(['git pull', 'make'])
+        expect(await getShellHistoryAsync(3)).toEqual(['git pull', 'make'])
+      } finally {
+        process.env.HOME = originalHome
+        process.env.SHELL = originalShell
+        if (originalHistfile !== undefined) {
+          process.env.HISTFILE = originalHistfile
+        }
+        if (originalZdotdir !== undefined) {
+          process.env.ZDOTDIR = originalZdotdir
+        }
+        rmSync(home, { recursive: true, force: true })
+      }
+    })
   })
 
   describe('Stdin', () => {
@@ -305,6 +687,36 @@ describe('Context Module', () => {
         console.log('Skipping stdin test - stdin is not a TTY')
       }
     }, 100) // Short timeout since this should be quick
+
+    it('should keep text input', () => {
+      const text = Buffer.from(
+        'line one\n\tindented\r\n\x1b[31mred\x1b[0m héllo\n'
+      )
+      expect(looksBinary(text)).toBe(false)
+      expect(decodeStdin(text)).toBe(text.toString('utf8'))
+    })
+
+    it('should keep empty input as empty', () => {
+      expect(looksBinary(Buffer.alloc(0))).toBe(false)
+      expect(decodeStdin(Buffer.alloc(0))).toBe('')
+    })
+
+    it('should omit binary input', () => {
+      // PNG signature followed by a chunk header (contains NUL bytes)
+      const png = Buffer.from([
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+        0x49, 0x48, 0x44, 0x52,
+      ])
+      expect(looksBinary(png)).toBe(true)
+      expect(decodeStdin(png)).toBe(BINARY_STDIN_MARKER)
+    })
+
+    it('should treat mostly invalid UTF-8 as binary even without NUL bytes', () => {
+      const garbage = Buffer.from(
+        Array.from({ length: 64 }, (_, i) => (i % 2 === 0 ? 0xff : 0x41))
+      )
+      expect(looksBinary(garbage)).toBe(true)
+    })
   })
 
   describe('gatherContext Integration', () => {
@@ -315,6 +727,7 @@ describe('Context Module', () => {
         maxHistory: 3,
         redactPaths: false,
         redactUsername: false,
+        mode: 'full',
       },
       safety: {
         confirmDangerous: true,
@@ -414,6 +827,136 @@ describe('Context Module', () => {
       expect(context.files.length).toBeLessThanOrEqual(2)
     }, 100)
 
+    it('should skip stdin when noStdin is set even if piped', async () => {
+      const originalIsTTY = process.stdin.isTTY
+      Object.defineProperty(process.stdin, 'isTTY', {
+        value: false,
+        writable: true,
+      })
+      const onSpy = vi.spyOn(process.stdin, 'on')
+
+      try {
+        const context = await gatherContext({ ...mockConfig, noStdin: true })
+        expect(context.stdin).toBeUndefined()
+        expect(onSpy).not.toHaveBeenCalledWith('data', expect.anything())
+      } finally {
+        onSpy.mockRestore()
+        Object.defineProperty(process.stdin, 'isTTY', { value: originalIsTTY })
+      }
+    })
+
+    it('should send no history with max-history = 0', async () => {
+      const home = join(process.cwd(), 'test-history-off')
+      const originalHome = process.env.HOME
+      const originalShell = process.env.SHELL
+      const originalHistfile = process.env.HISTFILE
+      mkdirSync(home, { recursive: true })
+      writeFileSync(join(home, '.bash_history'), 'cat ~/.secret\nls\n')
+      process.env.HOME = home
+      process.env.SHELL = '/bin/bash'
+      delete process.env.HISTFILE
+
+      try {
+        const context = await gatherContext({
+          ...mockConfig,
+          context: { ...mockConfig.context, maxHistory: 0, mode: 'history' },
+        })
+        expect(context.history).toEqual([])
+      } finally {
+        process.env.HOME = originalHome
+        process.env.SHELL = originalShell
+        if (originalHistfile !== undefined) {
+          process.env.HISTFILE = originalHistfile
+        }
+        rmSync(home, { recursive: true, force: true })
+      }
+    })
+
+    it('should only gather history in history mode', async () => {
+      const onSpy = vi.spyOn(process.stdin, 'on')
+
+      try {
+        const context = await gatherContext({
+          ...mockConfig,
+          context: { ...mockConfig.context, mode: 'history' },
+        })
+        expect(context.sections).toEqual(['history'])
+        expect(context.files).toEqual([])
+        expect(context.stdin).toBeUndefined()
+        expect(onSpy).not.toHaveBeenCalledWith('data', expect.anything())
+      } finally {
+        onSpy.mockRestore()
+      }
+    })
+
+    it('should include a well-formed timestamp when includeTime is set', async () => {
+      const context = await gatherContext({
+        ...mockConfig,
+        noStdin: true,
+        context: { ...mockConfig.context, includeTime: true },
+      })
+
+      const json = JSON.parse(JSON.stringify(context))
+      expect(json.time.local).toMatch(
+        /^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}[+-]\d{2}:\d{2}$/
+      )
+      expect(Number.isNaN(Date.parse(json.time.local))).toBe(false)
+      expect(json.time.timezone).toBeTruthy()
+    })
+
+    it('should omit the timestamp when includeTime is off', async () => {
+      const context = await gatherContext({
+        ...mockConfig,
+        noStdin: true,
+        context: { ...mockConfig.context, includeTime: false },
+      })
+
+      expect(context.time).toBeUndefined()
+    })
+
+    it('should bypass the system info cache with --no-cache-system-info', async () => {
+      const originalShell = process.env.SHELL
+      process.env.SHELL = '/bin/bash'
+
+      try {
+        getSystemInfo(false)
+        process.env.SHELL = '/usr/bin/fish'
+
+        const context = await gatherContext({
+          ...mockConfig,
+          noStdin: true,
+          noCacheSystemInfo: true,
+        })
+        expect(context.system.shell).toBe('fish')
+        expect(getSystemInfo(false).shell).toBe('bash')
+      } finally {
+        process.env.SHELL = originalShell
+        clearSystemCache()
+      }
+    })
+
+    it('should reflect an active VIRTUAL_ENV in the context', async () => {
+      const original = process.env.VIRTUAL_ENV
+      process.env.VIRTUAL_ENV = '/srv/app/.venv'
+
+      try {
+        const context = await gatherContext({
+          ...mockConfig,
+          noStdin: true,
+          context: { ...mockConfig.context, detectEnvironments: true },
+        })
+        expect(context.environments).toContain(
+          'python venv /srv/app/.venv (active)'
+        )
+      } finally {
+        if (original === undefined) {
+          delete process.env.VIRTUAL_ENV
+        } else {
+          process.env.VIRTUAL_ENV = original
+        }
+      }
+    })
+
     it('should handle CWD errors gracefully', async () => {
       // Mock process.cwd to throw
       const originalCwd = process.cwd
@@ -429,6 +972,292 @@ describe('Context Module', () => {
     })
   })
 
+  describe('Package Managers', () => {
+    const binDir = join(process.cwd(), 'test-fake-bin')
+    const otherDir = join(process.cwd(), 'test-fake-bin-2')
+
+    beforeEach(() => {
+      mkdirSync(binDir, { recursive: true })
+      mkdirSync(otherDir, { recursive: true })
+    })
+
+    afterEach(() => {
+      rmSync(binDir, { recursive: true, force: true })
+      rmSync(otherDir, { recursive: true, force: true })
+    })
+
+    it('should detect a fake brew on PATH', () => {
+      writeFileSync(join(binDir, 'brew'), '#!/bin/sh\n')
+      chmodSync(join(binDir, 'brew'), 0o755)
+
+      expect(detectPackageManagers(binDir)).toEqual(['brew'])
+    })
+
+    it('should search every PATH entry', () => {
+      writeFileSync(join(binDir, 'brew'), '#!/bin/sh\n')
+      chmodSync(join(binDir, 'brew'), 0o755)
+      writeFileSync(join(otherDir, 'apt'), '#!/bin/sh\n')
+      chmodSync(join(otherDir, 'apt'), 0o755)
+
+      expect(
+        detectPackageManagers([binDir, otherDir].join(delimiter))
+      ).toEqual(['apt', 'brew'])
+    })
+
+    it('should ignore non-executable files and directories', () => {
+      writeFileSync(join(binDir, 'brew'), 'not a program')
+      chmodSync(join(binDir, 'brew'), 0o644)
+      mkdirSync(join(binDir, 'apt'))
+
+      if (process.platform !== 'win32') {
+        expect(detectPackageManagers(binDir)).toEqual([])
+      }
+    })
+
+    it('should return nothing without a PATH', () => {
+      expect(detectPackageManagers('')).toEqual([])
+    })
+  })
+
+  describe('Current Time', () => {
+    it('should format local time with the UTC offset', () => {
+      const date = new Date(2026, 2, 14, 9, 5, 3)
+      const stamp = formatLocalTimestamp(date)
+
+      expect(stamp.startsWith('2026-03-14T09:05:03')).toBe(true)
+      expect(Date.parse(stamp)).toBe(date.getTime())
+    })
+
+    it('should prefer TZ for the timezone name', () => {
+      expect(detectTimezone({ TZ: 'America/New_York' })).toBe(
+        'America/New_York'
+      )
+      expect(detectTimezone({ TZ: ':Asia/Tokyo' })).toBe('Asia/Tokyo')
+    })
+
+    it('should fall back to a detected zone without TZ', () => {
+      expect(detectTimezone({})).toBeTruthy()
+    })
+  })
+
+  describe('Language Environments', () => {
+    const projectDir = join(process.cwd(), 'test-env-project')
+
+    beforeEach(() => {
+      mkdirSync(projectDir, { recursive: true })
+    })
+
+    afterEach(() => {
+      rmSync(projectDir, { recursive: true, force: true })
+    })
+
+    it('should report VIRTUAL_ENV, conda and nvm from the environment', () => {
+      const environments = detectEnvironments(
+        {
+          VIRTUAL_ENV: '/srv/app/.venv',
+          CONDA_DEFAULT_ENV: 'science',
+          NVM_BIN: '/home/u/.nvm/versions/node/v20.11.1/bin',
+        },
+        projectDir
+      )
+
+      expect(environments).toEqual([
+        'python venv /srv/app/.venv (active)',
+        'conda env science (active)',
+        'nvm node v20.11.1 (active)',
+      ])
+    })
+
+    it('should mention an unactivated .venv and project markers', () => {
+      mkdirSync(join(projectDir, '.venv'))
+      writeFileSync(join(projectDir, 'package.json'), '{}')
+
+      expect(detectEnvironments({}, projectDir)).toEqual([
+        'python venv .venv/ (not activated)',
+        'node project (package.json)',
+      ])
+    })
+
+    it('should find nothing in an empty directory', () => {
+      expect(detectEnvironments({}, projectDir)).toEqual([])
+    })
+  })
+
+  describe('Project Description', () => {
+    const projectDir = join(process.cwd(), 'test-project-description')
+
+    beforeEach(() => {
+      mkdirSync(projectDir, { recursive: true })
+    })
+
+    afterEach(() => {
+      rmSync(projectDir, { recursive: true, force: true })
+    })
+
+    it('should take the first README heading', () => {
+      writeFileSync(
+        join(projectDir, 'README.md'),
+        '[![CI](badge.svg)](ci)\n\n# Photo sorter for NAS backups\n\nMore text\n'
+      )
+
+      expect(readProjectDescription(projectDir)).toBe(
+        'Photo sorter for NAS backups'
+      )
+    })
+
+    it('should prefer the package.json or Cargo.toml description', () => {
+      writeFileSync(join(projectDir, 'README.md'), '# photo-sorter\n')
+      writeFileSync(
+        join(projectDir, 'Cargo.toml'),
+        '[package]\nname = "sorter"\ndescription = "Sorts photos by date"\n'
+      )
+      expect(readProjectDescription(projectDir)).toBe('Sorts photos by date')
+
+      writeFileSync(
+        join(projectDir, 'package.json'),
+        JSON.stringify({ description: 'Web UI for the sorter' })
+      )
+      expect(readProjectDescription(projectDir)).toBe('Web UI for the sorter')
+    })
+
+    it('should truncate long descriptions', () => {
+      writeFileSync(join(projectDir, 'README'), `${'a'.repeat(500)}\n`)
+
+      const description = readProjectDescription(projectDir)
+      expect(description).toHaveLength(MAX_PROJECT_DESCRIPTION)
+      expect(description!.endsWith('...')).toBe(true)
+      expect(readProjectDescription(projectDir, 10)).toBe('aaaaaaa...')
+    })
+
+    it('should find nothing without a README or manifest', () => {
+      expect(readProjectDescription(projectDir)).toBeUndefined()
+    })
+
+    it('should send the README description in the prompt when enabled', async () => {
+      writeFileSync(
+        join(projectDir, 'README.md'),
+        `# Log shipper ${'x'.repeat(300)}\n`
+      )
+      const config = {
+        provider: { default: 'openrouter', fallback: [] },
+        context: {
+          maxFiles: 10,
+          maxHistory: 0,
+          redactPaths: false,
+          redactUsername: false,
+          mode: 'full',
+          includeProjectDescription: true,
+        },
+        noStdin: true,
+      } as unknown as Config
+
+      const originalCwd = process.cwd()
+      process.chdir(projectDir)
+      try {
+        const context = await gatherContext(config)
+        const prompt = buildPrompt(context, 'list logs', 1)[1]!.content
+
+        const expected = `Log shipper ${'x'.repeat(MAX_PROJECT_DESCRIPTION - 15)}...`
+        expect(context.projectDescription).toBe(expected)
+        expect(prompt).toContain(`Project: ${expected}`)
+
+        const disabled = await gatherContext({
+          ...config,
+          context: { ...config.context, includeProjectDescription: false },
+        })
+        expect(disabled.projectDescription).toBeUndefined()
+      } finally {
+        process.chdir(originalCwd)
+      }
+    })
+  })
+
+  describe('summarizeContext', () => {
+    const context = {
+      system: {
+        osName: 'Linux',
+        osVersion: '6.1.0',
+        architecture: 'x64',
+        shell: 'zsh',
+        user: 'testuser',
+        totalMemoryMb: 8192,
+      },
+      cwd: '/home/test/project',
+      files: ['a.txt', 'b.txt', 'src'],
+      history: ['ls', 'cd src'],
+      stdin: 'x'.repeat(2048),
+      packageManagers: ['apt'],
+    }
+
+    it('should include the file count and prompt-size estimate', () => {
+      const summary = summarizeContext(context, 4321)
+
+      expect(summary).toContain('Files: 3')
+      expect(summary).toContain('Prompt size: ~4321 chars')
+    })
+
+    it('should describe system, history and stdin', () => {
+      const summary = summarizeContext(context, 0)
+
+      expect(summary).toContain('OS: Linux 6.1.0 (x64)')
+      expect(summary).toContain('Shell: zsh')
+      expect(summary).toContain('History: 2 line(s)')
+      expect(summary).toContain('Stdin: 2048 chars')
+      expect(summary).toContain('Package managers: apt')
+      expect(summary.endsWith('\n')).toBe(true)
+    })
+
+    it('should report missing stdin', () => {
+      const summary = summarizeContext({ ...context, stdin: undefined }, 0)
+      expect(summary).toContain('Stdin: none')
+    })
+  })
+
+  describe('selectTopN', () => {
+    const byNumber = (a: number, b: number) => a - b
+
+    it('should match a full sort at scale', () => {
+      const items = Array.from({ length: 50000 }, (_, i) => (i * 7919) % 50000)
+      const expected = [...items].sort(byNumber).slice(0, 100)
+      expect(selectTopN(items, 100, byNumber)).toEqual(expected)
+    })
+
+    it('should consume a generator without collecting it', () => {
+      let produced = 0
+      function* numbers() {
+        for (let i = 100000; i > 0; i--) {
+          produced++
+          yield i
+        }
+      }
+      expect(selectTopN(numbers(), 3, byNumber)).toEqual([1, 2, 3])
+      expect(produced).toBe(100000)
+    })
+
+    it('should return everything sorted when n exceeds the input', () => {
+      expect(selectTopN([3, 1, 2], 10, byNumber)).toEqual([1, 2, 3])
+    })
+
+    it('should return nothing for n <= 0', () => {
+      expect(selectTopN([3, 1, 2], 0, byNumber)).toEqual([])
+    })
+  })
+
+  describe('getContextSections', () => {
+    it('should include every section in full mode', () => {
+      expect(getContextSections('full')).toEqual([
+        'system',
+        'directory',
+        'history',
+        'stdin',
+      ])
+    })
+
+    it('should include only history in history mode', () => {
+      expect(getContextSections('history')).toEqual(['history'])
+    })
+  })
+
   describe('ContextError', () => {
     it('should create error with code', () => {
       const error = new ContextError('Test error', 5)
@@ -1,10 +1,19 @@
 import { describe, it, expect } from 'vitest'
-import { ClaiError, UsageError, InterruptError } from '../src/error/index.js'
+import {
+  ClaiError,
+  UsageError,
+  InterruptError,
+  toJsonError,
+  formatJsonError,
+  ExitCode,
+  exitCodeForCategory,
+} from '../src/error/index.js'
 import { ConfigError } from '../src/config/index.js'
 import { ContextError } from '../src/context/types.js'
-import { AIError } from '../src/ai/types.js'
+import { AIError, AITimeoutError } from '../src/ai/types.js'
 import { SafetyError } from '../src/safety/types.js'
 import { ExecutionError } from '../src/output/types.js'
+import { CancelledError } from '../src/ai/types.js'
 
 describe('ClaiError', () => {
   it('should have exit code and message', () => {
@@ -248,3 +257,111 @@ describe('Error hierarchy', () => {
     expect(aiError.cause).toBe(rootCause)
   })
 })
+
+describe('Exit codes', () => {
+  // Every logical outcome and the one documented code it maps to
+  const outcomes: [string, ClaiError, number][] = [
+    ['general error', new ClaiError('x'), 1],
+    ['usage error', new UsageError('x'), 2],
+    ['config error', new ConfigError('x'), 3],
+    ['context error', new ContextError('x'), 1],
+    ['api error', new AIError('x'), 4],
+    ['safety abort', new SafetyError('x'), 5],
+    ['interrupt', new InterruptError(), 130],
+    ['cancelled request', new CancelledError(), 130],
+  ]
+
+  it.each(outcomes)(
+    'should map a %s to its documented code',
+    (_, error, code) => {
+      expect(error.code).toBe(code)
+      expect(exitCodeForCategory(error.category)).toBe(code)
+    }
+  )
+
+  it('should document each code once', () => {
+    const codes = Object.values(ExitCode)
+    expect(new Set(codes).size).toBe(codes.length)
+    expect(ExitCode).toEqual({
+      Success: 0,
+      General: 1,
+      Usage: 2,
+      Config: 3,
+      Api: 4,
+      Safety: 5,
+      Interrupted: 130,
+    })
+  })
+
+  it('should not assign usage and safety the same code', () => {
+    expect(exitCodeForCategory('usage')).not.toBe(exitCodeForCategory('safety'))
+  })
+
+  it('should fall back to the general code for execution errors', () => {
+    expect(exitCodeForCategory('execution')).toBe(ExitCode.General)
+    expect(new ExecutionError('not found', 127).code).toBe(127)
+  })
+})
+
+describe('JSON error output', () => {
+  it.each([
+    [new ClaiError('general failure'), 'general', 1],
+    [new UsageError('bad arg'), 'usage', 2],
+    [new ConfigError('bad config'), 'config', 3],
+    [new ContextError('no cwd'), 'context', 1],
+    [new AIError('server error'), 'api', 4],
+    [new SafetyError('aborted'), 'safety', 5],
+    [new ExecutionError('not found', 127), 'execution', 127],
+    [new InterruptError(), 'interrupt', 130],
+  ])('should serialize %s', (error, category, exitCode) => {
+    expect(toJsonError(error)).toEqual({
+      error: error.message,
+      category,
+      exit_code: exitCode,
+    })
+  })
+
+  it('should include status code for API errors', () => {
+    const error = new AIError('Authentication error (401)', 401)
+    expect(toJsonError(error)).toEqual({
+      error: 'Authentication error (401)',
+      category: 'api',
+      exit_code: 4,
+      status_code: 401,
+    })
+  })
+
+  it('should report the timeout reason for API timeouts', () => {
+    expect(
+      toJsonError(new AITimeoutError('Timeout error (504)', 504))
+    ).toEqual({
+      error: 'Timeout error (504)',
+      category: 'api',
+      reason: 'timeout',
+      exit_code: 4,
+      status_code: 504,
+    })
+    expect(
+      toJsonError(new AIError('Authentication error (401)', 401))
+    ).not.toHaveProperty('reason')
+  })
+
+  it('should treat non-clai errors as general errors', () => {
+    expect(toJsonError(new Error('boom'))).toEqual({
+      error: 'boom',
+      category: 'general',
+      exit_code: 1,
+    })
+  })
+
+  it('should format as a single line of JSON', () => {
+    const line = formatJsonError(new AIError('rate limited', 429))
+    expect(line).not.toContain('\n')
+    expect(JSON.parse(line)).toEqual({
+      error: 'rate limited',
+      category: 'api',
+      exit_code: 4,
+      status_code: 429,
+    })
+  })
+})
@@ -6,10 +6,19 @@ import {
   ExecutionError,
   Errors,
   isRecursiveCall,
+  isInteractiveCommand,
   validateCommand,
   getShell,
+  resolveShell,
   executeCommand,
+  canExecuteInteractively,
+  writePreviewScript,
+  removePreviewScript,
+  quoteScriptPath,
+  shebangFor,
 } from '../src/output/index.js'
+import * as fs from 'fs'
+import * as os from 'os'
 
 describe('ExecutionError', () => {
   it('should have the correct name', () => {
@@ -55,6 +64,18 @@ describe('Errors factory functions', () => {
     expect(error.cause).toBe(cause)
   })
 
+  it('classifies each factory by kind', () => {
+    expect(Errors.emptyCommand().kind).toBe('rejected')
+    expect(Errors.recursiveCall().kind).toBe('rejected')
+    expect(Errors.spawnFailed('ls', new Error('x')).kind).toBe('spawn')
+    expect(Errors.shellNotFound('/bin/nope').kind).toBe('spawn')
+    expect(Errors.shellNotExecutable('/etc/passwd').code).toBe(126)
+    expect(Errors.commandNotFound('nope').kind).toBe('exit')
+    expect(Errors.permissionDenied('nope').kind).toBe('exit')
+    expect(Errors.signalKilled('SIGTERM').kind).toBe('signal')
+    expect(Errors.timeout(5).kind).toBe('timeout')
+  })
+
   it('shellNotFound returns error with code 127', () => {
     const error = Errors.shellNotFound('/bin/nonexistent')
     expect(error.code).toBe(127)
@@ -115,6 +136,40 @@ describe('getShell', () => {
   })
 })
 
+describe('resolveShell', () => {
+  const originalShell = process.env.SHELL
+
+  afterEach(() => {
+    if (originalShell === undefined) {
+      delete process.env.SHELL
+    } else {
+      process.env.SHELL = originalShell
+    }
+  })
+
+  it('keeps an executable SHELL', () => {
+    process.env.SHELL = '/bin/sh'
+    expect(resolveShell()).toEqual({ shell: '/bin/sh' })
+  })
+
+  it('falls back to /bin/sh with a warning for a bogus SHELL', () => {
+    process.env.SHELL = '/nonexistent/bin/bogus-shell'
+    const { shell, warning } = resolveShell()
+    expect(shell).toBe('/bin/sh')
+    expect(warning).toContain('SHELL=/nonexistent/bin/bogus-shell')
+  })
+
+  it('falls back when SHELL is a directory', () => {
+    process.env.SHELL = '/tmp'
+    expect(resolveShell().shell).toBe('/bin/sh')
+  })
+
+  it('leaves a bare shell name to the PATH lookup', () => {
+    process.env.SHELL = 'sh'
+    expect(resolveShell()).toEqual({ shell: 'sh' })
+  })
+})
+
 describe('isRecursiveCall', () => {
   describe('should detect recursive calls', () => {
     it('detects "clai" at start', () => {
@@ -182,6 +237,26 @@ describe('isRecursiveCall', () => {
   })
 })
 
+describe('isInteractiveCommand', () => {
+  it.each([
+    'vim foo.txt',
+    'sudo nano /etc/hosts',
+    'git log | less',
+    'cd /var/log && /usr/bin/top',
+    'TERM=xterm htop',
+    'ssh user@host',
+  ])('flags %s', (command) => {
+    expect(isInteractiveCommand(command)).toBe(true)
+  })
+
+  it.each(['ls', 'echo vim', 'cat top.txt', 'git log --oneline'])(
+    'does not flag %s',
+    (command) => {
+      expect(isInteractiveCommand(command)).toBe(false)
+    }
+  )
+})
+
 describe('validateCommand', () => {
   it('returns valid for normal commands', () => {
     expect(validateCommand('ls -la')).toEqual({ valid: true })
@@ -224,6 +299,21 @@ describe('validateCommand', () => {
   })
 })
 
+describe('canExecuteInteractively', () => {
+  it('should allow execution when stdin is a TTY', () => {
+    expect(canExecuteInteractively({ isTTY: true })).toBe(true)
+  })
+
+  it('should refuse execution when stdin is not a TTY', () => {
+    expect(canExecuteInteractively({ isTTY: false })).toBe(false)
+  })
+
+  it('should refuse execution when the TTY state is unknown', () => {
+    // Piped streams leave isTTY undefined
+    expect(canExecuteInteractively({})).toBe(false)
+  })
+})
+
 describe('executeCommand', () => {
   describe('success cases', () => {
     it('executes simple command and returns exit 0', async () => {
@@ -319,6 +409,76 @@ describe('executeCommand', () => {
     })
   })
 
+  describe('spawn failure vs non-zero exit', () => {
+    it('reports a successful command as ran with exit 0', async () => {
+      const result = await executeCommand('true', { inheritStdio: false })
+      expect(result).toEqual({ success: true, exitCode: 0 })
+    })
+
+    it('reports a non-zero exit as a result, not an error', async () => {
+      const result = await executeCommand('exit 3', { inheritStdio: false })
+      expect(result).toEqual({ success: true, exitCode: 3 })
+    })
+
+    it('reports a missing command as ran and exited 127', async () => {
+      const result = await executeCommand('nonexistentcmd123xyz', {
+        inheritStdio: false,
+      })
+      expect(result.success).toBe(false)
+      if (!result.success) {
+        expect(result.error.kind).toBe('exit')
+        expect(result.error.ran).toBe(true)
+      }
+    })
+
+    it('reports a non-existent shell as a spawn failure', async () => {
+      const result = await executeCommand('echo hi', {
+        shell: '/nonexistent/bin/sh',
+        inheritStdio: false,
+      })
+      expect(result.success).toBe(false)
+      if (!result.success) {
+        expect(result.error.kind).toBe('spawn')
+        expect(result.error.ran).toBe(false)
+        expect(result.error.code).toBe(127)
+        expect(result.error.message).toBe(
+          'Shell not found: /nonexistent/bin/sh'
+        )
+      }
+    })
+
+    it('runs with /bin/sh when SHELL is a bogus path', async () => {
+      const originalShell = process.env.SHELL
+      process.env.SHELL = '/nonexistent/bin/bogus-shell'
+      const warnings: string[] = []
+
+      try {
+        const result = await executeCommand('exit 7', {
+          inheritStdio: false,
+          onWarning: (message) => warnings.push(message),
+        })
+        expect(result).toEqual({ success: true, exitCode: 7 })
+        expect(warnings).toHaveLength(1)
+        expect(warnings[0]).toContain('/bin/sh')
+      } finally {
+        if (originalShell === undefined) {
+          delete process.env.SHELL
+        } else {
+          process.env.SHELL = originalShell
+        }
+      }
+    })
+
+    it('reports rejected commands as never run', async () => {
+      const result = await executeCommand('')
+      expect(result.success).toBe(false)
+      if (!result.success) {
+        expect(result.error.kind).toBe('rejected')
+        expect(result.error.ran).toBe(false)
+      }
+    })
+  })
+
   describe('edge cases', () => {
     it('handles very long commands', async () => {
       const longArg = 'x'.repeat(1000)
@@ -397,3 +557,45 @@ describe('executeCommand integration', () => {
     delete process.env.TEST_EXECUTE_VAR
   })
 })
+
+describe('preview script', () => {
+  it('should write the command under a shebang for the shell', () => {
+    const path = writePreviewScript(['ls -la', 'echo done'], '/bin/bash')
+
+    try {
+      expect(path.startsWith(os.tmpdir())).toBe(true)
+      expect(fs.readFileSync(path, 'utf-8')).toBe(
+        '#!/bin/bash\nls -la\necho done\n'
+      )
+      expect(fs.statSync(path).mode & 0o777).toBe(0o700)
+    } finally {
+      removePreviewScript(path)
+    }
+
+    expect(fs.existsSync(path)).toBe(false)
+  })
+
+  it('should look up a bare shell name through env', () => {
+    expect(shebangFor('zsh')).toBe('#!/usr/bin/env zsh')
+  })
+
+  it('should run as a script with the shebang shell', async () => {
+    const path = writePreviewScript(['exit 7'], '/bin/sh')
+
+    try {
+      const result = await executeCommand(quoteScriptPath(path), {
+        shell: '/bin/sh',
+        inheritStdio: false,
+      })
+      expect(result).toEqual({ success: true, exitCode: 7 })
+    } finally {
+      removePreviewScript(path)
+    }
+  })
+
+  it('should quote paths with spaces and quotes as one word', () => {
+    expect(quoteScriptPath("/tmp/it's here/command.sh")).toBe(
+      `'/tmp/it'\\''s here/command.sh'`
+    )
+  })
+})
@@ -0,0 +1,76 @@
+import { describe, it, expect, vi } from 'vitest'
+import { formatCommands, writeCommands } from '../src/output/index.js'
+
+describe('Output formats', () => {
+  const commands = ['ls -la', 'echo "hi there"']
+
+  describe('plain', () => {
+    it('should join commands without a trailing newline when piped', () => {
+      expect(formatCommands(commands, 'plain', { piped: true })).toBe(
+        'ls -la\necho "hi there"'
+      )
+    })
+
+    it('should annotate dry-run options on a terminal with one trailing newline', () => {
+      expect(formatCommands(commands, 'plain', { annotate: true })).toBe(
+        '# Generated 2 command(s):\n' +
+          '# Option 1:\nls -la\n\n' +
+          '# Option 2:\necho "hi there"\n'
+      )
+    })
+
+    it('should list dry-run options one per line when piped', () => {
+      expect(
+        formatCommands(commands, 'plain', { annotate: true, piped: true })
+      ).toBe('ls -la\necho "hi there"\n')
+    })
+
+    it('should write nothing for an empty dry-run listing', () => {
+      expect(formatCommands([], 'plain', { annotate: true })).toBe('')
+    })
+
+    it('should show a colored prompt marker on a terminal', () => {
+      const output = formatCommands(['ls'], 'plain')
+      expect(output).toBe('\x1b[32m\x1b[1m$\x1b[0m \x1b[36mls\x1b[0m\n')
+    })
+
+    it('should use red for dangerous commands on a terminal', () => {
+      const output = formatCommands(['rm -rf /tmp/x'], 'plain', {
+        dangerous: true,
+      })
+      expect(output).toBe(
+        '\x1b[31m\x1b[1m$\x1b[0m \x1b[31mrm -rf /tmp/x\x1b[0m\n'
+      )
+    })
+  })
+
+  it('should write json as a single object', () => {
+    expect(formatCommands(commands, 'json')).toBe(
+      '{"commands":["ls -la","echo \\"hi there\\""]}\n'
+    )
+  })
+
+  it('should write jsonl as one object per line', () => {
+    expect(formatCommands(commands, 'jsonl')).toBe(
+      '{"command":"ls -la"}\n{"command":"echo \\"hi there\\""}\n'
+    )
+  })
+
+  it('should write null format as NUL-terminated commands', () => {
+    expect(formatCommands(commands, 'null')).toBe('ls -la\0echo "hi there"\0')
+  })
+
+  it('should ignore annotations for machine-readable formats', () => {
+    expect(formatCommands(commands, 'json', { annotate: true })).toBe(
+      formatCommands(commands, 'json')
+    )
+  })
+
+  it('should write the formatted output to the given stream', () => {
+    const stream = { write: vi.fn() }
+    writeCommands(commands, 'jsonl', {}, stream as any)
+    expect(stream.write).toHaveBeenCalledWith(
+      '{"command":"ls -la"}\n{"command":"echo \\"hi there\\""}\n'
+    )
+  })
+})
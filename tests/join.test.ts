@@ -0,0 +1,29 @@
+import { describe, it, expect } from 'vitest'
+import { joinCommands, DEFAULT_COMMAND_JOIN } from '../src/ui/join.js'
+
+describe('joinCommands', () => {
+  const selected = ['make build', 'make test']
+
+  it('should default to && so a failure stops the chain', () => {
+    expect(DEFAULT_COMMAND_JOIN).toBe('&&')
+    expect(joinCommands(selected)).toBe('make build && make test')
+  })
+
+  it.each([
+    ['&&', 'make build && make test'],
+    [';', 'make build; make test'],
+    ['|', 'make build | make test'],
+  ] as const)('should join with %s', (separator, expected) => {
+    expect(joinCommands(selected, separator)).toBe(expected)
+  })
+
+  it('should group multi-line commands', () => {
+    expect(joinCommands(['cd /tmp\nls', 'pwd'], '&&')).toBe(
+      '{ cd /tmp\nls\n} && pwd'
+    )
+  })
+
+  it('should return a single command unchanged', () => {
+    expect(joinCommands(['cd /tmp\nls'], ';')).toBe('cd /tmp\nls')
+  })
+})
@@ -0,0 +1,81 @@
+import { describe, it, expect } from 'vitest'
+import {
+  DEFAULT_CONFIRM_KEYS,
+  enterAction,
+  formatKeyHints,
+  parseConfirmKey,
+  resolveConfirmKeys,
+  validateConfirmKeys,
+} from '../src/ui/keys.js'
+import { UserAction } from '../src/ui/types.js'
+
+describe('confirm keys', () => {
+  const yesNo = resolveConfirmKeys({
+    execute: 'y',
+    abort: 'n',
+    regenerate: 'c',
+  })
+
+  it('should default to e/a/r', () => {
+    expect(resolveConfirmKeys()).toEqual(DEFAULT_CONFIRM_KEYS)
+    expect(resolveConfirmKeys({ execute: 'y' })).toEqual({
+      execute: 'y',
+      abort: 'a',
+      regenerate: 'r',
+    })
+  })
+
+  it('should build the hint line from a y/n/c mapping', () => {
+    expect(formatKeyHints(yesNo)).toBe(
+      '↑↓ select  Enter confirm  y run  n cancel  c regenerate  Esc cancel'
+    )
+    expect(formatKeyHints(yesNo, false)).toBe(
+      '↑↓ select  Enter confirm  y run  n cancel  Esc cancel'
+    )
+  })
+
+  it.each([
+    ['y', UserAction.Execute],
+    ['Y', UserAction.Execute],
+    ['n', UserAction.Abort],
+    ['c', UserAction.Regenerate],
+    ['e', null],
+    ['r', null],
+  ])('should parse %s with a y/n/c mapping', (input, expected) => {
+    expect(parseConfirmKey(input, yesNo)).toBe(expected)
+  })
+
+  it('should make Enter execute unless output is the default', () => {
+    expect(enterAction()).toBe(UserAction.Execute)
+    expect(enterAction('execute')).toBe(UserAction.Execute)
+    expect(enterAction('output')).toBe(UserAction.Output)
+  })
+
+  it('should keep the execute key and say Enter prints with output', () => {
+    expect(parseConfirmKey('y', yesNo)).toBe(UserAction.Execute)
+    expect(formatKeyHints(yesNo, false, 'output')).toBe(
+      '↑↓ select  Enter print  y run  n cancel  Esc cancel'
+    )
+  })
+
+  it('should ignore the regenerate key when it is not offered', () => {
+    expect(parseConfirmKey('c', yesNo, false)).toBeNull()
+  })
+
+  it('should accept distinct single-character keys', () => {
+    expect(validateConfirmKeys(DEFAULT_CONFIRM_KEYS)).toBeNull()
+    expect(validateConfirmKeys(yesNo)).toBeNull()
+  })
+
+  it('should reject keys shared between actions (case-insensitive)', () => {
+    expect(
+      validateConfirmKeys({ execute: 'y', abort: 'Y', regenerate: 'r' })
+    ).toBe('execute and abort both use "Y"')
+  })
+
+  it.each(['', 'yes', ' ', '1'])('should reject the key "%s"', (key) => {
+    expect(
+      validateConfirmKeys({ execute: key, abort: 'n', regenerate: 'r' })
+    ).toContain('key for execute')
+  })
+})
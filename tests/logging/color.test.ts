@@ -0,0 +1,51 @@
+import { describe, it, expect } from 'vitest'
+import { shouldUseColor, colorSupport } from '../../src/logging/color.js'
+
+describe('colorSupport', () => {
+  it('classifies COLORTERM=truecolor as truecolor', () => {
+    expect(
+      colorSupport({ TERM: 'xterm-256color', COLORTERM: 'truecolor' }, true)
+    ).toBe('truecolor')
+    expect(colorSupport({ TERM: 'xterm', COLORTERM: '24bit' }, true)).toBe(
+      'truecolor'
+    )
+  })
+
+  it('classifies TERM=xterm-256color as ansi256', () => {
+    expect(colorSupport({ TERM: 'xterm-256color' }, true)).toBe('ansi256')
+    expect(colorSupport({ TERM: 'screen-256color' }, true)).toBe('ansi256')
+  })
+
+  it('classifies a -direct TERM as truecolor', () => {
+    expect(colorSupport({ TERM: 'xterm-direct' }, true)).toBe('truecolor')
+  })
+
+  it('falls back to ansi16 for other terminals', () => {
+    expect(colorSupport({ TERM: 'xterm' }, true)).toBe('ansi16')
+    expect(colorSupport({ TERM: 'vt100', COLORTERM: '1' }, true)).toBe(
+      'ansi16'
+    )
+  })
+
+  it('reports none off a TTY, with TERM=dumb or NO_COLOR', () => {
+    expect(colorSupport({ COLORTERM: 'truecolor' }, false)).toBe('none')
+    expect(colorSupport({ TERM: 'dumb' }, true)).toBe('none')
+    expect(
+      colorSupport({ TERM: 'xterm-256color', NO_COLOR: '1' }, true)
+    ).toBe('none')
+  })
+})
+
+describe('shouldUseColor', () => {
+  it('follows the TTY and TERM in auto mode', () => {
+    expect(shouldUseColor('auto', { TERM: 'xterm' }, true)).toBe(true)
+    expect(shouldUseColor('auto', { TERM: 'xterm' }, false)).toBe(false)
+    expect(shouldUseColor('auto', { TERM: 'dumb' }, true)).toBe(false)
+  })
+
+  it('lets NO_COLOR override always', () => {
+    expect(shouldUseColor('always', {}, false)).toBe(true)
+    expect(shouldUseColor('always', { NO_COLOR: '1' }, true)).toBe(false)
+    expect(shouldUseColor('never', { TERM: 'xterm' }, true)).toBe(false)
+  })
+})
@@ -1,5 +1,10 @@
 import { describe, it, expect, vi, beforeEach, afterEach } from 'vitest'
-import { Logger } from '../../src/logging/logger.js'
+import {
+  Logger,
+  createMemorySink,
+  logLevelFromName,
+  resolveLogLevel,
+} from '../../src/logging/logger.js'
 
 describe('Logger level filtering', () => {
   let stderrSpy: ReturnType<typeof vi.spyOn>
@@ -151,3 +156,87 @@ describe('Logger message formatting', () => {
     expect(stderrSpy).toHaveBeenCalledWith('[DEBUG] test debug\n')
   })
 })
+
+describe('Logger sinks', () => {
+  it('captures formatted lines in a memory sink', () => {
+    const { sink, lines } = createMemorySink()
+    const logger = new Logger('verbose', 'never', sink)
+
+    logger.warn('disk almost full')
+    logger.info('using provider openrouter')
+
+    expect(lines).toEqual([
+      '[WARN] disk almost full',
+      '[INFO] using provider openrouter',
+    ])
+  })
+
+  it('applies level filtering before the sink', () => {
+    const { sink, lines } = createMemorySink()
+    const logger = new Logger('normal', 'never', sink)
+
+    logger.error('e')
+    logger.warn('w')
+    logger.info('i')
+    logger.debug('d')
+
+    expect(lines).toEqual(['[ERROR] e', '[WARN] w'])
+  })
+
+  it('does not write to stderr when a custom sink is used', () => {
+    const stderrSpy = vi
+      .spyOn(process.stderr, 'write')
+      .mockImplementation(() => true)
+    try {
+      const { sink } = createMemorySink()
+      new Logger('verbose', 'never', sink).error('captured')
+      expect(stderrSpy).not.toHaveBeenCalled()
+    } finally {
+      stderrSpy.mockRestore()
+    }
+  })
+})
+
+describe('Log level names', () => {
+  it('maps --log-level names to log levels', () => {
+    expect(logLevelFromName('error')).toBe('error')
+    expect(logLevelFromName('warn')).toBe('normal')
+    expect(logLevelFromName('info')).toBe('info')
+    expect(logLevelFromName('debug')).toBe('verbose')
+    expect(logLevelFromName('trace')).toBe('verbose')
+  })
+
+  it('error level only shows errors', () => {
+    const { sink, lines } = createMemorySink()
+    const logger = new Logger('error', 'never', sink)
+    logger.error('e')
+    logger.warn('w')
+    logger.info('i')
+    expect(lines).toEqual(['[ERROR] e'])
+  })
+
+  it('info level shows info but not debug', () => {
+    const { sink, lines } = createMemorySink()
+    const logger = new Logger('info', 'never', sink)
+    logger.info('i')
+    logger.debug('d')
+    expect(lines).toEqual(['[INFO] i'])
+  })
+
+  it('falls back to quiet/verbose flags without --log-level', () => {
+    const base = { quiet: false, verbose: 0, debug: false }
+    expect(resolveLogLevel(base)).toBe('normal')
+    expect(resolveLogLevel({ ...base, quiet: true })).toBe('quiet')
+    expect(resolveLogLevel({ ...base, verbose: 2 })).toBe('verbose')
+    expect(resolveLogLevel({ ...base, debug: true })).toBe('verbose')
+  })
+
+  it('--log-level takes precedence over -v and -q', () => {
+    expect(
+      resolveLogLevel({ logLevel: 'error', quiet: false, verbose: 3, debug: false })
+    ).toBe('error')
+    expect(
+      resolveLogLevel({ logLevel: 'debug', quiet: true, verbose: 0, debug: false })
+    ).toBe('verbose')
+  })
+})
@@ -0,0 +1,80 @@
+// Integration tests for the `clai config` subcommand
+import { describe, it, expect, beforeEach, afterEach } from 'vitest'
+import { spawnSync } from 'child_process'
+import * as fs from 'fs'
+import * as path from 'path'
+import { resolve } from 'path'
+
+const CLI_PATH = resolve(__dirname, '../dist/main.js')
+
+describe('main.ts config subcommand', () => {
+  const xdgDir = path.join('/tmp', `clai-config-${process.pid}`)
+  const userConfig = path.join(xdgDir, 'clai', 'config.toml')
+
+  function run(args: string[]) {
+    return spawnSync('bun', [CLI_PATH, ...args], {
+      encoding: 'utf-8',
+      env: { ...process.env, MOCK_AI: '1', XDG_CONFIG_HOME: xdgDir },
+    })
+  }
+
+  beforeEach(() => {
+    fs.mkdirSync(xdgDir, { recursive: true })
+  })
+
+  afterEach(() => {
+    fs.rmSync(xdgDir, { recursive: true, force: true })
+  })
+
+  it('prints the config locations with config path', () => {
+    const result = run(['config', 'path'])
+
+    expect(result.status).toBe(0)
+    const lines = result.stdout.trim().split('\n')
+    expect(lines[0]).toBe('/etc/clai/config.toml')
+    expect(lines).toContain(userConfig)
+    expect(lines[lines.length - 1]).toMatch(/\.clai\.toml( \(found\))?$/)
+  })
+
+  it('marks files that exist', () => {
+    fs.mkdirSync(path.dirname(userConfig), { recursive: true })
+    fs.writeFileSync(userConfig, '[ui]\ncolor = "never"\n', { mode: 0o600 })
+
+    const result = run(['config', 'path'])
+
+    expect(result.stdout).toContain(`${userConfig} (found)\n`)
+  })
+
+  it('creates the user config with config init and reads values back', () => {
+    expect(run(['config', 'init']).status).toBe(0)
+    expect(fs.existsSync(userConfig)).toBe(true)
+
+    expect(run(['config', 'set', 'ui.color=never']).status).toBe(0)
+    const result = run(['config', 'get', 'ui.color'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('never\n')
+  })
+
+  it('prints the provider and model a hinted --model resolves to', () => {
+    fs.mkdirSync(path.dirname(userConfig), { recursive: true })
+    fs.writeFileSync(
+      userConfig,
+      '[[provider.model-provider-hints]]\nprefix = "local/"\nprovider = "ollama"\n',
+      { mode: 0o600 }
+    )
+
+    const result = run(['--model-info', '--model', 'local/llama3'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('')
+    expect(result.stderr).toContain('Provider: ollama\nModel: local/llama3\n')
+  })
+
+  it('still translates an instruction without a subcommand', () => {
+    const result = run(['do thing'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('echo "mock command"')
+  })
+})
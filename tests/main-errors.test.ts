@@ -2,6 +2,7 @@
 import { describe, it, expect } from 'vitest'
 import { spawn } from 'child_process'
 import { resolve } from 'path'
+import { VERSION } from '../src/version.js'
 
 const CLI_PATH = resolve(__dirname, '../dist/main.js')
 
@@ -24,7 +25,7 @@ describe('main.ts error handling', () => {
     })
   })
 
-  it('should exit with code 4 for AIError (missing API key)', (done) => {
+  it('should exit with code 3 for ConfigError (missing API key)', (done) => {
     const proc = spawn('bun', [CLI_PATH, 'test command'], {
       stdio: ['pipe', 'pipe', 'pipe'],
       env: {
@@ -40,8 +41,9 @@ describe('main.ts error handling', () => {
     })
 
     proc.on('close', (code) => {
-      expect(code).toBe(4)
-      expect(stderr).toContain('OpenRouter API key not configured')
+      expect(code).toBe(3)
+      expect(stderr).toContain("AI provider 'openrouter' is not usable")
+      expect(stderr).toContain('openrouter: no API key')
       done()
     })
   })
@@ -67,4 +69,24 @@ describe('main.ts error handling', () => {
       done()
     })
   })
+
+  it('should print version info as JSON for --version-json', (done) => {
+    const proc = spawn('bun', [CLI_PATH, '--version-json'], {
+      stdio: ['pipe', 'pipe', 'pipe'],
+    })
+
+    let stdout = ''
+    proc.stdout.on('data', (chunk) => {
+      stdout += chunk.toString()
+    })
+
+    proc.on('close', (code) => {
+      expect(code).toBe(0)
+      const info = JSON.parse(stdout)
+      expect(info.name).toBe('clai')
+      expect(info.version).toBe(VERSION)
+      expect(info.target).toBe(`${process.platform}-${process.arch}`)
+      done()
+    })
+  })
 })
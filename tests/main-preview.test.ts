@@ -0,0 +1,30 @@
+// Integration tests for --preview-script without a terminal
+import { describe, it, expect } from 'vitest'
+import { spawnSync } from 'child_process'
+import { readdirSync } from 'fs'
+import { tmpdir } from 'os'
+import { resolve } from 'path'
+
+const CLI_PATH = resolve(__dirname, '../dist/main.js')
+
+function previewDirs(): string[] {
+  return readdirSync(tmpdir()).filter((name) =>
+    name.startsWith('clai-preview-')
+  )
+}
+
+describe('main.ts --preview-script', () => {
+  it('prints the command and leaves no script behind when piped', () => {
+    const before = previewDirs()
+    const result = spawnSync(
+      'bun',
+      [CLI_PATH, '--preview-script', 'list files'],
+      { encoding: 'utf-8', env: { ...process.env, MOCK_AI: '1' } }
+    )
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('echo "mock command"')
+    expect(result.stderr).toContain('--preview-script needs a terminal')
+    expect(previewDirs()).toEqual(before)
+  })
+})
@@ -0,0 +1,57 @@
+// Integration tests for --show-raw / --raw-file and --explain-only
+import { describe, it, expect, afterEach } from 'vitest'
+import { spawnSync } from 'child_process'
+import * as fs from 'fs'
+import * as path from 'path'
+import { resolve } from 'path'
+
+const CLI_PATH = resolve(__dirname, '../dist/main.js')
+
+function run(args: string[]) {
+  return spawnSync('bun', [CLI_PATH, ...args], {
+    encoding: 'utf-8',
+    env: { ...process.env, MOCK_AI: '1' },
+  })
+}
+
+describe('main.ts raw model output', () => {
+  const rawFile = path.join('/tmp', `clai-raw-${process.pid}.txt`)
+
+  afterEach(() => {
+    fs.rmSync(rawFile, { force: true })
+  })
+
+  it('writes the raw response to stderr and only the command to stdout', () => {
+    const result = run(['--show-raw', '-o', '3', 'list files'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('echo "mock command 1"')
+    expect(result.stderr).toContain(
+      '{"commands":["echo \\"mock command 1\\"","echo \\"mock command 2\\"","echo \\"mock command 3\\""]}'
+    )
+  })
+
+  it('does not write the raw response with --quiet', () => {
+    const result = run(['--show-raw', '--quiet', 'list files'])
+
+    expect(result.stdout).toBe('echo "mock command"')
+    expect(result.stderr).not.toContain('mock command')
+  })
+
+  it('writes the raw response to --raw-file instead of stderr', () => {
+    const result = run(['--raw-file', rawFile, '-o', '2', 'list files'])
+
+    expect(result.stdout).toBe('echo "mock command 1"')
+    expect(result.stderr).not.toContain('"commands"')
+    expect(fs.readFileSync(rawFile, 'utf-8')).toContain('"commands"')
+  })
+})
+
+describe('main.ts --explain-only', () => {
+  it('prints the explanation of the given command to stdout', () => {
+    const result = run(['--explain-only', 'tar xzvf file.tar.gz'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('Mock explanation of: tar xzvf file.tar.gz\n')
+  })
+})
@@ -0,0 +1,84 @@
+// Integration tests for --record (audit trail)
+import { describe, it, expect, afterEach } from 'vitest'
+import { spawnSync } from 'child_process'
+import * as fs from 'fs'
+import * as path from 'path'
+import { resolve } from 'path'
+
+const CLI_PATH = resolve(__dirname, '../dist/main.js')
+
+function run(args: string[], input?: string) {
+  return spawnSync('bun', [CLI_PATH, ...args], {
+    encoding: 'utf-8',
+    env: { ...process.env, MOCK_AI: '1' },
+    input,
+  })
+}
+
+describe('main.ts --record', () => {
+  const recordFile = path.join('/tmp', `clai-record-${process.pid}.jsonl`)
+
+  afterEach(() => {
+    fs.rmSync(recordFile, { force: true })
+  })
+
+  it('appends one audit record per run', () => {
+    const result = run(['--record', recordFile, 'list files'])
+
+    expect(result.status).toBe(0)
+    expect(result.stdout).toBe('echo "mock command"')
+
+    const lines = fs.readFileSync(recordFile, 'utf-8').trim().split('\n')
+    expect(lines).toHaveLength(1)
+
+    const record = JSON.parse(lines[0]!)
+    expect(Number.isNaN(Date.parse(record.ts))).toBe(false)
+    expect(record).toMatchObject({
+      instruction: 'list files',
+      commands: ['echo "mock command"'],
+      command: 'echo "mock command"',
+      dangerous: false,
+      decision: 'printed',
+    })
+    expect(typeof record.provider).toBe('string')
+
+    run(['--record', recordFile, '--dry-run', 'list files'])
+    const records = fs.readFileSync(recordFile, 'utf-8').trim().split('\n')
+    expect(records).toHaveLength(2)
+    expect(JSON.parse(records[1]!).decision).toBe('dry-run')
+  })
+
+  it('appends one audit record per --batch line', () => {
+    const result = run(
+      ['--record', recordFile, '--batch'],
+      'list files\nshow disk usage\n'
+    )
+
+    expect(result.status).toBe(0)
+
+    const records = fs
+      .readFileSync(recordFile, 'utf-8')
+      .trim()
+      .split('\n')
+      .map((line) => JSON.parse(line))
+    expect(records).toHaveLength(2)
+    expect(records.map((record) => record.instruction)).toEqual([
+      'list files',
+      'show disk usage',
+    ])
+    for (const record of records) {
+      expect(record).toMatchObject({
+        commands: ['echo "mock command"'],
+        command: 'echo "mock command"',
+        dangerous: false,
+        decision: 'printed',
+      })
+    }
+  })
+
+  it('writes nothing without --record', () => {
+    run(['list files'])
+
+    expect(fs.existsSync(recordFile)).toBe(false)
+  })
+})
@@ -0,0 +1,54 @@
+import { describe, it, expect } from 'vitest'
+import { renderOptionsTable } from '../src/ui/output.js'
+
+describe('renderOptionsTable', () => {
+  const commands = ['ls -la', 'rm -rf build', 'find . -type f']
+
+  it('should align index, danger marker and command (color off)', () => {
+    const table = renderOptionsTable(commands, 1, {
+      dangerous: [false, true, false],
+    })
+
+    expect(table).toBe(
+      '  1.   ls -la\n' + '> 2. ! rm -rf build\n' + '  3.   find . -type f\n'
+    )
+  })
+
+  it('should pad indexes to the widest one', () => {
+    const many = Array.from({ length: 10 }, (_, i) => `echo ${i + 1}`)
+    const lines = renderOptionsTable(many, 9).trimEnd().split('\n')
+
+    expect(lines[0]).toBe('   1.   echo 1')
+    expect(lines[9]).toBe('> 10.   echo 10')
+    // Commands start in the same column on every row
+    const columns = lines.map((line) => line.indexOf('echo'))
+    expect(new Set(columns).size).toBe(1)
+  })
+
+  it('should show multi-line commands on one row', () => {
+    expect(renderOptionsTable(['cd /tmp\nls'], 0)).toBe('> 1.   cd /tmp; ls\n')
+  })
+
+  it('should truncate commands to the terminal width', () => {
+    const table = renderOptionsTable(['x'.repeat(100)], 0, { width: 40 })
+    const row = table.trimEnd()
+
+    expect(row.length).toBeLessThanOrEqual(40)
+    expect(row.endsWith('...')).toBe(true)
+  })
+
+  it('should only emit ANSI codes when color is on', () => {
+    const plain = renderOptionsTable(commands, 0, { dangerous: [true] })
+    const colored = renderOptionsTable(commands, 0, {
+      color: true,
+      dangerous: [true],
+    })
+
+    expect(plain).not.toContain('\x1b[')
+    expect(colored).toContain('\x1b[31m')
+  })
+
+  it('should render nothing for no commands', () => {
+    expect(renderOptionsTable([], 0)).toBe('')
+  })
+})
@@ -0,0 +1,43 @@
+import { describe, it, expect } from 'vitest'
+import {
+  MAX_REGENERATIONS,
+  canRegenerate,
+  buildRegenerateInstruction,
+} from '../src/ui/regenerate.js'
+
+describe('Regenerate action', () => {
+  it('should allow regenerating until the limit is reached', () => {
+    expect(canRegenerate(0)).toBe(true)
+    expect(canRegenerate(MAX_REGENERATIONS - 1)).toBe(true)
+    expect(canRegenerate(MAX_REGENERATIONS)).toBe(false)
+  })
+
+  it('should respect a custom limit', () => {
+    expect(canRegenerate(0, 0)).toBe(false)
+    expect(canRegenerate(1, 2)).toBe(true)
+  })
+
+  it('should stop a user who always regenerates after the limit', () => {
+    let regenerations = 0
+    // Mirrors the prompt loop: the key is only honored while allowed
+    while (canRegenerate(regenerations)) {
+      regenerations++
+    }
+    expect(regenerations).toBe(MAX_REGENERATIONS)
+  })
+
+  it('should list rejected commands in the regenerate instruction', () => {
+    const instruction = buildRegenerateInstruction('list files', [
+      'ls',
+      'ls -la',
+    ])
+
+    expect(instruction).toMatch(/^list files\n\n/)
+    expect(instruction).toContain('give different commands')
+    expect(instruction).toContain('- ls\n- ls -la')
+  })
+
+  it('should leave the instruction alone when nothing was rejected', () => {
+    expect(buildRegenerateInstruction('list files', [])).toBe('list files')
+  })
+})
@@ -0,0 +1,44 @@
+// Shell integration snippet tests
+
+import { describe, it, expect } from 'vitest'
+import {
+  shellIntegration,
+  INTEGRATION_SHELLS,
+  WIDGET_NAME,
+} from '../src/shell/index.js'
+
+describe('shellIntegration', () => {
+  it.each(INTEGRATION_SHELLS)(
+    'defines the widget and invokes clai for %s',
+    (shell) => {
+      const snippet = shellIntegration(shell)
+
+      expect(WIDGET_NAME).toBe('_clai_widget')
+      expect(snippet).toContain(`# clai shell integration for ${shell}`)
+      expect(snippet).toContain('clai --no-stdin --no-spinner --')
+      expect(snippet.endsWith('\n')).toBe(true)
+    }
+  )
+
+  it('binds a readline function for bash', () => {
+    const snippet = shellIntegration('bash')
+    expect(snippet).toContain('_clai_widget() {')
+    expect(snippet).toContain('"$READLINE_LINE"')
+    expect(snippet).toContain(`bind -x '"\\C-g": _clai_widget'`)
+  })
+
+  it('registers a zle widget for zsh', () => {
+    const snippet = shellIntegration('zsh')
+    expect(snippet).toContain('_clai_widget() {')
+    expect(snippet).toContain('"$BUFFER"')
+    expect(snippet).toContain('zle -N _clai_widget')
+    expect(snippet).toContain("bindkey '^G' _clai_widget")
+  })
+
+  it('defines a fish function bound to Ctrl+G', () => {
+    const snippet = shellIntegration('fish')
+    expect(snippet).toContain('function _clai_widget')
+    expect(snippet).toContain('commandline -r -- $cmd')
+    expect(snippet).toContain('bind \\cg _clai_widget')
+  })
+})
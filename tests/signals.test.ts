@@ -4,6 +4,11 @@ import {
   checkInterrupt,
   isTTY,
   isInteractive,
+  registerCleanup,
+  runCleanup,
+  restoreTerminal,
+  RawModeGuard,
+  cancellable,
 } from '../src/signals/index.js'
 
 describe('Signal handlers', () => {
@@ -189,3 +194,143 @@ describe('Signal handler behavior', () => {
     }
   })
 })
+
+describe('cancellable', () => {
+  it('should abort the signal of a running operation on SIGINT', async () => {
+    const exitSpy = vi.spyOn(process, 'exit').mockImplementation((() => {
+      // Empty implementation
+    }) as any)
+
+    try {
+      registerSignalHandlers()
+      const aborted = await cancellable(async (signal) => {
+        process.emit('SIGINT', 'SIGINT')
+        return signal.aborted
+      })
+
+      expect(aborted).toBe(true)
+    } finally {
+      exitSpy.mockRestore()
+    }
+  })
+})
+
+describe('Cleanup on interrupt', () => {
+  afterEach(() => {
+    runCleanup()
+  })
+
+  it('should run registered cleanup before exiting on SIGTERM', () => {
+    const order: string[] = []
+    const exitSpy = vi.spyOn(process, 'exit').mockImplementation(((
+      code: number
+    ) => {
+      order.push(`exit:${code}`)
+    }) as any)
+
+    try {
+      registerCleanup(() => order.push('cleanup'))
+      registerSignalHandlers()
+      process.emit('SIGTERM', 'SIGTERM')
+
+      expect(order).toEqual(['cleanup', 'exit:130'])
+    } finally {
+      exitSpy.mockRestore()
+    }
+  })
+
+  it('should not run unregistered cleanup', () => {
+    const fn = vi.fn()
+    const unregister = registerCleanup(fn)
+    unregister()
+
+    runCleanup()
+    expect(fn).not.toHaveBeenCalled()
+  })
+
+  it('should run remaining cleanup when one throws', () => {
+    const fn = vi.fn()
+    registerCleanup(() => {
+      throw new Error('boom')
+    })
+    registerCleanup(fn)
+
+    expect(() => runCleanup()).not.toThrow()
+    expect(fn).toHaveBeenCalledTimes(1)
+  })
+
+  it('should disable raw mode when restoring the terminal', () => {
+    const originalIsTTY = process.stdin.isTTY
+    const originalIsRaw = (process.stdin as any).isRaw
+    const originalSetRawMode = (process.stdin as any).setRawMode
+    const setRawMode = vi.fn()
+
+    Object.defineProperty(process.stdin, 'isTTY', { value: true, writable: true })
+    Object.defineProperty(process.stdin, 'isRaw', { value: true, writable: true })
+    ;(process.stdin as any).setRawMode = setRawMode
+
+    try {
+      restoreTerminal()
+      expect(setRawMode).toHaveBeenCalledWith(false)
+    } finally {
+      Object.defineProperty(process.stdin, 'isTTY', { value: originalIsTTY })
+      Object.defineProperty(process.stdin, 'isRaw', { value: originalIsRaw })
+      ;(process.stdin as any).setRawMode = originalSetRawMode
+    }
+  })
+})
+
+describe('RawModeGuard', () => {
+  function rawStream(isTTY = true) {
+    const stream = {
+      isTTY,
+      isRaw: true,
+      setRawMode: vi.fn((mode: boolean) => {
+        stream.isRaw = mode
+      }),
+    }
+    return stream
+  }
+
+  afterEach(() => {
+    runCleanup()
+  })
+
+  it('should disable raw mode on release', () => {
+    const stream = rawStream()
+    const guard = new RawModeGuard(stream)
+
+    guard.release()
+
+    expect(stream.setRawMode).toHaveBeenCalledWith(false)
+    expect(stream.isRaw).toBe(false)
+  })
+
+  it('should only disable raw mode once', () => {
+    const stream = rawStream()
+    const guard = new RawModeGuard(stream)
+
+    guard.release()
+    stream.isRaw = true
+    guard.release()
+
+    expect(stream.setRawMode).toHaveBeenCalledTimes(1)
+  })
+
+  it('should disable raw mode when interrupt cleanup runs', () => {
+    const stream = rawStream()
+    new RawModeGuard(stream)
+
+    runCleanup()
+
+    expect(stream.setRawMode).toHaveBeenCalledWith(false)
+  })
+
+  it('should not touch a non-TTY stream', () => {
+    const stream = rawStream(false)
+    const guard = new RawModeGuard(stream)
+
+    expect(() => guard.release()).not.toThrow()
+    expect(stream.setRawMode).not.toHaveBeenCalled()
+  })
+})
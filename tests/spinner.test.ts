@@ -0,0 +1,38 @@
+import { describe, it, expect } from 'vitest'
+import { isSpinnerEnabled } from '../src/ui/spinner.js'
+
+describe('isSpinnerEnabled', () => {
+  const flags = { noSpinner: false, quiet: false }
+
+  it('should be enabled by default', () => {
+    expect(isSpinnerEnabled(flags, {})).toBe(true)
+  })
+
+  it('should be disabled by --no-spinner', () => {
+    expect(isSpinnerEnabled({ ...flags, noSpinner: true }, {})).toBe(false)
+  })
+
+  it('should be disabled by --quiet', () => {
+    expect(isSpinnerEnabled({ ...flags, quiet: true }, {})).toBe(false)
+  })
+
+  it.each(['1', 'true', 'yes', 'on', 'anything'])(
+    'should be disabled by CLAI_NO_SPINNER=%s',
+    (value) => {
+      expect(isSpinnerEnabled(flags, { CLAI_NO_SPINNER: value })).toBe(false)
+    }
+  )
+
+  it.each(['0', 'false', 'no', 'off'])(
+    'should stay enabled with CLAI_NO_SPINNER=%s',
+    (value) => {
+      expect(isSpinnerEnabled(flags, { CLAI_NO_SPINNER: value })).toBe(true)
+    }
+  )
+
+  it('should stay disabled by --quiet even if the env var says otherwise', () => {
+    expect(
+      isSpinnerEnabled({ ...flags, quiet: true }, { CLAI_NO_SPINNER: '0' })
+    ).toBe(false)
+  })
+})
@@ -0,0 +1,27 @@
+import { describe, it, expect } from 'vitest'
+import { formatRunStats } from '../src/ui/output.js'
+
+describe('formatRunStats', () => {
+  it('should include provider/model, token count and duration', () => {
+    expect(
+      formatRunStats({
+        provider: 'openrouter',
+        model: 'qwen3-coder',
+        totalTokens: 342,
+        durationMs: 1234,
+      })
+    ).toBe('openrouter/qwen3-coder · 342 tokens · 1.2s')
+  })
+
+  it('should omit tokens when usage was not reported', () => {
+    expect(
+      formatRunStats({ provider: 'openrouter', model: 'm', durationMs: 80 })
+    ).toBe('openrouter/m · 0.1s')
+  })
+
+  it('should show the provider alone without a model', () => {
+    expect(
+      formatRunStats({ provider: 'mock', totalTokens: 0, durationMs: 0 })
+    ).toBe('mock · 0 tokens · 0.0s')
+  })
+})
@@ -0,0 +1,78 @@
+import { describe, it, expect } from 'vitest'
+import {
+  applyCommandTemplate,
+  validateCommandTemplate,
+} from '../src/output/template.js'
+import { checkSafety, isDangerous, loadPatterns } from '../src/safety/index.js'
+import { validateFileConfig } from '../src/config/index.js'
+import type { Config } from '../src/config/types.js'
+
+describe('applyCommandTemplate', () => {
+  it('should leave the command unchanged without a template', () => {
+    expect(applyCommandTemplate('ls -la')).toBe('ls -la')
+    expect(applyCommandTemplate('ls -la', '')).toBe('ls -la')
+  })
+
+  it('should wrap the command in the template', () => {
+    expect(applyCommandTemplate('make build', 'time {command}')).toBe(
+      'time make build'
+    )
+    expect(
+      applyCommandTemplate('make', 'nice -n 10 {command} && echo ok')
+    ).toBe('nice -n 10 make && echo ok')
+  })
+
+  it('should replace every placeholder', () => {
+    expect(applyCommandTemplate('ls', 'echo {command}; {command}')).toBe(
+      'echo ls; ls'
+    )
+  })
+
+  it('should group multi-line commands', () => {
+    expect(applyCommandTemplate('cd /tmp\nls', 'time {command}')).toBe(
+      'time { cd /tmp\nls\n}'
+    )
+  })
+})
+
+describe('validateCommandTemplate', () => {
+  it('should require the {command} placeholder', () => {
+    expect(validateCommandTemplate('time {command}')).toBeNull()
+    expect(validateCommandTemplate('time')).toBe('must contain {command}')
+  })
+
+  it('should reject a template without a placeholder on load', () => {
+    expect(() =>
+      validateFileConfig({
+        provider: { default: 'openrouter', fallback: [] },
+        providers: {},
+        postprocess: { commandTemplate: 'time' },
+      })
+    ).toThrow('postprocess.command-template: must contain {command}')
+  })
+})
+
+describe('command template and safety', () => {
+  const template = 'shred -u old.log; {command}'
+  const config = {
+    safety: {
+      confirmDangerous: true,
+      dangerousPatterns: [],
+      maxCommandLength: 4096,
+      maxCommandLines: 100,
+    },
+    postprocess: { commandTemplate: template },
+    force: false,
+  } as unknown as Config
+
+  it('should judge the generated command, not the wrapped one', () => {
+    const wrapped = applyCommandTemplate('ls -la', template)
+
+    expect(isDangerous(wrapped, loadPatterns(config))).toBe(true)
+    expect(checkSafety(['ls -la'], config).isDangerous).toBe(false)
+  })
+
+  it('should still flag a dangerous generated command', () => {
+    expect(checkSafety(['rm -rf /'], config).isDangerous).toBe(true)
+  })
+})
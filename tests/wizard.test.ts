@@ -0,0 +1,90 @@
+// First-run setup wizard tests (scripted answers, no terminal)
+
+import { describe, it, expect, vi } from 'vitest'
+import { runSetupWizard, type WizardIO } from '../src/wizard/index.js'
+
+function scriptedIO(answers: string[], confirmAnswer: boolean) {
+  const queue = [...answers]
+  const messages: string[] = []
+  const questions: string[] = []
+  const io: WizardIO = {
+    ask: async (question) => {
+      questions.push(question)
+      return queue.shift() ?? ''
+    },
+    confirm: async (question) => {
+      questions.push(question)
+      return confirmAnswer
+    },
+    say: (message) => messages.push(message),
+  }
+  return { io, messages, questions }
+}
+
+const CONFIG_PATH = '/home/u/.config/clai/config.toml'
+
+describe('runSetupWizard', () => {
+  it('saves the chosen provider and key when confirmed', async () => {
+    const { io, messages, questions } = scriptedIO(
+      ['openrouter', 'sk-or-test'],
+      true
+    )
+    const save = vi.fn(() => CONFIG_PATH)
+
+    const result = await runSetupWizard(io, 'openrouter', save, CONFIG_PATH)
+
+    expect(result).toEqual({
+      provider: 'openrouter',
+      apiKey: 'sk-or-test',
+      savedTo: CONFIG_PATH,
+    })
+    expect(save).toHaveBeenCalledWith([
+      'provider.default=openrouter',
+      'openrouter.api-key="sk-or-test"',
+    ])
+    expect(questions[2]).toBe(`Save to ${CONFIG_PATH} (mode 0600)?`)
+    expect(messages).toContain(`Saved to ${CONFIG_PATH}`)
+  })
+
+  it('uses the default provider for an empty answer', async () => {
+    const { io } = scriptedIO(['', 'sk-or-test'], false)
+
+    const result = await runSetupWizard(io, 'openrouter', vi.fn(), CONFIG_PATH)
+
+    expect(result?.provider).toBe('openrouter')
+  })
+
+  it('uses the key for this run only when saving is declined', async () => {
+    const { io, messages } = scriptedIO(['openrouter', ' sk-or-test '], false)
+    const save = vi.fn(() => CONFIG_PATH)
+
+    const result = await runSetupWizard(io, 'openrouter', save, CONFIG_PATH)
+
+    expect(result).toEqual({ provider: 'openrouter', apiKey: 'sk-or-test' })
+    expect(save).not.toHaveBeenCalled()
+    expect(messages).toContain('Not saved; the key is used for this run only.')
+  })
+
+  it('skips setup when no key is entered', async () => {
+    const { io, questions } = scriptedIO(['openrouter', ''], true)
+    const save = vi.fn(() => CONFIG_PATH)
+
+    await expect(
+      runSetupWizard(io, 'openrouter', save, CONFIG_PATH)
+    ).resolves.toBeNull()
+    expect(save).not.toHaveBeenCalled()
+    // Never asked to save
+    expect(questions).toHaveLength(2)
+  })
+
+  it('rejects an unknown provider', async () => {
+    const { io, messages } = scriptedIO(['acme', 'sk-test'], true)
+    const save = vi.fn(() => CONFIG_PATH)
+
+    await expect(
+      runSetupWizard(io, 'openrouter', save, CONFIG_PATH)
+    ).resolves.toBeNull()
+    expect(messages).toContain('Unknown provider: acme')
+    expect(save).not.toHaveBeenCalled()
+  })
+})